@@ -0,0 +1,4 @@
+fn main() {
+  // Embedded migrations must be refreshed whenever one is added or changed
+  println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,15 @@
+CREATE TABLE "sessions" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    last_used_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    expires_at timestamp NOT NULL,
+    revoked_at timestamp,
+    refresh_token_hash text UNIQUE NOT NULL,
+    -- Kept after rotating the refresh token to detect reuse of a stolen token
+    previous_refresh_token_hash text UNIQUE,
+    user_agent varchar(512),
+    ip_address varchar(64)
+);
+
+CREATE INDEX "sessions_user_id_idx" ON "sessions" (user_id);
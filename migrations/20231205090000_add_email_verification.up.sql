@@ -0,0 +1,14 @@
+ALTER TABLE "users" ADD COLUMN email_verified_at timestamp;
+
+CREATE TABLE "email_verifications" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    -- The email address to be verified, in case if the user changes it
+    email varchar(255) NOT NULL,
+    token_hash text UNIQUE NOT NULL,
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    expires_at timestamp NOT NULL,
+    used_at timestamp
+);
+
+CREATE INDEX "email_verifications_user_id_idx" ON "email_verifications" (user_id);
@@ -0,0 +1,10 @@
+CREATE TABLE "password_resets" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    token_hash text UNIQUE NOT NULL,
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    expires_at timestamp NOT NULL,
+    used_at timestamp
+);
+
+CREATE INDEX "password_resets_user_id_idx" ON "password_resets" (user_id);
@@ -0,0 +1,17 @@
+CREATE TABLE "totp_secrets" (
+    user_id bigint PRIMARY KEY REFERENCES "users"(id) ON DELETE CASCADE,
+    encrypted_secret text NOT NULL,
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    -- NULL until the user confirms the enrollment with a valid code
+    enabled_at timestamp,
+    last_used_step bigint
+);
+
+CREATE TABLE "recovery_codes" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    code_hash text NOT NULL,
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    used_at timestamp,
+    UNIQUE (user_id, code_hash)
+);
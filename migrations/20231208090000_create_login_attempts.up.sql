@@ -0,0 +1,6 @@
+CREATE TABLE "login_attempts" (
+    -- SHA-256 hash of the throttled account or IP address
+    key text PRIMARY KEY,
+    failures integer NOT NULL DEFAULT 0,
+    last_failed_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc')
+);
@@ -0,0 +1,14 @@
+CREATE TABLE "api_tokens" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    name varchar(64) NOT NULL,
+    -- First few characters of the token so users can tell their tokens apart
+    prefix varchar(16) NOT NULL,
+    token_hash text UNIQUE NOT NULL,
+    scopes text[] NOT NULL DEFAULT '{}',
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    expires_at timestamp,
+    last_used_at timestamp
+);
+
+CREATE INDEX "api_tokens_user_id_idx" ON "api_tokens" (user_id);
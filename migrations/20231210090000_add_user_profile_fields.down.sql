@@ -0,0 +1,4 @@
+ALTER TABLE "users"
+    DROP COLUMN bio,
+    DROP COLUMN location,
+    DROP COLUMN website;
@@ -0,0 +1,4 @@
+ALTER TABLE "users"
+    ADD COLUMN bio varchar(160),
+    ADD COLUMN location varchar(64),
+    ADD COLUMN website varchar(255);
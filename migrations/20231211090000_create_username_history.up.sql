@@ -0,0 +1,10 @@
+CREATE TABLE "username_history" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    user_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    old_name varchar(20) NOT NULL,
+    new_name varchar(20) NOT NULL,
+    changed_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX "username_history_user_id_idx" ON "username_history" (user_id);
+CREATE INDEX "username_history_old_name_idx" ON "username_history" (old_name);
@@ -0,0 +1,3 @@
+DROP INDEX "users_lower_name_idx";
+DROP INDEX "users_name_skeleton_idx";
+ALTER TABLE "users" DROP COLUMN name_skeleton;
@@ -0,0 +1,10 @@
+ALTER TABLE "users" ADD COLUMN name_skeleton varchar(30);
+
+-- Usernames are ASCII only, see `types::username::skeleton`
+UPDATE "users" SET name_skeleton = replace(replace(replace(
+    translate(lower(name), '01i34578.-_', 'olleastb'),
+    'rn', 'm'), 'vv', 'w'), 'cl', 'd');
+
+ALTER TABLE "users" ALTER COLUMN name_skeleton SET NOT NULL;
+CREATE INDEX "users_name_skeleton_idx" ON "users" (name_skeleton);
+CREATE INDEX "users_lower_name_idx" ON "users" (lower(name));
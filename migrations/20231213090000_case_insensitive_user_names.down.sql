@@ -0,0 +1,6 @@
+DROP INDEX "users_email_lower_key";
+DROP INDEX "users_name_lower_key";
+
+CREATE INDEX "users_lower_name_idx" ON "users" (lower(name));
+ALTER TABLE "users" ADD CONSTRAINT "users_email_key" UNIQUE (email);
+ALTER TABLE "users" ADD CONSTRAINT "users_name_key" UNIQUE (name);
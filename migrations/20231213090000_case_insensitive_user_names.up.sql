@@ -0,0 +1,12 @@
+-- This fails if there are users whose names or emails only differ
+-- by case, they have to be resolved manually before migrating.
+UPDATE "users"
+SET email = split_part(btrim(email), '@', 1) || '@' || lower(split_part(btrim(email), '@', 2))
+WHERE email IS NOT NULL;
+
+ALTER TABLE "users" DROP CONSTRAINT "users_name_key";
+ALTER TABLE "users" DROP CONSTRAINT "users_email_key";
+DROP INDEX "users_lower_name_idx";
+
+CREATE UNIQUE INDEX "users_name_lower_key" ON "users" (lower(name));
+CREATE UNIQUE INDEX "users_email_lower_key" ON "users" (lower(email));
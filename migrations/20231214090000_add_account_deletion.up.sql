@@ -0,0 +1,4 @@
+ALTER TABLE "users" ADD COLUMN deletion_requested_at timestamp;
+
+CREATE INDEX "users_deletion_requested_at_idx" ON "users" (deletion_requested_at)
+    WHERE deletion_requested_at IS NOT NULL;
@@ -0,0 +1,13 @@
+CREATE TABLE "data_exports" (
+    id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+    -- Kept after the user is purged so the archive can be cleaned up
+    user_id bigint REFERENCES "users"(id) ON DELETE SET NULL,
+    created_at timestamp NOT NULL DEFAULT(now() AT TIME ZONE 'utc'),
+    storage_key text,
+    completed_at timestamp,
+    failed_at timestamp,
+    expires_at timestamp,
+    downloaded_at timestamp
+);
+
+CREATE INDEX "data_exports_user_id_idx" ON "data_exports" (user_id);
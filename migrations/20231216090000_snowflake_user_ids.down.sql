@@ -0,0 +1,6 @@
+ALTER TABLE "users" ALTER COLUMN id DROP DEFAULT;
+ALTER TABLE "users" ALTER COLUMN id ADD GENERATED ALWAYS AS IDENTITY;
+SELECT setval(pg_get_serial_sequence('users', 'id'), (SELECT max(id) FROM "users"));
+
+DROP FUNCTION whim_next_id();
+DROP SEQUENCE "whim_id_seq";
@@ -0,0 +1,21 @@
+-- Snowflake IDs generated by the database, see `types::id::IdGenerator`
+-- for its layout. The last worker ID (1023) is reserved for the database.
+CREATE SEQUENCE "whim_id_seq";
+
+CREATE FUNCTION whim_next_id() RETURNS bigint AS $$
+DECLARE
+    -- Whim epoch in UNIX milliseconds
+    epoch bigint := 1700265168293;
+    worker_id bigint := 1023;
+    now_millis bigint;
+    seq bigint;
+BEGIN
+    SELECT nextval('whim_id_seq') % 1024 INTO seq;
+    SELECT floor(extract(epoch FROM clock_timestamp()) * 1000) INTO now_millis;
+    RETURN ((now_millis - epoch) << 20) | (worker_id << 10) | seq;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Existing users keep their IDs, only new users get snowflake IDs.
+ALTER TABLE "users" ALTER COLUMN id DROP IDENTITY;
+ALTER TABLE "users" ALTER COLUMN id SET DEFAULT whim_next_id();
@@ -0,0 +1,58 @@
+ALTER TABLE "users"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN updated_at TYPE timestamp USING updated_at AT TIME ZONE 'utc',
+    ALTER COLUMN email_verified_at TYPE timestamp USING email_verified_at AT TIME ZONE 'utc',
+    ALTER COLUMN deletion_requested_at TYPE timestamp USING deletion_requested_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "sessions"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN last_used_at TYPE timestamp USING last_used_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamp USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN revoked_at TYPE timestamp USING revoked_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc'),
+    ALTER COLUMN last_used_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "email_verifications"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamp USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN used_at TYPE timestamp USING used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "password_resets"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamp USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN used_at TYPE timestamp USING used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "totp_secrets"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN enabled_at TYPE timestamp USING enabled_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "recovery_codes"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN used_at TYPE timestamp USING used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "login_attempts"
+    ALTER COLUMN last_failed_at TYPE timestamp USING last_failed_at AT TIME ZONE 'utc',
+    ALTER COLUMN last_failed_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "api_tokens"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamp USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN last_used_at TYPE timestamp USING last_used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "username_history"
+    ALTER COLUMN changed_at TYPE timestamp USING changed_at AT TIME ZONE 'utc',
+    ALTER COLUMN changed_at SET DEFAULT (now() AT TIME ZONE 'utc');
+
+ALTER TABLE "data_exports"
+    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN completed_at TYPE timestamp USING completed_at AT TIME ZONE 'utc',
+    ALTER COLUMN failed_at TYPE timestamp USING failed_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamp USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN downloaded_at TYPE timestamp USING downloaded_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT (now() AT TIME ZONE 'utc');
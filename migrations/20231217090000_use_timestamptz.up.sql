@@ -0,0 +1,59 @@
+-- Existing timestamps were stored in UTC without a time zone.
+ALTER TABLE "users"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN updated_at TYPE timestamptz USING updated_at AT TIME ZONE 'utc',
+    ALTER COLUMN email_verified_at TYPE timestamptz USING email_verified_at AT TIME ZONE 'utc',
+    ALTER COLUMN deletion_requested_at TYPE timestamptz USING deletion_requested_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
+
+ALTER TABLE "sessions"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN last_used_at TYPE timestamptz USING last_used_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamptz USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN revoked_at TYPE timestamptz USING revoked_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now(),
+    ALTER COLUMN last_used_at SET DEFAULT now();
+
+ALTER TABLE "email_verifications"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamptz USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN used_at TYPE timestamptz USING used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
+
+ALTER TABLE "password_resets"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamptz USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN used_at TYPE timestamptz USING used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
+
+ALTER TABLE "totp_secrets"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN enabled_at TYPE timestamptz USING enabled_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
+
+ALTER TABLE "recovery_codes"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN used_at TYPE timestamptz USING used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
+
+ALTER TABLE "login_attempts"
+    ALTER COLUMN last_failed_at TYPE timestamptz USING last_failed_at AT TIME ZONE 'utc',
+    ALTER COLUMN last_failed_at SET DEFAULT now();
+
+ALTER TABLE "api_tokens"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamptz USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN last_used_at TYPE timestamptz USING last_used_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
+
+ALTER TABLE "username_history"
+    ALTER COLUMN changed_at TYPE timestamptz USING changed_at AT TIME ZONE 'utc',
+    ALTER COLUMN changed_at SET DEFAULT now();
+
+ALTER TABLE "data_exports"
+    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'utc',
+    ALTER COLUMN completed_at TYPE timestamptz USING completed_at AT TIME ZONE 'utc',
+    ALTER COLUMN failed_at TYPE timestamptz USING failed_at AT TIME ZONE 'utc',
+    ALTER COLUMN expires_at TYPE timestamptz USING expires_at AT TIME ZONE 'utc',
+    ALTER COLUMN downloaded_at TYPE timestamptz USING downloaded_at AT TIME ZONE 'utc',
+    ALTER COLUMN created_at SET DEFAULT now();
@@ -0,0 +1,8 @@
+CREATE TABLE "posts" (
+    id bigint PRIMARY KEY DEFAULT whim_next_id(),
+    author_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    content text NOT NULL
+);
+
+CREATE INDEX "posts_author_id_idx" ON "posts" (author_id);
@@ -0,0 +1,10 @@
+CREATE TABLE "follows" (
+    follower_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    followee_id bigint NOT NULL REFERENCES "users"(id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (follower_id, followee_id),
+    CONSTRAINT "follows_no_self_follow" CHECK (follower_id <> followee_id)
+);
+
+-- The primary key covers listing who a user follows
+CREATE INDEX "follows_followee_id_idx" ON "follows" (followee_id, follower_id);
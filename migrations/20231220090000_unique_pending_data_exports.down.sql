@@ -0,0 +1 @@
+DROP INDEX "data_exports_pending_user_id_key";
@@ -0,0 +1,12 @@
+-- Only the latest export of a user could have been built anyway
+UPDATE "data_exports" SET failed_at = now()
+WHERE completed_at IS NULL AND failed_at IS NULL
+  AND id NOT IN (
+    SELECT max(id) FROM "data_exports"
+    WHERE completed_at IS NULL AND failed_at IS NULL
+    GROUP BY user_id
+  );
+
+-- Concurrent requests must not build more than one export at a time
+CREATE UNIQUE INDEX "data_exports_pending_user_id_key" ON "data_exports" (user_id)
+    WHERE completed_at IS NULL AND failed_at IS NULL;
@@ -0,0 +1,13 @@
+CREATE OR REPLACE FUNCTION whim_next_id() RETURNS bigint AS $$
+DECLARE
+    -- Whim epoch in UNIX milliseconds
+    epoch bigint := 1700265168293;
+    worker_id bigint := 1023;
+    now_millis bigint;
+    seq bigint;
+BEGIN
+    SELECT nextval('whim_id_seq') % 1024 INTO seq;
+    SELECT floor(extract(epoch FROM clock_timestamp()) * 1000) INTO now_millis;
+    RETURN ((now_millis - epoch) << 20) | (worker_id << 10) | seq;
+END;
+$$ LANGUAGE plpgsql;
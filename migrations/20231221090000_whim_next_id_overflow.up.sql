@@ -0,0 +1,30 @@
+-- The sequence used to wrap around after 1024 IDs within the same
+-- millisecond and repeat IDs. It now holds the last ID's timestamp
+-- and sequence number (`millis << 10 | seq`) instead, so running out
+-- of sequence numbers borrows the next millisecond like `IdGenerator`.
+SELECT setval('whim_id_seq', 1, false);
+
+CREATE OR REPLACE FUNCTION whim_next_id() RETURNS bigint AS $$
+DECLARE
+    -- Whim epoch in UNIX milliseconds
+    epoch bigint := 1700265168293;
+    worker_id bigint := 1023;
+    now_ticks bigint;
+    ticks bigint;
+BEGIN
+    SELECT (floor(extract(epoch FROM clock_timestamp()) * 1000)::bigint - epoch) << 10
+        INTO now_ticks;
+
+    -- Catching up with the clock must not race with other sessions.
+    -- The lock is held until the transaction ends, which only slows
+    -- down concurrent inserts relying on this function since the
+    -- server generates IDs with `IdGenerator` itself.
+    PERFORM pg_advisory_xact_lock(hashtext('whim_next_id'));
+    ticks := nextval('whim_id_seq');
+    IF ticks < now_ticks THEN
+        ticks := setval('whim_id_seq', now_ticks);
+    END IF;
+
+    RETURN ((ticks >> 10) << 20) | (worker_id << 10) | (ticks & 1023);
+END;
+$$ LANGUAGE plpgsql;
@@ -3,8 +3,16 @@ use std::sync::Arc;
 use thiserror::Error;
 
 use crate::{
+  auth::password::DummyHash,
   config,
   database::{self, error::ErrorExt2},
+  http::rate_limit::RateLimiter,
+  mailer::{self, Mailer},
+  metrics::Metrics,
+  shutdown::BackgroundTasks,
+  storage::{self, Storage},
+  throttle::{self, LoginThrottle},
+  types::id::IdGenerator,
 };
 
 #[derive(Debug, Clone)]
@@ -12,6 +20,14 @@ pub struct App {
   pub config: Arc<config::Server>,
   pub primary_db: database::Pool,
   pub replica_db: Option<database::Pool>,
+  pub mailer: Arc<dyn Mailer>,
+  pub login_throttle: LoginThrottle,
+  pub rate_limiter: Arc<RateLimiter>,
+  pub storage: Arc<dyn Storage>,
+  pub id_generator: Arc<IdGenerator>,
+  pub metrics: Arc<Metrics>,
+  pub dummy_password_hash: DummyHash,
+  pub tasks: BackgroundTasks,
 }
 
 #[derive(Debug, Error)]
@@ -21,13 +37,19 @@ pub struct Error;
 impl App {
   #[tracing::instrument]
   pub async fn new(cfg: config::Server) -> Result<Self, Error> {
+    let metrics = Metrics::new().change_context(Error)?;
+
     let db_cfg = cfg.db();
-    let primary_db = database::Pool::new(&db_cfg, &db_cfg.primary())
-      .await
-      .change_context(Error)?;
+    let primary_db = database::Pool::new(
+      &db_cfg,
+      &db_cfg.primary(),
+      metrics.db_acquire_duration("primary"),
+    )
+    .await
+    .change_context(Error)?;
 
     let replica_db = if let Some(replica) = db_cfg.replica().as_ref() {
-      let pool = database::Pool::new(&db_cfg, replica)
+      let pool = database::Pool::new(&db_cfg, replica, metrics.db_acquire_duration("replica"))
         .await
         .change_context(Error)?;
 
@@ -36,10 +58,40 @@ impl App {
       None
     };
 
+    let mailer = mailer::from_config(cfg.mailer()).change_context(Error)?;
+    if matches!(cfg.mailer().transport(), config::MailTransport::Memory) {
+      tracing::warn!("Mailer is not configured, emails will not be delivered to users");
+    }
+
+    let storage = storage::from_config(cfg.storage());
+    if matches!(cfg.storage().backend(), config::StorageBackend::Memory) {
+      tracing::warn!("Storage is not configured, stored files will be lost after restarting");
+    }
+
+    let login_throttle = LoginThrottle::new(
+      throttle::from_config(cfg.throttle(), &primary_db),
+      cfg.throttle().login().clone(),
+    );
+
+    let dummy_password_hash = DummyHash::new(cfg.auth().password())
+      .await
+      .change_context(Error)?;
+
+    // Worker IDs are already validated when loading the config
+    let id_generator = Arc::new(IdGenerator::new(cfg.worker_id()));
+
     let app = Self {
       config: Arc::new(cfg),
       primary_db,
       replica_db,
+      mailer,
+      login_throttle,
+      rate_limiter: Arc::new(RateLimiter::new()),
+      storage,
+      id_generator,
+      metrics: Arc::new(metrics),
+      dummy_password_hash,
+      tasks: BackgroundTasks::new(),
     };
 
     Ok(app)
@@ -47,6 +99,19 @@ impl App {
 }
 
 impl App {
+  /// Closes the primary and replica database pools. It should be
+  /// called after the web server, background jobs and [tasks] have
+  /// stopped.
+  ///
+  /// [tasks]: BackgroundTasks::wait
+  #[tracing::instrument(skip_all)]
+  pub async fn close(&self) {
+    self.primary_db.close().await;
+    if let Some(replica) = self.replica_db.as_ref() {
+      replica.close().await;
+    }
+  }
+
   #[tracing::instrument(skip_all)]
   pub async fn db_write(&self) -> Result<database::PoolConnection, database::Error> {
     Ok(self.primary_db.get().await?)
@@ -58,7 +123,7 @@ impl App {
       match replica.get().await {
         Ok(conn) => return Ok(conn),
         // fallback
-        Err(err) if err.is_unhealthy() => {}
+        Err(err) if err.is_unhealthy() => self.metrics.record_replica_fallback(),
         Err(err) => return Err(err.into()),
       }
     }
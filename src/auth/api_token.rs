@@ -0,0 +1,39 @@
+use crate::util::Sensitive;
+
+/// Every API token starts with this prefix so they can be told apart
+/// from JWTs and be detected by secret scanners.
+pub const PREFIX: &str = "whim_";
+
+// Number of random characters kept visible after the prefix
+const VISIBLE_LEN: usize = 8;
+
+/// Generates a new API token.
+pub fn generate() -> Sensitive<String> {
+  Sensitive::new(format!("{PREFIX}{}", super::token::generate().as_str()))
+}
+
+/// Whether a bearer token looks like an API token.
+pub fn is_api_token(token: &str) -> bool {
+  token.starts_with(PREFIX)
+}
+
+/// Gets the visible part of an API token which is safe to store
+/// as is and display to the user.
+pub fn visible_prefix(token: &str) -> &str {
+  let end = PREFIX.len() + VISIBLE_LEN;
+  token.get(..end).unwrap_or(token)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let token = generate();
+    assert!(is_api_token(&token));
+    assert_eq!(visible_prefix(&token).len(), PREFIX.len() + VISIBLE_LEN);
+    assert!(token.starts_with(visible_prefix(&token)));
+    assert!(!is_api_token("eyJhbGciOiJIUzUxMiJ9"));
+  }
+}
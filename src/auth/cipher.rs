@@ -0,0 +1,104 @@
+use aes_gcm::{
+  aead::{Aead, AeadCore, KeyInit},
+  Aes256Gcm, Key, Nonce,
+};
+use error_stack::{Report, Result};
+use rand::rngs::OsRng;
+use sha2::Digest;
+use thiserror::Error;
+
+use crate::util::Sensitive;
+
+/// Secret encryption related errors
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error("failed to encrypt secret")]
+  Encrypt,
+  /// The ciphertext is malformed, tampered or encrypted
+  /// with a different key.
+  #[error("failed to decrypt secret")]
+  Decrypt,
+}
+
+/// Encrypts secrets that have to be stored in the database
+/// and read back later (like TOTP secrets) with AES-256-GCM.
+///
+/// Encrypted secrets are hex encoded with their
+/// randomly generated nonce prepended.
+pub struct Cipher(Aes256Gcm);
+
+impl Cipher {
+  const NONCE_LEN: usize = 12;
+
+  /// Creates a cipher where its 256-bit key is derived from
+  /// the SHA-256 hash of the configured secret key.
+  pub fn new(key: &str) -> Self {
+    let key = sha2::Sha256::digest(key.as_bytes());
+    Self(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+  }
+
+  pub fn encrypt(&self, plaintext: &[u8]) -> Result<String, Error> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = self
+      .0
+      .encrypt(&nonce, plaintext)
+      .map_err(|_| Report::new(Error::Encrypt))?;
+
+    let mut output = nonce.to_vec();
+    output.extend(ciphertext);
+    Ok(hex::encode(output))
+  }
+
+  pub fn decrypt(&self, encrypted: &str) -> Result<Sensitive<Vec<u8>>, Error> {
+    let bytes =
+      hex::decode(encrypted).map_err(|e| Report::new(e).change_context(Error::Decrypt))?;
+    if bytes.len() < Self::NONCE_LEN {
+      return Err(Report::new(Error::Decrypt));
+    }
+
+    let (nonce, ciphertext) = bytes.split_at(Self::NONCE_LEN);
+    self
+      .0
+      .decrypt(Nonce::from_slice(nonce), ciphertext)
+      .map(Sensitive::new)
+      .map_err(|_| Report::new(Error::Decrypt))
+  }
+}
+
+impl std::fmt::Debug for Cipher {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Cipher").finish_non_exhaustive()
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_roundtrip() {
+    let cipher = Cipher::new("correct horse battery staple");
+    let a = cipher.encrypt(b"hello").unwrap();
+    let b = cipher.encrypt(b"hello").unwrap();
+    assert_ne!(a, b);
+    assert_eq!(cipher.decrypt(&a).unwrap().into_inner(), b"hello");
+    assert_eq!(cipher.decrypt(&b).unwrap().into_inner(), b"hello");
+  }
+
+  #[test]
+  fn test_rejects_invalid_ciphertext() {
+    let cipher = Cipher::new("correct horse battery staple");
+    let encrypted = cipher.encrypt(b"hello").unwrap();
+
+    let other = Cipher::new("incorrect horse battery staple");
+    assert!(other.decrypt(&encrypted).is_err());
+
+    let mut tampered = hex::decode(&encrypted).unwrap();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(cipher.decrypt(&hex::encode(tampered)).is_err());
+
+    assert!(cipher.decrypt("").is_err());
+    assert!(cipher.decrypt("not hex").is_err());
+  }
+}
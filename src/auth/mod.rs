@@ -0,0 +1,6 @@
+pub mod api_token;
+pub mod cipher;
+pub mod password;
+pub mod recovery_code;
+pub mod token;
+pub mod totp;
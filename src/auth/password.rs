@@ -0,0 +1,293 @@
+use argon2::{
+  password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+  Algorithm, Argon2, Params, Version,
+};
+use error_stack::{Report, Result, ResultExt};
+use rand::rngs::OsRng;
+use sha2::Digest;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+use crate::{config::PasswordHashing, util::Sensitive};
+
+/// Password hashing related errors
+#[derive(Debug, Error)]
+pub enum Error {
+  /// The configured Argon2 cost parameters are rejected.
+  #[error("invalid password hashing parameters")]
+  InvalidParams,
+  /// Argon2 failed to hash or verify the password.
+  #[error("failed to hash password")]
+  Hash,
+  /// The stored password hash is neither in PHC string
+  /// format nor in the legacy SHA-512 format.
+  #[error("unknown password hash format")]
+  UnknownFormat,
+  /// The blocking task responsible for hashing the password
+  /// panicked or got cancelled.
+  #[error("password hashing task failed")]
+  Task,
+}
+
+/// The outcome of verifying a password against its stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+  /// The password does not match with the stored hash.
+  Invalid,
+  /// The password matches and the stored hash is up to date.
+  Valid,
+  /// The password matches but the stored hash is either
+  /// a legacy SHA-512 hash or hashed with outdated parameters.
+  ///
+  /// The caller should replace the stored hash with a new one.
+  NeedsRehash,
+}
+
+impl Verification {
+  /// Whether the password matches with the stored hash.
+  pub const fn is_valid(self) -> bool {
+    matches!(self, Self::Valid | Self::NeedsRehash)
+  }
+}
+
+/// Argon2id hash of a random password that nobody knows.
+///
+/// Passwords of unknown users are verified against it, so rejecting
+/// them takes as long as rejecting existing users and response times
+/// do not reveal which names and email addresses are registered.
+#[derive(Debug, Clone)]
+pub struct DummyHash(Arc<str>);
+
+impl DummyHash {
+  /// Hashes a random password with the configured parameters.
+  pub async fn new(cfg: &PasswordHashing) -> Result<Self, Error> {
+    let hash = hash(cfg, &super::token::generate()).await?;
+    Ok(Self(hash.into()))
+  }
+
+  /// Verifies a password against the dummy hash and discards the
+  /// result as it never matches.
+  #[tracing::instrument(skip_all)]
+  pub async fn verify(&self, cfg: &PasswordHashing, password: &Sensitive<String>) {
+    if let Err(error) = verify(cfg, "", password, &self.0).await {
+      tracing::warn!(?error, "failed to verify password against dummy hash");
+    }
+  }
+}
+
+/// Hashes a password into a PHC string formatted Argon2id hash
+/// with a randomly generated salt.
+///
+/// Hashing a password is CPU and memory intensive, so it will be
+/// performed in [Tokio's blocking thread pool](tokio::task::spawn_blocking)
+/// to avoid stalling the web server's workers.
+#[tracing::instrument(skip_all)]
+pub async fn hash(cfg: &PasswordHashing, password: &Sensitive<String>) -> Result<String, Error> {
+  let cfg = cfg.clone();
+  let password = password.clone();
+  tokio::task::spawn_blocking(move || hash_blocking(&cfg, &password))
+    .await
+    .change_context(Error::Task)?
+}
+
+/// Verifies a password against its stored hash in
+/// [Tokio's blocking thread pool](tokio::task::spawn_blocking).
+///
+/// `name` is only needed to verify legacy SHA-512 hashes
+/// as they were salted with the user's name.
+#[tracing::instrument(skip_all)]
+pub async fn verify(
+  cfg: &PasswordHashing,
+  name: &str,
+  password: &Sensitive<String>,
+  hash: &str,
+) -> Result<Verification, Error> {
+  let cfg = cfg.clone();
+  let name = name.to_string();
+  let password = password.clone();
+  let hash = hash.to_string();
+  tokio::task::spawn_blocking(move || verify_blocking(&cfg, &name, &password, &hash))
+    .await
+    .change_context(Error::Task)?
+}
+
+/// Blocking version of [`hash`].
+pub fn hash_blocking(cfg: &PasswordHashing, password: &str) -> Result<String, Error> {
+  let salt = SaltString::generate(&mut OsRng);
+  let hash = hasher(cfg)?
+    .hash_password(password.as_bytes(), &salt)
+    .map_err(|e| Report::new(e).change_context(Error::Hash))?;
+
+  Ok(hash.to_string())
+}
+
+/// Blocking version of [`verify`].
+pub fn verify_blocking(
+  cfg: &PasswordHashing,
+  name: &str,
+  password: &str,
+  hash: &str,
+) -> Result<Verification, Error> {
+  if is_legacy_hash(hash) {
+    return Ok(verify_legacy(name, password, hash));
+  }
+
+  let hash =
+    PasswordHash::new(hash).map_err(|e| Report::new(e).change_context(Error::UnknownFormat))?;
+  match hasher(cfg)?.verify_password(password.as_bytes(), &hash) {
+    Ok(()) if is_outdated(cfg, &hash)? => Ok(Verification::NeedsRehash),
+    Ok(()) => Ok(Verification::Valid),
+    Err(password_hash::Error::Password) => Ok(Verification::Invalid),
+    Err(e) => Err(Report::new(e).change_context(Error::Hash)),
+  }
+}
+
+fn hasher(cfg: &PasswordHashing) -> Result<Argon2<'static>, Error> {
+  let params = cfg
+    .params()
+    .map_err(|e| Report::new(e).change_context(Error::InvalidParams))?;
+
+  Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+fn is_outdated(cfg: &PasswordHashing, hash: &PasswordHash<'_>) -> Result<bool, Error> {
+  if hash.algorithm != Algorithm::Argon2id.ident() || hash.version != Some(Version::V0x13.into()) {
+    return Ok(true);
+  }
+
+  let current = cfg
+    .params()
+    .map_err(|e| Report::new(e).change_context(Error::InvalidParams))?;
+
+  let Ok(stored) = Params::try_from(hash) else {
+    return Ok(true);
+  };
+
+  Ok(
+    stored.m_cost() != current.m_cost()
+      || stored.t_cost() != current.t_cost()
+      || stored.p_cost() != current.p_cost(),
+  )
+}
+
+// Legacy password hashes are hex encoded unsalted SHA-512 hashes of
+// `{name}:{password}`. They're kept verifiable so existing users can
+// log in and get their password hash upgraded without a reset.
+const LEGACY_HASH_LEN: usize = 128;
+
+fn is_legacy_hash(hash: &str) -> bool {
+  hash.len() == LEGACY_HASH_LEN && hash.bytes().all(|v| v.is_ascii_hexdigit())
+}
+
+fn verify_legacy(name: &str, password: &str, hash: &str) -> Verification {
+  let mut hasher = sha2::Sha512::default();
+  hasher.update(format!("{name}:{password}"));
+
+  let attempt = hex::encode(hasher.finalize());
+  let expected = hash.to_ascii_lowercase();
+  if bool::from(attempt.as_bytes().ct_eq(expected.as_bytes())) {
+    Verification::NeedsRehash
+  } else {
+    Verification::Invalid
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Argon2's recommended parameters are too slow for tests
+  fn cheap_cfg() -> PasswordHashing {
+    PasswordHashing {
+      memory_cost_kib: 64,
+      iterations: 1,
+      parallelism: 1,
+    }
+  }
+
+  fn legacy_hash(name: &str, password: &str) -> String {
+    let mut hasher = sha2::Sha512::default();
+    hasher.update(format!("{name}:{password}"));
+    hex::encode(hasher.finalize())
+  }
+
+  #[tokio::test]
+  async fn test_dummy_hash() {
+    let cfg = cheap_cfg();
+    let dummy = DummyHash::new(&cfg).await.unwrap();
+
+    // it takes as long as verifying an up to date hash
+    let hash = PasswordHash::new(&dummy.0).unwrap();
+    assert!(!is_outdated(&cfg, &hash).unwrap());
+
+    let result = verify_blocking(&cfg, "", "", &dummy.0).unwrap();
+    assert_eq!(result, Verification::Invalid);
+  }
+
+  #[test]
+  fn test_hash_and_verify() {
+    let cfg = cheap_cfg();
+    let hash = hash_blocking(&cfg, "correct horse battery staple").unwrap();
+    assert!(hash.starts_with("$argon2id$v=19$"));
+
+    let result = verify_blocking(&cfg, "memo", "correct horse battery staple", &hash).unwrap();
+    assert_eq!(result, Verification::Valid);
+
+    let result = verify_blocking(&cfg, "memo", "incorrect horse battery staple", &hash).unwrap();
+    assert_eq!(result, Verification::Invalid);
+  }
+
+  #[test]
+  fn test_salts_are_unique() {
+    let cfg = cheap_cfg();
+    let a = hash_blocking(&cfg, "correct horse battery staple").unwrap();
+    let b = hash_blocking(&cfg, "correct horse battery staple").unwrap();
+    assert_ne!(a, b);
+  }
+
+  #[test]
+  fn test_outdated_params() {
+    let cfg = cheap_cfg();
+    let hash = hash_blocking(&cfg, "correct horse battery staple").unwrap();
+
+    let mut new_cfg = cheap_cfg();
+    new_cfg.iterations = 2;
+
+    let result = verify_blocking(&new_cfg, "memo", "correct horse battery staple", &hash).unwrap();
+    assert_eq!(result, Verification::NeedsRehash);
+  }
+
+  #[test]
+  fn test_legacy_hash() {
+    let cfg = cheap_cfg();
+    let hash = legacy_hash("memo", "correct horse battery staple");
+
+    let result = verify_blocking(&cfg, "memo", "correct horse battery staple", &hash).unwrap();
+    assert_eq!(result, Verification::NeedsRehash);
+
+    let result = verify_blocking(&cfg, "memo", "incorrect horse battery staple", &hash).unwrap();
+    assert_eq!(result, Verification::Invalid);
+
+    let result = verify_blocking(&cfg, "lemo", "correct horse battery staple", &hash).unwrap();
+    assert_eq!(result, Verification::Invalid);
+  }
+
+  #[test]
+  fn test_unknown_format() {
+    let cfg = cheap_cfg();
+    assert!(verify_blocking(&cfg, "memo", "password", "not a hash").is_err());
+    assert!(verify_blocking(&cfg, "memo", "password", "").is_err());
+  }
+
+  #[tokio::test]
+  async fn test_async_fns() {
+    let cfg = cheap_cfg();
+    let password = Sensitive::new("correct horse battery staple".to_string());
+
+    let hash = hash(&cfg, &password).await.unwrap();
+    let result = verify(&cfg, "memo", &password, &hash).await.unwrap();
+    assert_eq!(result, Verification::Valid);
+  }
+}
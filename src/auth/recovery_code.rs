@@ -0,0 +1,66 @@
+use rand::Rng;
+
+use crate::util::Sensitive;
+
+/// Number of recovery codes generated for a user at once.
+pub const COUNT: usize = 10;
+
+// Ambiguous characters like `0`/`o` and `1`/`l` are left out
+// as users may need to type these codes manually.
+const CHARSET: &[u8] = b"23456789abcdefghjkmnpqrstuvwxyz";
+const GROUP_LEN: usize = 5;
+
+/// Generates a set of one-time recovery codes formatted
+/// as `xxxxx-xxxxx` allowing users to log in when they
+/// lose access to their authenticator app.
+pub fn generate() -> Vec<Sensitive<String>> {
+  let mut rng = rand::rngs::OsRng;
+  (0..COUNT)
+    .map(|_| {
+      let mut code = String::with_capacity(GROUP_LEN * 2 + 1);
+      for i in 0..GROUP_LEN * 2 {
+        if i == GROUP_LEN {
+          code.push('-');
+        }
+        code.push(char::from(CHARSET[rng.gen_range(0..CHARSET.len())]));
+      }
+      Sensitive::new(code)
+    })
+    .collect()
+}
+
+/// Hashes a recovery code so it can be stored and looked up
+/// from the database. Letter case, whitespaces and dashes
+/// entered by the user are ignored.
+pub fn hash(code: &str) -> String {
+  let normalized = code
+    .chars()
+    .filter(|v| !v.is_whitespace() && *v != '-')
+    .collect::<String>()
+    .to_ascii_lowercase();
+
+  super::token::hash(&normalized)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let codes = generate();
+    assert_eq!(codes.len(), COUNT);
+    for code in &codes {
+      assert_eq!(code.len(), GROUP_LEN * 2 + 1);
+      assert_eq!(code.find('-'), Some(GROUP_LEN));
+    }
+    assert_ne!(codes[0].as_str(), codes[1].as_str());
+  }
+
+  #[test]
+  fn test_hash() {
+    assert_eq!(hash("abcde-fghjk"), hash("ABCDE FGHJK"));
+    assert_eq!(hash("abcde-fghjk"), hash("abcdefghjk"));
+    assert_ne!(hash("abcde-fghjk"), hash("abcde-fghjm"));
+  }
+}
@@ -0,0 +1,63 @@
+use rand::RngCore;
+use sha2::Digest;
+use std::time::Duration;
+
+use crate::{types::Timestamp, util::Sensitive};
+
+/// Length of the randomly generated part of a token in bytes.
+pub const TOKEN_BYTES: usize = 32;
+
+/// Generates an opaque token with 256 bits of entropy
+/// encoded as a hexadecimal string.
+///
+/// Opaque tokens (refresh tokens for example) must never be stored
+/// as is. Store their [hash](hash) instead.
+pub fn generate() -> Sensitive<String> {
+  let mut bytes = [0u8; TOKEN_BYTES];
+  rand::rngs::OsRng.fill_bytes(&mut bytes);
+  Sensitive::new(hex::encode(bytes))
+}
+
+/// Hashes an opaque token with SHA-256 so it can be stored
+/// and looked up from the database.
+///
+/// A slow password hashing function is not needed because generated
+/// tokens are long enough to make brute-force attacks infeasible.
+pub fn hash(token: &str) -> String {
+  let mut hasher = sha2::Sha256::new();
+  hasher.update(token.as_bytes());
+  hex::encode(hasher.finalize())
+}
+
+/// Calculates when a token issued right now will expire.
+pub fn expiry(lifetime: Duration) -> Timestamp {
+  Timestamp::now().saturating_add(lifetime)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let a = generate();
+    let b = generate();
+    assert_eq!(a.len(), TOKEN_BYTES * 2);
+    assert_ne!(a.as_str(), b.as_str());
+  }
+
+  #[test]
+  fn test_hash() {
+    let token = generate();
+    assert_eq!(hash(&token), hash(&token));
+    assert_ne!(hash(&token), hash(&generate()));
+    assert_ne!(hash(&token), token.as_str());
+  }
+
+  #[test]
+  fn test_expiry() {
+    let now = Timestamp::now();
+    assert!(expiry(Duration::from_secs(60)) > now);
+    assert_eq!(expiry(Duration::MAX), Timestamp::MAX);
+  }
+}
@@ -0,0 +1,171 @@
+//! Time-based one-time passwords ([RFC 6238]) compatible
+//! with most authenticator apps.
+//!
+//! [RFC 6238]: https://datatracker.ietf.org/doc/html/rfc6238
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use subtle::ConstantTimeEq;
+
+use crate::util::Sensitive;
+
+/// Length of a generated TOTP secret in bytes.
+pub const SECRET_BYTES: usize = 20;
+/// Number of digits of a TOTP code.
+pub const DIGITS: u32 = 6;
+/// How long (in seconds) a TOTP code is valid.
+pub const PERIOD_SECS: i64 = 30;
+
+// Accepts codes from one time step before and after the current
+// time step to tolerate clock skew of the user's device.
+const ALLOWED_SKEW_STEPS: i64 = 1;
+
+/// Generates a new random TOTP secret.
+pub fn generate_secret() -> Sensitive<Vec<u8>> {
+  let mut secret = vec![0u8; SECRET_BYTES];
+  rand::rngs::OsRng.fill_bytes(&mut secret);
+  Sensitive::new(secret)
+}
+
+/// Encodes a TOTP secret into unpadded base32, the format
+/// authenticator apps expect when entered manually.
+pub fn encode_secret(secret: &[u8]) -> Sensitive<String> {
+  Sensitive::new(BASE32_NOPAD.encode(secret))
+}
+
+/// Builds an `otpauth://` URI which can be rendered
+/// as a QR code for authenticator apps to scan.
+pub fn uri(issuer: &str, account: &str, secret: &[u8]) -> Sensitive<String> {
+  let issuer = percent_encode(issuer);
+  let account = percent_encode(account);
+  Sensitive::new(format!(
+    "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={PERIOD_SECS}",
+    secret = encode_secret(secret).as_str(),
+  ))
+}
+
+/// Gets the time step of a UNIX timestamp (in seconds).
+pub const fn time_step(unix_secs: i64) -> i64 {
+  unix_secs.div_euclid(PERIOD_SECS)
+}
+
+/// Generates the TOTP code of a time step.
+pub fn code_at(secret: &[u8], step: i64) -> u32 {
+  #[allow(clippy::expect_used)]
+  let mut mac = Hmac::<sha1::Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+  mac.update(&step.to_be_bytes());
+
+  // Dynamic truncation from RFC 4226
+  let hash = mac.finalize().into_bytes();
+  let offset = usize::from(hash[hash.len() - 1] & 0xf);
+  let binary = u32::from_be_bytes([
+    hash[offset] & 0x7f,
+    hash[offset + 1],
+    hash[offset + 2],
+    hash[offset + 3],
+  ]);
+  binary % 10u32.pow(DIGITS)
+}
+
+/// Verifies a TOTP code at the given UNIX timestamp (in seconds).
+///
+/// Codes from time steps at or before `last_used_step` are rejected
+/// so an intercepted code cannot be replayed. It returns the time
+/// step of the matched code which must be stored as the new
+/// `last_used_step` once the code is accepted.
+pub fn verify(
+  secret: &[u8],
+  code: &str,
+  unix_secs: i64,
+  last_used_step: Option<i64>,
+) -> Option<i64> {
+  let code = code.trim();
+  if code.len() != DIGITS as usize || !code.bytes().all(|v| v.is_ascii_digit()) {
+    return None;
+  }
+
+  let current = time_step(unix_secs);
+  let mut matched = None;
+  for step in (current - ALLOWED_SKEW_STEPS)..=(current + ALLOWED_SKEW_STEPS) {
+    let expected = format!("{:0width$}", code_at(secret, step), width = DIGITS as usize);
+    // Every step is checked regardless to keep verification in constant time
+    if bool::from(expected.as_bytes().ct_eq(code.as_bytes())) && matched.is_none() {
+      matched = Some(step);
+    }
+  }
+
+  matched.filter(|step| last_used_step.map_or(true, |last| *step > last))
+}
+
+fn percent_encode(value: &str) -> String {
+  // `form_urlencoded` encodes spaces as `+` which is not valid in the
+  // label part. A literal `+` is always encoded as `%2B` beforehand.
+  url::form_urlencoded::byte_serialize(value.as_bytes())
+    .collect::<String>()
+    .replace('+', "%20")
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Test vectors from RFC 6238 (truncated to 6 digits)
+  const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+  #[test]
+  fn test_code_at() {
+    assert_eq!(code_at(RFC_SECRET, time_step(59)), 287_082);
+    assert_eq!(code_at(RFC_SECRET, time_step(1_111_111_109)), 81_804);
+    assert_eq!(code_at(RFC_SECRET, time_step(1_234_567_890)), 5_924);
+    assert_eq!(code_at(RFC_SECRET, time_step(2_000_000_000)), 279_037);
+  }
+
+  #[test]
+  fn test_verify() {
+    let now = 1_111_111_109;
+    assert_eq!(
+      verify(RFC_SECRET, "081804", now, None),
+      Some(time_step(now))
+    );
+    assert_eq!(
+      verify(RFC_SECRET, " 081804 ", now, None),
+      Some(time_step(now))
+    );
+
+    // within the allowed clock skew
+    assert_eq!(
+      verify(RFC_SECRET, "081804", now + PERIOD_SECS, None),
+      Some(time_step(now))
+    );
+    assert_eq!(
+      verify(RFC_SECRET, "081804", now + PERIOD_SECS * 2, None),
+      None
+    );
+
+    assert_eq!(verify(RFC_SECRET, "000000", now, None), None);
+    assert_eq!(verify(RFC_SECRET, "81804", now, None), None);
+    assert_eq!(verify(RFC_SECRET, "08180a", now, None), None);
+  }
+
+  #[test]
+  fn test_replay() {
+    let now = 1_111_111_109;
+    let step = verify(RFC_SECRET, "081804", now, None).unwrap();
+    assert_eq!(verify(RFC_SECRET, "081804", now, Some(step)), None);
+    assert_eq!(
+      verify(RFC_SECRET, "081804", now, Some(step - 1)),
+      Some(step)
+    );
+  }
+
+  #[test]
+  fn test_uri() {
+    let secret = generate_secret();
+    let encoded = encode_secret(secret.as_ref());
+    let uri = uri("Whim Server", "memo", secret.as_ref());
+    assert!(uri.starts_with("otpauth://totp/Whim%20Server:memo?"));
+    assert!(uri.contains(&format!("secret={}", encoded.as_str())));
+    assert!(uri.contains("issuer=Whim%20Server"));
+  }
+}
@@ -1,28 +1,152 @@
-use actix_web::{middleware::ErrorHandlers, web, App, HttpServer};
+use actix_web::{dev::ServerHandle, middleware::ErrorHandlers, web, App, HttpServer};
+use error_stack::{Result, ResultExt};
+use thiserror::Error;
 use tracing_actix_web::TracingLogger;
-use whim::config;
+use whim::config::{self, ListenAddr};
+
+#[derive(Debug, Error)]
+#[error("Failed to start the server")]
+struct StartError;
 
 #[tokio::main]
-async fn main() {
-  tracing_subscriber::fmt()
-    .pretty()
-    .with_max_level(tracing::Level::DEBUG)
-    .init();
+async fn main() -> Result<(), StartError> {
+  let config = config::Server::from_env().change_context(StartError)?;
+  let telemetry = whim::telemetry::init(config.telemetry()).change_context(StartError)?;
+
+  let app = whim::App::new(config).await.change_context(StartError)?;
+
+  let shutdown = app.tasks.shutdown_token().clone();
+  let jobs = whim::jobs::spawn(&app, &shutdown);
+
+  let config = app.config.clone();
+  let http = config.http();
+  let tls = http
+    .tls()
+    .map(whim::http::tls::load)
+    .transpose()
+    .change_context(StartError)?;
+
+  let metrics = config.metrics();
+  let public_metrics = metrics.enabled() && metrics.listen().is_none();
 
-  let config = config::Server::from_env().unwrap();
-  let app = whim::App::new(config).await.unwrap();
+  let payload_limit = http.payload_limit();
+  let json_limit = http.json_limit();
 
-  HttpServer::new(move || {
+  let connections = whim::shutdown::Connections::new();
+  let factory_app = app.clone();
+  let mut server = HttpServer::new(move || {
     App::new()
-      .app_data(web::Data::new(app.clone()))
+      .app_data(web::Data::new(factory_app.clone()))
+      .app_data(web::PayloadConfig::new(payload_limit))
+      .app_data(web::JsonConfig::default().limit(json_limit))
+      .wrap(whim::http::rate_limit::RateLimit)
+      .wrap(whim::http::metrics::RequestMetrics)
       .wrap(TracingLogger::<whim::http::util::QuieterRootSpanBuilder>::new())
       .wrap(ErrorHandlers::new().default_handler(whim::http::util::handle_actix_web_error))
       .configure(whim::http::controllers::configure)
+      .configure(|cfg| {
+        if public_metrics {
+          whim::http::controllers::configure_metrics(cfg);
+        }
+      })
+  })
+  .on_connect(connections.on_connect())
+  .keep_alive(http.keep_alive())
+  .shutdown_timeout(http.shutdown_timeout().as_secs())
+  // Signals are handled by `whim::shutdown::signal` instead
+  .disable_signals();
+
+  if let Some(workers) = http.workers() {
+    server = server.workers(workers);
+  }
+
+  for addr in http.listen() {
+    server = match (addr, &tls) {
+      (ListenAddr::Tcp(addr), Some(tls)) => server.bind_rustls(addr, tls.clone()),
+      (ListenAddr::Tcp(addr), None) => server.bind(addr),
+      #[cfg(unix)]
+      (ListenAddr::Unix(path), _) => server.bind_uds(path),
+      #[cfg(not(unix))]
+      (ListenAddr::Unix(..), _) => Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+      )),
+    }
+    .change_context(StartError)
+    .attach_printable_lazy(|| format!("with address: {addr}"))?;
+
+    tracing::info!("listening on {addr}");
+  }
+
+  let metrics_server = match metrics.listen().filter(|_| metrics.enabled()) {
+    Some(addr) => Some(metrics_server(&app, addr)?),
+    None => None,
+  };
+
+  let result = whim::shutdown::serve(
+    server.run(),
+    whim::shutdown::signal(),
+    shutdown,
+    connections,
+    http.shutdown_timeout(),
+  )
+  .await;
+
+  if let Some(metrics_server) = metrics_server {
+    metrics_server.stop(false).await;
+  }
+
+  for job in jobs {
+    if let Err(error) = job.await {
+      tracing::warn!(%error, "background job panicked");
+    }
+  }
+  if !app.tasks.is_empty() {
+    tracing::info!(
+      tasks = app.tasks.len(),
+      "waiting for background tasks to finish"
+    );
+  }
+  app.tasks.wait().await;
+  app.close().await;
+  telemetry.shutdown().await;
+
+  result.change_context(StartError)
+}
+
+/// Serves `/metrics` on its own address so it is not exposed
+/// along with the public API.
+fn metrics_server(app: &whim::App, addr: &ListenAddr) -> Result<ServerHandle, StartError> {
+  let factory_app = app.clone();
+  let server = HttpServer::new(move || {
+    App::new()
+      .app_data(web::Data::new(factory_app.clone()))
+      .configure(whim::http::controllers::configure_metrics)
   })
   .workers(1)
-  .bind(("localhost", 3000))
-  .unwrap()
-  .run()
-  .await
-  .unwrap();
+  .disable_signals();
+
+  let server = match addr {
+    ListenAddr::Tcp(addr) => server.bind(addr),
+    #[cfg(unix)]
+    ListenAddr::Unix(path) => server.bind_uds(path),
+    #[cfg(not(unix))]
+    ListenAddr::Unix(..) => Err(std::io::Error::new(
+      std::io::ErrorKind::Unsupported,
+      "Unix sockets are not supported on this platform",
+    )),
+  }
+  .change_context(StartError)
+  .attach_printable_lazy(|| format!("with metrics address: {addr}"))?
+  .run();
+
+  tracing::info!("serving metrics on {addr}");
+
+  let handle = server.handle();
+  tokio::spawn(async move {
+    if let Err(error) = server.await {
+      tracing::warn!(%error, "metrics server failed");
+    }
+  });
+  Ok(handle)
 }
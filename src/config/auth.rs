@@ -1,6 +1,7 @@
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
+use std::{num::NonZeroU64, time::Duration};
 use validator::{Validate, ValidateError};
 
 use crate::util::{MaybeGenerated, Sensitive};
@@ -13,13 +14,90 @@ pub struct Auth {
   // Pre-computed hash
   #[serde(skip)]
   pub(crate) jwt_key_hash: OnceCell<String>,
+  /// The issuer (`iss` claim) of every JWT signed by this server.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_JWT_ISSUER`
+  #[serde(default = "Auth::default_jwt_issuer")]
+  pub(crate) jwt_issuer: String,
+  /// How long (in seconds) a JWT is valid after it is issued.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_JWT_LIFETIME_SECS`
+  #[serde(default = "Auth::default_jwt_lifetime_secs")]
+  pub(crate) jwt_lifetime_secs: NonZeroU64,
+  /// Allowed clock skew (in seconds) when validating
+  /// the expiration time of a JWT.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_JWT_LEEWAY_SECS`
+  #[serde(default = "Auth::default_jwt_leeway_secs")]
+  pub(crate) jwt_leeway_secs: u64,
+  /// How long (in seconds) a session can be refreshed since
+  /// its refresh token was last rotated.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_REFRESH_TOKEN_LIFETIME_SECS`
+  #[serde(default = "Auth::default_refresh_token_lifetime_secs")]
+  pub(crate) refresh_token_lifetime_secs: NonZeroU64,
+  /// How long (in seconds) an email verification token is valid.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_EMAIL_VERIFICATION_LIFETIME_SECS`
+  #[serde(default = "Auth::default_email_verification_lifetime_secs")]
+  pub(crate) email_verification_lifetime_secs: NonZeroU64,
+  /// How long (in seconds) a password reset token is valid.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_PASSWORD_RESET_LIFETIME_SECS`
+  #[serde(default = "Auth::default_password_reset_lifetime_secs")]
+  pub(crate) password_reset_lifetime_secs: NonZeroU64,
+  /// How long (in seconds) a user has to enter their two-factor
+  /// authentication code after logging in with their password.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_TWO_FACTOR_CHALLENGE_LIFETIME_SECS`
+  #[serde(default = "Auth::default_two_factor_challenge_lifetime_secs")]
+  pub(crate) two_factor_challenge_lifetime_secs: NonZeroU64,
+  /// Secret key used to encrypt users' TOTP secrets in the database.
+  ///
+  /// Changing this key will lock out every user who has enabled
+  /// two-factor authentication until they use their recovery code.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_TOTP_KEY`
+  #[serde(default = "Auth::generate_secret_key")]
+  pub(crate) totp_key: MaybeGenerated<Sensitive<String>>,
+  /// Cost parameters used to hash users' passwords.
+  #[serde(default)]
+  pub(crate) password: PasswordHashing,
 }
 
 impl std::fmt::Debug for Auth {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     f.debug_struct("Auth")
       .field("jwt_key_hash", &self.jwt_key_hash())
-      .finish()
+      .field("jwt_issuer", &self.jwt_issuer)
+      .field("jwt_lifetime_secs", &self.jwt_lifetime_secs)
+      .field("jwt_leeway_secs", &self.jwt_leeway_secs)
+      .field(
+        "refresh_token_lifetime_secs",
+        &self.refresh_token_lifetime_secs,
+      )
+      .field(
+        "email_verification_lifetime_secs",
+        &self.email_verification_lifetime_secs,
+      )
+      .field(
+        "password_reset_lifetime_secs",
+        &self.password_reset_lifetime_secs,
+      )
+      .field(
+        "two_factor_challenge_lifetime_secs",
+        &self.two_factor_challenge_lifetime_secs,
+      )
+      .field("password", &self.password)
+      .finish_non_exhaustive()
   }
 }
 
@@ -46,12 +124,112 @@ impl Auth {
     self.jwt_key_hash.set(hash).expect("should be empty");
     self.jwt_key_hash.get().unwrap()
   }
+
+  /// Gets the issuer of every JWT signed by this server.
+  pub fn jwt_issuer(&self) -> &str {
+    &self.jwt_issuer
+  }
+
+  /// How long a JWT is valid after it is issued.
+  pub const fn jwt_lifetime(&self) -> Duration {
+    Duration::from_secs(self.jwt_lifetime_secs.get())
+  }
+
+  /// Allowed clock skew when validating the expiration time of a JWT.
+  pub const fn jwt_leeway(&self) -> Duration {
+    Duration::from_secs(self.jwt_leeway_secs)
+  }
+
+  /// How long a session can be refreshed since its refresh
+  /// token was last rotated.
+  pub const fn refresh_token_lifetime(&self) -> Duration {
+    Duration::from_secs(self.refresh_token_lifetime_secs.get())
+  }
+
+  /// How long an email verification token is valid.
+  pub const fn email_verification_lifetime(&self) -> Duration {
+    Duration::from_secs(self.email_verification_lifetime_secs.get())
+  }
+
+  /// How long a password reset token is valid.
+  pub const fn password_reset_lifetime(&self) -> Duration {
+    Duration::from_secs(self.password_reset_lifetime_secs.get())
+  }
+
+  /// How long a user has to enter their two-factor authentication
+  /// code after logging in with their password.
+  pub const fn two_factor_challenge_lifetime(&self) -> Duration {
+    Duration::from_secs(self.two_factor_challenge_lifetime_secs.get())
+  }
+
+  /// Gets the raw value of the TOTP secrets' encryption key
+  pub fn totp_key(&self) -> Sensitive<&str> {
+    Sensitive::new(self.totp_key.value().as_str())
+  }
+
+  /// Gets the cost parameters used to hash users' passwords.
+  pub const fn password(&self) -> &PasswordHashing {
+    &self.password
+  }
 }
 
 impl Auth {
   pub const MIN_JWT_KEY_LENGTH: usize = 24;
   pub const MAX_JWT_KEY_LENGTH: usize = 1024;
 
+  const DEFAULT_JWT_ISSUER: &'static str = "whim";
+  // Access tokens are short-lived, clients must refresh them with their refresh token
+  const DEFAULT_JWT_LIFETIME_SECS: u64 = 60 * 15;
+  const DEFAULT_JWT_LEEWAY_SECS: u64 = 60;
+  const DEFAULT_REFRESH_TOKEN_LIFETIME_SECS: u64 = 60 * 60 * 24 * 30;
+  const DEFAULT_EMAIL_VERIFICATION_LIFETIME_SECS: u64 = 60 * 60 * 24;
+  const DEFAULT_PASSWORD_RESET_LIFETIME_SECS: u64 = 60 * 30;
+  const DEFAULT_TWO_FACTOR_CHALLENGE_LIFETIME_SECS: u64 = 60 * 5;
+
+  // Required by serde
+  fn default_jwt_issuer() -> String {
+    Self::DEFAULT_JWT_ISSUER.into()
+  }
+
+  const fn default_jwt_lifetime_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_JWT_LIFETIME_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_JWT_LIFETIME_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_jwt_leeway_secs() -> u64 {
+    Self::DEFAULT_JWT_LEEWAY_SECS
+  }
+
+  const fn default_refresh_token_lifetime_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_REFRESH_TOKEN_LIFETIME_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_REFRESH_TOKEN_LIFETIME_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_email_verification_lifetime_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_EMAIL_VERIFICATION_LIFETIME_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_EMAIL_VERIFICATION_LIFETIME_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_password_reset_lifetime_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_PASSWORD_RESET_LIFETIME_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_PASSWORD_RESET_LIFETIME_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_two_factor_challenge_lifetime_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_TWO_FACTOR_CHALLENGE_LIFETIME_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_TWO_FACTOR_CHALLENGE_LIFETIME_SECS is accidentally set to 0"),
+    }
+  }
+
   /// Generates a new JWT secret key with alphabetic and special
   /// characters are randomized and scrambled into 24 characters.
   /// (minimum amount of characters required for a JWT secret key for Whim)
@@ -61,6 +239,11 @@ impl Auth {
     let output: String = random_string::generate(Self::MIN_JWT_KEY_LENGTH, &*CHARSET);
     MaybeGenerated::Generated(Sensitive::new(output))
   }
+
+  // Same rules as the JWT secret key applies to other secret keys
+  fn generate_secret_key() -> MaybeGenerated<Sensitive<String>> {
+    Self::generate_jwt_key()
+  }
 }
 
 impl Default for Auth {
@@ -72,6 +255,15 @@ impl Default for Auth {
     let auth = Self {
       jwt_key: Self::generate_jwt_key(),
       jwt_key_hash: OnceCell::new(),
+      jwt_issuer: Self::default_jwt_issuer(),
+      jwt_lifetime_secs: Self::default_jwt_lifetime_secs(),
+      jwt_leeway_secs: Self::default_jwt_leeway_secs(),
+      refresh_token_lifetime_secs: Self::default_refresh_token_lifetime_secs(),
+      email_verification_lifetime_secs: Self::default_email_verification_lifetime_secs(),
+      password_reset_lifetime_secs: Self::default_password_reset_lifetime_secs(),
+      two_factor_challenge_lifetime_secs: Self::default_two_factor_challenge_lifetime_secs(),
+      totp_key: Self::generate_secret_key(),
+      password: PasswordHashing::default(),
     };
     let _ = auth.jwt_key_hash();
     auth
@@ -103,13 +295,104 @@ impl Validate for Auth {
       }
       fields.insert("jwt_key", jwt_errs.build());
     }
+    if self.totp_key.len() < Self::MIN_JWT_KEY_LENGTH {
+      let mut error = ValidateError::msg_builder();
+      error.insert("TOTP secret key must have at least 24 characters");
+      fields.insert("totp_key", error.build());
+    }
+    if self.jwt_issuer.trim().is_empty() {
+      let mut error = ValidateError::msg_builder();
+      error.insert("JWT issuer must not be empty");
+      fields.insert("jwt_issuer", error.build());
+    }
+    if let Err(error) = self.password.validate() {
+      fields.insert("password", error);
+    }
     fields.build().into_result()
   }
 }
 
+/// Argon2id cost parameters for hashing users' passwords.
+///
+/// Changing any of these values does not invalidate existing
+/// password hashes. Each user's password hash will be upgraded
+/// with the new parameters the next time they successfully log in.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PasswordHashing {
+  /// Amount of memory (in kibibytes) used to hash a password.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_PASSWORD_MEMORY_COST_KIB`
+  #[serde(default = "PasswordHashing::default_memory_cost_kib")]
+  pub(crate) memory_cost_kib: u32,
+  /// Number of passes over the memory.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_PASSWORD_ITERATIONS`
+  #[serde(default = "PasswordHashing::default_iterations")]
+  pub(crate) iterations: u32,
+  /// Degree of parallelism (number of lanes).
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_AUTH_PASSWORD_PARALLELISM`
+  #[serde(default = "PasswordHashing::default_parallelism")]
+  pub(crate) parallelism: u32,
+}
+
+impl PasswordHashing {
+  // Recommended minimum configuration from OWASP's password storage cheat sheet
+  const DEFAULT_MEMORY_COST_KIB: u32 = 19 * 1024;
+  const DEFAULT_ITERATIONS: u32 = 2;
+  const DEFAULT_PARALLELISM: u32 = 1;
+
+  const fn default_memory_cost_kib() -> u32 {
+    Self::DEFAULT_MEMORY_COST_KIB
+  }
+
+  const fn default_iterations() -> u32 {
+    Self::DEFAULT_ITERATIONS
+  }
+
+  const fn default_parallelism() -> u32 {
+    Self::DEFAULT_PARALLELISM
+  }
+
+  /// Builds Argon2 parameters out of this configuration.
+  pub fn params(&self) -> Result<argon2::Params, argon2::Error> {
+    argon2::Params::new(
+      self.memory_cost_kib,
+      self.iterations,
+      self.parallelism,
+      None,
+    )
+  }
+}
+
+impl Default for PasswordHashing {
+  fn default() -> Self {
+    Self {
+      memory_cost_kib: Self::DEFAULT_MEMORY_COST_KIB,
+      iterations: Self::DEFAULT_ITERATIONS,
+      parallelism: Self::DEFAULT_PARALLELISM,
+    }
+  }
+}
+
+impl Validate for PasswordHashing {
+  /// It checks if Argon2 accepts all of the cost parameters.
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut errors = ValidateError::msg_builder();
+    if let Err(error) = self.params() {
+      errors.insert(format!("Invalid Argon2 parameters: {error}"));
+    }
+    errors.build().into_result()
+  }
+}
+
 #[cfg(test)]
 mod tests {
-  use super::Auth;
+  use super::{Auth, PasswordHashing};
   use validator::Validate;
 
   #[test]
@@ -123,4 +406,15 @@ mod tests {
     let auth = Auth::default();
     assert_eq!(auth.validate(), Ok(()));
   }
+
+  #[test]
+  fn test_password_hashing() {
+    let mut auth = Auth::default();
+    auth.password.memory_cost_kib = 1;
+    assert!(auth.validate().is_err());
+
+    auth.password = PasswordHashing::default();
+    auth.password.parallelism = 0;
+    assert!(auth.validate().is_err());
+  }
 }
@@ -0,0 +1,446 @@
+use serde::{de::IntoDeserializer, Deserialize, Deserializer};
+use std::{
+  fmt::Display,
+  net::IpAddr,
+  num::NonZeroUsize,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+use validator::{Validate, ValidateError};
+
+/// Configuration for the HTTP server.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Http {
+  /// Addresses where the server listens for requests, either
+  /// `host:port` or `unix:/path/to/socket` for Unix sockets.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_LISTEN` (e.g. `[0.0.0.0:3000,unix:/run/whim.sock]`)
+  #[serde(default = "Http::default_listen", deserialize_with = "one_or_many")]
+  pub(crate) listen: Vec<ListenAddr>,
+  /// Number of worker threads handling requests. It defaults
+  /// to the number of physical CPU cores if it is not set.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_WORKERS`
+  #[serde(default)]
+  pub(crate) workers: Option<NonZeroUsize>,
+  /// How long (in seconds) idle connections are kept open.
+  /// Setting it to 0 disables keep-alive.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_KEEP_ALIVE_SECS`
+  #[serde(default = "Http::default_keep_alive_secs")]
+  pub(crate) keep_alive_secs: u64,
+  /// Maximum size (in bytes) of a request body.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_PAYLOAD_LIMIT_BYTES`
+  #[serde(default = "Http::default_payload_limit_bytes")]
+  pub(crate) payload_limit_bytes: NonZeroUsize,
+  /// Maximum size (in bytes) of a JSON request body.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_JSON_LIMIT_BYTES`
+  #[serde(default = "Http::default_json_limit_bytes")]
+  pub(crate) json_limit_bytes: NonZeroUsize,
+  /// How long (in seconds) in-flight requests are given to finish
+  /// after the server is told to shut down. Requests which are still
+  /// running after this deadline are dropped.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_SHUTDOWN_TIMEOUT_SECS`
+  #[serde(default = "Http::default_shutdown_timeout_secs")]
+  pub(crate) shutdown_timeout_secs: u64,
+  /// Serves HTTPS instead of HTTP on TCP addresses if it is set.
+  ///
+  /// Unix sockets are always served with plain HTTP
+  /// as they're meant to be used behind a reverse proxy.
+  #[serde(default)]
+  pub(crate) tls: Option<Tls>,
+  /// Addresses or networks (e.g. `10.0.0.0/8`) of the reverse proxies
+  /// in front of the server. Client addresses are only taken from the
+  /// `Forwarded` or `X-Forwarded-For` headers of requests sent by them,
+  /// otherwise clients could pretend to be anyone.
+  ///
+  /// Requests received from Unix sockets are always trusted as only
+  /// a local reverse proxy can send them.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_TRUSTED_PROXIES` (e.g. `[127.0.0.1,10.0.0.0/8]`)
+  #[serde(default, deserialize_with = "one_or_many")]
+  pub(crate) trusted_proxies: Vec<IpNetwork>,
+}
+
+impl Http {
+  /// Addresses where the server listens for requests.
+  pub fn listen(&self) -> &[ListenAddr] {
+    &self.listen
+  }
+
+  /// Number of worker threads if it is set.
+  pub fn workers(&self) -> Option<usize> {
+    self.workers.map(NonZeroUsize::get)
+  }
+
+  /// How long idle connections are kept open. It
+  /// returns `None` if keep-alive is disabled.
+  pub const fn keep_alive(&self) -> Option<Duration> {
+    if self.keep_alive_secs == 0 {
+      None
+    } else {
+      Some(Duration::from_secs(self.keep_alive_secs))
+    }
+  }
+
+  /// Maximum size (in bytes) of a request body.
+  pub const fn payload_limit(&self) -> usize {
+    self.payload_limit_bytes.get()
+  }
+
+  /// Maximum size (in bytes) of a JSON request body.
+  pub const fn json_limit(&self) -> usize {
+    self.json_limit_bytes.get()
+  }
+
+  /// How long in-flight requests are given to finish when shutting down.
+  pub const fn shutdown_timeout(&self) -> Duration {
+    Duration::from_secs(self.shutdown_timeout_secs)
+  }
+
+  /// Gets the certificate and private key paths if HTTPS is enabled.
+  pub const fn tls(&self) -> Option<&Tls> {
+    self.tls.as_ref()
+  }
+
+  /// Networks of the reverse proxies in front of the server.
+  pub fn trusted_proxies(&self) -> &[IpNetwork] {
+    &self.trusted_proxies
+  }
+}
+
+impl Http {
+  const DEFAULT_LISTEN: &'static str = "localhost:3000";
+  const DEFAULT_KEEP_ALIVE_SECS: u64 = 5;
+  const DEFAULT_PAYLOAD_LIMIT_BYTES: usize = 256 * 1024;
+  const DEFAULT_JSON_LIMIT_BYTES: usize = 64 * 1024;
+  const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
+  // Required by serde
+  fn default_listen() -> Vec<ListenAddr> {
+    vec![ListenAddr::Tcp(Self::DEFAULT_LISTEN.into())]
+  }
+
+  const fn default_keep_alive_secs() -> u64 {
+    Self::DEFAULT_KEEP_ALIVE_SECS
+  }
+
+  const fn default_payload_limit_bytes() -> NonZeroUsize {
+    match NonZeroUsize::new(Self::DEFAULT_PAYLOAD_LIMIT_BYTES) {
+      Some(n) => n,
+      None => panic!("DEFAULT_PAYLOAD_LIMIT_BYTES is accidentally set to 0"),
+    }
+  }
+
+  const fn default_json_limit_bytes() -> NonZeroUsize {
+    match NonZeroUsize::new(Self::DEFAULT_JSON_LIMIT_BYTES) {
+      Some(n) => n,
+      None => panic!("DEFAULT_JSON_LIMIT_BYTES is accidentally set to 0"),
+    }
+  }
+
+  const fn default_shutdown_timeout_secs() -> u64 {
+    Self::DEFAULT_SHUTDOWN_TIMEOUT_SECS
+  }
+}
+
+impl Default for Http {
+  fn default() -> Self {
+    Self {
+      listen: Self::default_listen(),
+      workers: None,
+      keep_alive_secs: Self::default_keep_alive_secs(),
+      payload_limit_bytes: Self::default_payload_limit_bytes(),
+      json_limit_bytes: Self::default_json_limit_bytes(),
+      shutdown_timeout_secs: Self::default_shutdown_timeout_secs(),
+      tls: None,
+      trusted_proxies: Vec::new(),
+    }
+  }
+}
+
+impl Validate for Http {
+  /// It checks if the server has anything to listen on and
+  /// HTTPS is enabled only if there's a TCP address to serve.
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    if self.listen.is_empty() {
+      let mut error = ValidateError::msg_builder();
+      error.insert("At least one address to listen on is required");
+      fields.insert("listen", error.build());
+    }
+    if let Some(tls) = self.tls.as_ref() {
+      if !self.listen.iter().any(|v| matches!(v, ListenAddr::Tcp(..))) {
+        let mut error = ValidateError::msg_builder();
+        error.insert("TLS requires at least one TCP address to listen on");
+        fields.insert("tls", error.build());
+      } else if let Err(error) = tls.validate() {
+        fields.insert("tls", error);
+      }
+    }
+    fields.build().into_result()
+  }
+}
+
+/// Paths of the PEM encoded certificate chain and private key
+/// used to serve HTTPS.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tls {
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_TLS_CERT_PATH`
+  pub(crate) cert_path: PathBuf,
+  /// **Environment variables**:
+  /// - `WHIM_HTTP_TLS_KEY_PATH`
+  pub(crate) key_path: PathBuf,
+}
+
+impl Tls {
+  pub fn cert_path(&self) -> &Path {
+    &self.cert_path
+  }
+
+  pub fn key_path(&self) -> &Path {
+    &self.key_path
+  }
+}
+
+impl Validate for Tls {
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    for (field, path) in [("cert_path", &self.cert_path), ("key_path", &self.key_path)] {
+      if path.as_os_str().is_empty() {
+        let mut error = ValidateError::msg_builder();
+        error.insert("Path must not be empty");
+        fields.insert(field, error.build());
+      }
+    }
+    fields.build().into_result()
+  }
+}
+
+/// An address where the server listens for requests.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ListenAddr {
+  /// A TCP address in `host:port` form.
+  Tcp(String),
+  /// Path of a Unix socket.
+  Unix(PathBuf),
+}
+
+impl ListenAddr {
+  const UNIX_PREFIX: &'static str = "unix:";
+}
+
+impl TryFrom<String> for ListenAddr {
+  type Error = &'static str;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    if let Some(path) = value.strip_prefix(Self::UNIX_PREFIX) {
+      if path.is_empty() {
+        return Err("Unix socket path must not be empty");
+      }
+      return Ok(Self::Unix(path.into()));
+    }
+
+    match value.rsplit_once(':') {
+      Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => Ok(Self::Tcp(value)),
+      _ => Err("address must be either `host:port` or `unix:/path/to/socket`"),
+    }
+  }
+}
+
+impl Display for ListenAddr {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Tcp(addr) => f.write_str(addr),
+      Self::Unix(path) => write!(f, "{}{}", Self::UNIX_PREFIX, path.display()),
+    }
+  }
+}
+
+/// An IP address or a network of them in CIDR notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct IpNetwork(ipnet::IpNet);
+
+impl IpNetwork {
+  #[must_use]
+  pub fn contains(&self, ip: &IpAddr) -> bool {
+    self.0.contains(ip)
+  }
+}
+
+impl TryFrom<String> for IpNetwork {
+  type Error = &'static str;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    if let Ok(ip) = value.parse::<IpAddr>() {
+      return Ok(Self(ip.into()));
+    }
+    value
+      .parse()
+      .map(Self)
+      .map_err(|_| "expected an IP address or a network (e.g. `10.0.0.0/8`)")
+  }
+}
+
+impl Display for IpNetwork {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+/// Environment variables cannot hold a list with a single
+/// value, so a single value is accepted as well.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+  D: Deserializer<'de>,
+  T: Deserialize<'de>,
+{
+  struct Visitor<T>(std::marker::PhantomData<T>);
+
+  impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+      f.write_str("a value or a list of values")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+      E: serde::de::Error,
+    {
+      T::deserialize(v.into_deserializer()).map(|v| vec![v])
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+      A: serde::de::SeqAccess<'de>,
+    {
+      let mut values = Vec::new();
+      while let Some(value) = seq.next_element()? {
+        values.push(value);
+      }
+      Ok(values)
+    }
+  }
+
+  deserializer.deserialize_any(Visitor(std::marker::PhantomData))
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_listen_addr() {
+    assert_eq!(
+      ListenAddr::try_from("0.0.0.0:3000".to_string()),
+      Ok(ListenAddr::Tcp("0.0.0.0:3000".into()))
+    );
+    assert_eq!(
+      ListenAddr::try_from("[::1]:3000".to_string()),
+      Ok(ListenAddr::Tcp("[::1]:3000".into()))
+    );
+    assert_eq!(
+      ListenAddr::try_from("unix:/run/whim.sock".to_string()),
+      Ok(ListenAddr::Unix("/run/whim.sock".into()))
+    );
+
+    for value in [
+      "localhost",
+      ":3000",
+      "localhost:http",
+      "localhost:70000",
+      "unix:",
+    ] {
+      assert!(ListenAddr::try_from(value.to_string()).is_err(), "{value}");
+    }
+  }
+
+  #[test]
+  fn test_deserialize() {
+    let http = figment::Figment::new()
+      .merge(figment::providers::Serialized::default(
+        "listen",
+        "127.0.0.1:8080",
+      ))
+      .extract::<Http>()
+      .unwrap();
+    assert_eq!(http.listen(), [ListenAddr::Tcp("127.0.0.1:8080".into())]);
+
+    let http = figment::Figment::new()
+      .merge(figment::providers::Serialized::default(
+        "listen",
+        ["127.0.0.1:8080", "unix:/run/whim.sock"],
+      ))
+      .extract::<Http>()
+      .unwrap();
+    assert_eq!(http.listen().len(), 2);
+
+    let http = figment::Figment::new()
+      .merge(figment::providers::Serialized::default(
+        "trusted_proxies",
+        "10.0.0.0/8",
+      ))
+      .extract::<Http>()
+      .unwrap();
+    let network = &http.trusted_proxies()[0];
+    assert!(network.contains(&"10.1.2.3".parse().unwrap()));
+    assert!(!network.contains(&"11.0.0.1".parse().unwrap()));
+
+    let http = figment::Figment::new()
+      .merge(figment::providers::Serialized::default(
+        "trusted_proxies",
+        ["127.0.0.1", "::1"],
+      ))
+      .extract::<Http>()
+      .unwrap();
+    assert_eq!(http.trusted_proxies().len(), 2);
+    assert!(http.trusted_proxies()[0].contains(&"127.0.0.1".parse().unwrap()));
+  }
+
+  #[test]
+  fn test_validate() {
+    assert!(Http::default().validate().is_ok());
+
+    let cfg = Http {
+      listen: Vec::new(),
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+
+    let cfg = Http {
+      listen: vec![ListenAddr::Unix("/run/whim.sock".into())],
+      tls: Some(Tls {
+        cert_path: "cert.pem".into(),
+        key_path: "key.pem".into(),
+      }),
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+
+    let cfg = Http {
+      tls: Some(Tls {
+        cert_path: "cert.pem".into(),
+        key_path: PathBuf::new(),
+      }),
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+  }
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use validator::Validate;
+
+/// Instance-wide policies decided by the maintainer of a Whim instance.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Instance {
+  /// Allows users who have not verified their email address to log in.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_INSTANCE_ALLOW_UNVERIFIED_LOGIN`
+  #[serde(default = "Instance::default_allow_unverified_login")]
+  pub(crate) allow_unverified_login: bool,
+  /// Allows users who have not verified their email address to post.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_INSTANCE_ALLOW_UNVERIFIED_POSTING`
+  #[serde(default)]
+  pub(crate) allow_unverified_posting: bool,
+}
+
+impl Instance {
+  /// Whether users who have not verified their email address can log in.
+  pub const fn allow_unverified_login(&self) -> bool {
+    self.allow_unverified_login
+  }
+
+  /// Whether users who have not verified their email address can post.
+  pub const fn allow_unverified_posting(&self) -> bool {
+    self.allow_unverified_posting
+  }
+}
+
+impl Instance {
+  // Required by serde
+  const fn default_allow_unverified_login() -> bool {
+    true
+  }
+}
+
+impl Default for Instance {
+  fn default() -> Self {
+    Self {
+      allow_unverified_login: Self::default_allow_unverified_login(),
+      allow_unverified_posting: false,
+    }
+  }
+}
@@ -0,0 +1,165 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use validator::Validate;
+
+use crate::util::Sensitive;
+
+/// Configuration for sending emails to users.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Mailer {
+  /// The sender of every email sent by this server.
+  ///
+  /// It accepts either a plain email address or a mailbox
+  /// with a name like `Whim <noreply@example.com>`.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_MAILER_FROM`
+  #[serde(default = "Mailer::default_from")]
+  #[validate(with = "Mailer::validate_from", error = "Invalid sender mailbox")]
+  pub(crate) from: String,
+  /// Where the emails will be delivered.
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) transport: MailTransport,
+}
+
+impl Mailer {
+  /// Gets the sender of every email sent by this server.
+  pub fn from(&self) -> &str {
+    &self.from
+  }
+
+  /// Gets the transport where the emails will be delivered.
+  pub const fn transport(&self) -> &MailTransport {
+    &self.transport
+  }
+}
+
+impl Mailer {
+  const DEFAULT_FROM: &'static str = "Whim <noreply@localhost>";
+
+  // Required by serde
+  fn default_from() -> String {
+    Self::DEFAULT_FROM.into()
+  }
+
+  fn validate_from(from: &str) -> bool {
+    from.parse::<lettre::message::Mailbox>().is_ok()
+  }
+}
+
+impl Default for Mailer {
+  fn default() -> Self {
+    Self {
+      from: Self::default_from(),
+      transport: MailTransport::default(),
+    }
+  }
+}
+
+/// Transport used to deliver emails.
+///
+/// **Environment variables**:
+/// - `WHIM_MAILER_TRANSPORT_KIND`
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MailTransport {
+  /// Emails are kept in memory and never delivered.
+  ///
+  /// This is only useful for testing and development.
+  #[default]
+  Memory,
+  /// Emails are written as `.eml` files into a directory.
+  File {
+    /// **Environment variables**:
+    /// - `WHIM_MAILER_TRANSPORT_PATH`
+    path: PathBuf,
+  },
+  /// Emails are delivered through an SMTP server.
+  Smtp(#[validate(nested)] SmtpTransport),
+}
+
+/// Configuration for delivering emails through an SMTP server.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpTransport {
+  /// **Environment variables**:
+  /// - `WHIM_MAILER_TRANSPORT_HOST`
+  #[validate(length(min = 1))]
+  pub(crate) host: String,
+  /// Uses the default port of the selected encryption if not set.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_MAILER_TRANSPORT_PORT`
+  pub(crate) port: Option<u16>,
+  /// **Environment variables**:
+  /// - `WHIM_MAILER_TRANSPORT_USERNAME`
+  pub(crate) username: Option<String>,
+  /// **Environment variables**:
+  /// - `WHIM_MAILER_TRANSPORT_PASSWORD`
+  pub(crate) password: Option<Sensitive<String>>,
+  /// **Environment variables**:
+  /// - `WHIM_MAILER_TRANSPORT_ENCRYPTION`
+  #[serde(default)]
+  pub(crate) encryption: SmtpEncryption,
+}
+
+impl SmtpTransport {
+  pub fn host(&self) -> &str {
+    &self.host
+  }
+
+  pub const fn port(&self) -> Option<u16> {
+    self.port
+  }
+
+  /// Gets the username and password used to authenticate
+  /// with the SMTP server (if both are set).
+  pub fn credentials(&self) -> Option<(&str, &Sensitive<String>)> {
+    match (self.username.as_deref(), self.password.as_ref()) {
+      (Some(username), Some(password)) => Some((username, password)),
+      _ => None,
+    }
+  }
+
+  pub const fn encryption(&self) -> SmtpEncryption {
+    self.encryption
+  }
+}
+
+/// How the connection to the SMTP server is encrypted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpEncryption {
+  /// Upgrades the connection with `STARTTLS`.
+  #[default]
+  Starttls,
+  /// Connects with TLS from the start (implicit TLS).
+  Tls,
+  /// No encryption at all. Only use this for local SMTP servers.
+  None,
+}
+
+impl MailTransport {
+  /// Gets the directory path if the transport writes emails into files.
+  pub fn path(&self) -> Option<&Path> {
+    match self {
+      Self::File { path } => Some(path),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate_from() {
+    assert!(Mailer::validate_from("noreply@example.com"));
+    assert!(Mailer::validate_from("Whim <noreply@example.com>"));
+    assert!(!Mailer::validate_from("noreply"));
+    assert!(Mailer::default().validate().is_ok());
+  }
+}
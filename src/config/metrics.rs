@@ -0,0 +1,77 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use super::ListenAddr;
+
+/// Configuration for exporting Prometheus metrics.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Metrics {
+  /// Exports metrics at `/metrics` in Prometheus' text format.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_METRICS_ENABLED`
+  #[serde(default = "Metrics::default_enabled")]
+  pub(crate) enabled: bool,
+  /// Serves `/metrics` on a separate address (without TLS), either
+  /// `host:port` or `unix:/path/to/socket`, instead of the addresses
+  /// from [`Http::listen`] so it is not exposed to the public.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_METRICS_LISTEN`
+  ///
+  /// [`Http::listen`]: super::Http::listen
+  #[serde(default)]
+  pub(crate) listen: Option<ListenAddr>,
+}
+
+impl Metrics {
+  /// Whether metrics are exported at `/metrics`.
+  pub const fn enabled(&self) -> bool {
+    self.enabled
+  }
+
+  /// Separate address where `/metrics` is served from.
+  pub const fn listen(&self) -> Option<&ListenAddr> {
+    self.listen.as_ref()
+  }
+}
+
+impl Metrics {
+  // Required by serde
+  const fn default_enabled() -> bool {
+    true
+  }
+}
+
+impl Default for Metrics {
+  fn default() -> Self {
+    Self {
+      enabled: Self::default_enabled(),
+      listen: None,
+    }
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_deserialize() {
+    let metrics = figment::Figment::new()
+      .merge(figment::providers::Serialized::default(
+        "listen",
+        "127.0.0.1:9090",
+      ))
+      .extract::<Metrics>()
+      .unwrap();
+
+    assert!(metrics.enabled());
+    assert_eq!(
+      metrics.listen(),
+      Some(&ListenAddr::Tcp("127.0.0.1:9090".into()))
+    );
+  }
+}
@@ -2,11 +2,29 @@ use thiserror::Error;
 
 mod auth;
 mod database;
+mod http;
+mod instance;
+mod mailer;
+mod metrics;
+mod rate_limit;
 mod server;
+mod storage;
+mod telemetry;
+mod throttle;
+mod users;
 
-pub use auth::Auth;
+pub use auth::{Auth, PasswordHashing};
 pub use database::{Database, DbPoolConfig};
+pub use http::{Http, IpNetwork, ListenAddr, Tls};
+pub use instance::Instance;
+pub use mailer::{MailTransport, Mailer, SmtpEncryption, SmtpTransport};
+pub use metrics::Metrics;
+pub use rate_limit::{RateLimit, RateLimitPolicy};
 pub use server::Server;
+pub use storage::{Storage, StorageBackend};
+pub use telemetry::{LogFormat, Otlp, OtlpProtocol, Telemetry};
+pub use throttle::{LoginThrottle, Throttle, ThrottleStorage};
+pub use users::Users;
 
 #[derive(Debug, Error)]
 #[error("Failed to load configuration")]
@@ -0,0 +1,194 @@
+use serde::Deserialize;
+use std::{
+  collections::BTreeMap,
+  num::{NonZeroU32, NonZeroU64},
+  time::Duration,
+};
+use validator::{Validate, ValidateError};
+
+/// Configuration for limiting how many requests a client can make.
+///
+/// Clients are identified by their user id if they're logged in,
+/// or by their IP address otherwise. Requests are counted separately
+/// for each scope.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+  /// **Environment variables**:
+  /// - `WHIM_RATE_LIMIT_ENABLED`
+  #[serde(default = "RateLimit::default_enabled")]
+  pub(crate) enabled: bool,
+  /// Policy applied to routes not covered by any of the scopes.
+  #[serde(default = "RateLimit::default_default")]
+  pub(crate) default: RateLimitPolicy,
+  /// Policies applied to routes starting with the given path. If
+  /// more than one scope matches, the longest path takes priority.
+  ///
+  /// ```toml
+  /// [rate_limit.scopes."/users/register"]
+  /// requests = 5
+  /// period_secs = 3600
+  /// ```
+  #[serde(default = "RateLimit::default_scopes")]
+  pub(crate) scopes: BTreeMap<String, RateLimitPolicy>,
+}
+
+impl RateLimit {
+  /// Whether requests are rate limited.
+  pub const fn enabled(&self) -> bool {
+    self.enabled
+  }
+
+  /// Gets the policy applied to routes not covered by any of the scopes.
+  pub const fn default_policy(&self) -> &RateLimitPolicy {
+    &self.default
+  }
+
+  /// Gets all of the configured scopes.
+  pub const fn scopes(&self) -> &BTreeMap<String, RateLimitPolicy> {
+    &self.scopes
+  }
+
+  /// Finds the most specific scope covering a path. It returns the
+  /// scope's path and its policy, or `None` for the default policy.
+  pub fn scope_of(&self, path: &str) -> Option<(&str, &RateLimitPolicy)> {
+    self
+      .scopes
+      .iter()
+      .filter(|(scope, _)| covers(scope, path))
+      .max_by_key(|(scope, _)| scope.len())
+      .map(|(scope, policy)| (scope.as_str(), policy))
+  }
+}
+
+impl RateLimit {
+  // Required by serde
+  const fn default_enabled() -> bool {
+    true
+  }
+
+  const fn default_default() -> RateLimitPolicy {
+    RateLimitPolicy::new(120, 60)
+  }
+
+  fn default_scopes() -> BTreeMap<String, RateLimitPolicy> {
+    BTreeMap::from([
+      ("/users/register".into(), RateLimitPolicy::new(5, 60 * 60)),
+      ("/users/login".into(), RateLimitPolicy::new(10, 60)),
+      (
+        "/users/forgot-password".into(),
+        RateLimitPolicy::new(5, 60 * 60),
+      ),
+      (
+        "/users/verify-email/resend".into(),
+        RateLimitPolicy::new(5, 60 * 60),
+      ),
+    ])
+  }
+}
+
+impl Default for RateLimit {
+  fn default() -> Self {
+    Self {
+      enabled: Self::default_enabled(),
+      default: Self::default_default(),
+      scopes: Self::default_scopes(),
+    }
+  }
+}
+
+impl Validate for RateLimit {
+  /// It checks if every scope is an absolute path.
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    for scope in self.scopes.keys() {
+      if !scope.starts_with('/') {
+        let mut error = ValidateError::msg_builder();
+        error.insert("Scope must be a path starting with `/`");
+        fields.insert(scope.clone(), error.build());
+      }
+    }
+    fields.build().into_result()
+  }
+}
+
+/// Allows up to `requests` requests per `period_secs`, with up to
+/// `burst` requests at once (defaults to `requests`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitPolicy {
+  /// **Environment variables** (default policy only):
+  /// - `WHIM_RATE_LIMIT_DEFAULT_REQUESTS`
+  pub(crate) requests: NonZeroU32,
+  /// **Environment variables** (default policy only):
+  /// - `WHIM_RATE_LIMIT_DEFAULT_PERIOD_SECS`
+  pub(crate) period_secs: NonZeroU64,
+  /// **Environment variables** (default policy only):
+  /// - `WHIM_RATE_LIMIT_DEFAULT_BURST`
+  #[serde(default)]
+  pub(crate) burst: Option<NonZeroU32>,
+}
+
+impl RateLimitPolicy {
+  const fn new(requests: u32, period_secs: u64) -> Self {
+    match (NonZeroU32::new(requests), NonZeroU64::new(period_secs)) {
+      (Some(requests), Some(period_secs)) => Self {
+        requests,
+        period_secs,
+        burst: None,
+      },
+      _ => panic!("rate limit policy is accidentally set to 0"),
+    }
+  }
+
+  /// Maximum number of requests allowed at once.
+  pub const fn burst(&self) -> u32 {
+    match self.burst {
+      Some(burst) => burst.get(),
+      None => self.requests.get(),
+    }
+  }
+
+  /// Time needed to regain one request.
+  pub fn emission_interval(&self) -> Duration {
+    Duration::from_secs(self.period_secs.get()) / self.requests.get()
+  }
+}
+
+fn covers(scope: &str, path: &str) -> bool {
+  let scope = scope.trim_end_matches('/');
+  match path.strip_prefix(scope) {
+    Some(rest) => rest.is_empty() || rest.starts_with('/'),
+    None => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_scope_of() {
+    let mut cfg = RateLimit::default();
+    cfg
+      .scopes
+      .insert("/users".into(), RateLimitPolicy::new(60, 60));
+
+    let scope_of = |path| cfg.scope_of(path).map(|(scope, _)| scope);
+    assert_eq!(scope_of("/users/register"), Some("/users/register"));
+    assert_eq!(scope_of("/users/login/2fa"), Some("/users/login"));
+    assert_eq!(scope_of("/users/loginx"), Some("/users"));
+    assert_eq!(scope_of("/auth/refresh"), None);
+  }
+
+  #[test]
+  fn test_validate() {
+    assert_eq!(RateLimit::default().validate(), Ok(()));
+
+    let mut cfg = RateLimit::default();
+    cfg
+      .scopes
+      .insert("users".into(), RateLimitPolicy::new(60, 60));
+    assert!(cfg.validate().is_err());
+  }
+}
@@ -5,7 +5,10 @@ use std::path::{Path, PathBuf};
 use validator::Validate;
 
 use super::LoadError;
-use crate::util::shims::{FigmentErrorAttachable, IntoValidatorReport};
+use crate::{
+  types::id::MAX_WORKER_ID,
+  util::shims::{FigmentErrorAttachable, IntoValidatorReport},
+};
 
 /// Server configuration for running a web server.
 #[derive(Debug, Deserialize, Validate)]
@@ -15,6 +18,44 @@ pub struct Server {
   pub(crate) auth: super::Auth,
   #[validate(nested)]
   pub(crate) db: super::Database,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) http: super::Http,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) instance: super::Instance,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) mailer: super::Mailer,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) metrics: super::Metrics,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) rate_limit: super::RateLimit,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) storage: super::Storage,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) telemetry: super::Telemetry,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) throttle: super::Throttle,
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) users: super::Users,
+  /// Identifies this process when generating IDs, every process
+  /// sharing the same database must have a unique worker ID.
+  ///
+  /// It must not be greater than [`MAX_WORKER_ID`] as the last
+  /// worker ID is reserved for IDs generated by the database.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_WORKER_ID`
+  #[serde(default)]
+  #[validate(with = "Server::validate_worker_id", error = "Invalid worker ID")]
+  pub(crate) worker_id: u16,
   #[serde(skip, default)]
   pub(crate) path: Option<PathBuf>,
 }
@@ -157,6 +198,47 @@ impl Server {
     &self.db
   }
 
+  pub const fn http(&self) -> &super::Http {
+    &self.http
+  }
+
+  pub const fn instance(&self) -> &super::Instance {
+    &self.instance
+  }
+
+  pub const fn mailer(&self) -> &super::Mailer {
+    &self.mailer
+  }
+
+  pub const fn metrics(&self) -> &super::Metrics {
+    &self.metrics
+  }
+
+  pub const fn rate_limit(&self) -> &super::RateLimit {
+    &self.rate_limit
+  }
+
+  pub const fn storage(&self) -> &super::Storage {
+    &self.storage
+  }
+
+  pub const fn telemetry(&self) -> &super::Telemetry {
+    &self.telemetry
+  }
+
+  pub const fn throttle(&self) -> &super::Throttle {
+    &self.throttle
+  }
+
+  pub const fn users(&self) -> &super::Users {
+    &self.users
+  }
+
+  /// Identifies this process when generating IDs.
+  pub const fn worker_id(&self) -> u16 {
+    self.worker_id
+  }
+
   /// Gets the config file path of `whim.toml`.
   pub fn path(&self) -> Option<&Path> {
     self.path.as_deref()
@@ -164,6 +246,12 @@ impl Server {
 }
 
 impl Server {
+  // Required by validator
+  #[allow(clippy::trivially_copy_pass_by_ref)]
+  fn validate_worker_id(worker_id: &u16) -> bool {
+    *worker_id <= MAX_WORKER_ID
+  }
+
   /// Creates a base [`Figment`] object for [`Server`] to load bare
   /// server configuration. This function is there for implementing
   /// loader functions and testing.
@@ -187,12 +275,80 @@ impl Server {
         "AUTH_JWT_KEY_KEY" => "auth.jwt_key".into(),
         "JWT_KEY" => "auth.jwt_key".into(),
         "JWT_KEY_KEY" => "auth.jwt_key".into(),
+        "AUTH_JWT_ISSUER" => "auth.jwt_issuer".into(),
+        "AUTH_JWT_LIFETIME_SECS" => "auth.jwt_lifetime_secs".into(),
+        "AUTH_JWT_LEEWAY_SECS" => "auth.jwt_leeway_secs".into(),
+        "AUTH_REFRESH_TOKEN_LIFETIME_SECS" => "auth.refresh_token_lifetime_secs".into(),
+        "AUTH_EMAIL_VERIFICATION_LIFETIME_SECS" => "auth.email_verification_lifetime_secs".into(),
+        "AUTH_PASSWORD_RESET_LIFETIME_SECS" => "auth.password_reset_lifetime_secs".into(),
+        "AUTH_TWO_FACTOR_CHALLENGE_LIFETIME_SECS" => {
+          "auth.two_factor_challenge_lifetime_secs".into()
+        }
+        "AUTH_TOTP_KEY" => "auth.totp_key".into(),
+
+        "HTTP_LISTEN" => "http.listen".into(),
+        "HTTP_WORKERS" => "http.workers".into(),
+        "HTTP_KEEP_ALIVE_SECS" => "http.keep_alive_secs".into(),
+        "HTTP_PAYLOAD_LIMIT_BYTES" => "http.payload_limit_bytes".into(),
+        "HTTP_JSON_LIMIT_BYTES" => "http.json_limit_bytes".into(),
+        "HTTP_SHUTDOWN_TIMEOUT_SECS" => "http.shutdown_timeout_secs".into(),
+        "HTTP_TLS_CERT_PATH" => "http.tls.cert_path".into(),
+        "HTTP_TLS_KEY_PATH" => "http.tls.key_path".into(),
+        "HTTP_TRUSTED_PROXIES" => "http.trusted_proxies".into(),
+
+        "INSTANCE_ALLOW_UNVERIFIED_LOGIN" => "instance.allow_unverified_login".into(),
+        "INSTANCE_ALLOW_UNVERIFIED_POSTING" => "instance.allow_unverified_posting".into(),
+
+        "MAILER_TRANSPORT_KIND" => "mailer.transport.kind".into(),
+        "MAILER_TRANSPORT_HOST" => "mailer.transport.host".into(),
+        "MAILER_TRANSPORT_PORT" => "mailer.transport.port".into(),
+        "MAILER_TRANSPORT_USERNAME" => "mailer.transport.username".into(),
+        "MAILER_TRANSPORT_PASSWORD" => "mailer.transport.password".into(),
+        "MAILER_TRANSPORT_ENCRYPTION" => "mailer.transport.encryption".into(),
+        "MAILER_TRANSPORT_PATH" => "mailer.transport.path".into(),
+
+        "METRICS_ENABLED" => "metrics.enabled".into(),
+        "METRICS_LISTEN" => "metrics.listen".into(),
+
+        "STORAGE_BACKEND_KIND" => "storage.backend.kind".into(),
+        "STORAGE_BACKEND_PATH" => "storage.backend.path".into(),
+
+        "RATE_LIMIT_ENABLED" => "rate_limit.enabled".into(),
+        "RATE_LIMIT_DEFAULT_REQUESTS" => "rate_limit.default.requests".into(),
+        "RATE_LIMIT_DEFAULT_PERIOD_SECS" => "rate_limit.default.period_secs".into(),
+        "RATE_LIMIT_DEFAULT_BURST" => "rate_limit.default.burst".into(),
+
+        "TELEMETRY_LOG_FORMAT" => "telemetry.log_format".into(),
+        "TELEMETRY_LOG_FILTER" => "telemetry.log_filter".into(),
+        "TELEMETRY_OTLP_ENDPOINT" => "telemetry.otlp.endpoint".into(),
+        "TELEMETRY_OTLP_PROTOCOL" => "telemetry.otlp.protocol".into(),
+        "TELEMETRY_OTLP_SERVICE_NAME" => "telemetry.otlp.service_name".into(),
+        "TELEMETRY_OTLP_TIMEOUT_SECS" => "telemetry.otlp.timeout_secs".into(),
+
+        "THROTTLE_LOGIN_FREE_ATTEMPTS" => "throttle.login.free_attempts".into(),
+        "THROTTLE_LOGIN_BACKOFF_BASE_SECS" => "throttle.login.backoff_base_secs".into(),
+        "THROTTLE_LOGIN_BACKOFF_MAX_SECS" => "throttle.login.backoff_max_secs".into(),
+        "THROTTLE_LOGIN_LOCKOUT_THRESHOLD" => "throttle.login.lockout_threshold".into(),
+        "THROTTLE_LOGIN_LOCKOUT_SECS" => "throttle.login.lockout_secs".into(),
+        "THROTTLE_LOGIN_WINDOW_SECS" => "throttle.login.window_secs".into(),
+
+        "USERS_USERNAME_CHANGE_COOLDOWN_SECS" => "users.username_change_cooldown_secs".into(),
+        "USERS_USERNAME_GRACE_PERIOD_SECS" => "users.username_grace_period_secs".into(),
+        "USERS_RESERVED_USERNAMES" => "users.reserved_usernames".into(),
+        "USERS_DELETION_GRACE_PERIOD_SECS" => "users.deletion_grace_period_secs".into(),
+        "USERS_DELETION_PURGE_INTERVAL_SECS" => "users.deletion_purge_interval_secs".into(),
+        "USERS_EXPORT_LIFETIME_SECS" => "users.export_lifetime_secs".into(),
+
+        "AUTH_PASSWORD_MEMORY_COST_KIB" => "auth.password.memory_cost_kib".into(),
+
+        "WORKER_ID" => "worker_id".into(),
 
         _ => v.as_str().replace("_", ".").into(),
       }))
       // Environment variable aliases
       .merge(Env::raw().map(|v| match v.as_str() {
         "DATABASE_URL" => "db.primary.url".into(),
+        "RUST_LOG" => "telemetry.log_filter".into(),
         _ => v.into(),
       }))
   }
@@ -209,6 +365,16 @@ impl Server {
           .set_prefix("# Automatically generated by Whim. Any changes to the key\n# will result all users' crediential tokens will be invalid.\n");
       }
     }
+    if self.auth.totp_key.is_generated() {
+      let auth = tbl.entry("auth").or_insert(toml_edit::table());
+      if let Some(auth) = auth.as_table_mut() {
+        overriden = true;
+        auth["totp_key"] = toml_edit::value(&*self.auth.totp_key);
+        if let Some(decor) = auth.key_decor_mut("totp_key") {
+          decor.set_prefix("# Automatically generated by Whim. Any changes to the key\n# will lock out all users with two-factor authentication enabled.\n");
+        }
+      }
+    }
     overriden
   }
 }
@@ -0,0 +1,51 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use validator::Validate;
+
+/// Configuration for storing files generated by this server
+/// (such as users' data exports).
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Storage {
+  /// Where the files will be stored.
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) backend: StorageBackend,
+}
+
+impl Storage {
+  /// Gets the backend where the files will be stored.
+  pub const fn backend(&self) -> &StorageBackend {
+    &self.backend
+  }
+}
+
+/// Backend used to store files.
+///
+/// **Environment variables**:
+/// - `WHIM_STORAGE_BACKEND_KIND`
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum StorageBackend {
+  /// Files are kept in memory and lost after restarting.
+  ///
+  /// This is only useful for testing and development.
+  #[default]
+  Memory,
+  /// Files are written into a local directory.
+  Local {
+    /// **Environment variables**:
+    /// - `WHIM_STORAGE_BACKEND_PATH`
+    path: PathBuf,
+  },
+}
+
+impl StorageBackend {
+  /// Gets the directory path if the files are stored locally.
+  pub fn path(&self) -> Option<&Path> {
+    match self {
+      Self::Local { path } => Some(path),
+      Self::Memory => None,
+    }
+  }
+}
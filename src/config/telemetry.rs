@@ -0,0 +1,222 @@
+use serde::Deserialize;
+use std::{num::NonZeroU64, time::Duration};
+use tracing_subscriber::EnvFilter;
+use validator::Validate;
+
+/// Configuration for logging and exporting traces.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Telemetry {
+  /// How logs are written to the standard output.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_TELEMETRY_LOG_FORMAT`
+  #[serde(default)]
+  pub(crate) log_format: LogFormat,
+  /// Which logs and spans are recorded, written in the same
+  /// syntax as `RUST_LOG` (e.g. `info,whim=debug`).
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_TELEMETRY_LOG_FILTER` or `RUST_LOG`
+  #[serde(default = "Telemetry::default_log_filter")]
+  #[validate(
+    with = "Telemetry::validate_log_filter",
+    error = "Invalid log filter directives"
+  )]
+  pub(crate) log_filter: String,
+  /// Exports traces to an OpenTelemetry collector if it is set.
+  #[validate(nested, optional)]
+  pub(crate) otlp: Option<Otlp>,
+}
+
+/// How logs are written to the standard output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+  /// Human readable and multi-line logs.
+  #[default]
+  Pretty,
+  /// One JSON object per line, for log aggregators.
+  Json,
+}
+
+/// Configuration for exporting traces with the OpenTelemetry protocol (OTLP).
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Otlp {
+  /// Base URL of the OpenTelemetry collector, such as
+  /// `http://localhost:4317` for gRPC or `http://localhost:4318`
+  /// for HTTP. `/v1/traces` is appended to it when using HTTP.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_TELEMETRY_OTLP_ENDPOINT`
+  #[validate(with = "Otlp::validate_endpoint", error = "Invalid OTLP endpoint")]
+  pub(crate) endpoint: String,
+  /// **Environment variables**:
+  /// - `WHIM_TELEMETRY_OTLP_PROTOCOL`
+  #[serde(default)]
+  pub(crate) protocol: OtlpProtocol,
+  /// Name of this service shown in the collected traces.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_TELEMETRY_OTLP_SERVICE_NAME`
+  #[serde(default = "Otlp::default_service_name")]
+  #[validate(length(min = 1))]
+  pub(crate) service_name: String,
+  /// How long (in seconds) it waits for the collector
+  /// to accept a batch of spans.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_TELEMETRY_OTLP_TIMEOUT_SECS`
+  #[serde(default = "Otlp::default_timeout_secs")]
+  pub(crate) timeout_secs: NonZeroU64,
+}
+
+/// Transport used to export traces to the collector.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OtlpProtocol {
+  #[default]
+  Grpc,
+  /// Protobuf encoded spans over HTTP.
+  Http,
+}
+
+impl Telemetry {
+  /// How logs are written to the standard output.
+  pub const fn log_format(&self) -> LogFormat {
+    self.log_format
+  }
+
+  /// Filter directives of logs and spans.
+  pub fn log_filter(&self) -> &str {
+    &self.log_filter
+  }
+
+  /// Gets the [`Otlp`] config if traces are exported.
+  pub const fn otlp(&self) -> Option<&Otlp> {
+    self.otlp.as_ref()
+  }
+}
+
+impl Otlp {
+  /// Base URL of the OpenTelemetry collector.
+  pub fn endpoint(&self) -> &str {
+    &self.endpoint
+  }
+
+  /// Transport used to export traces to the collector.
+  pub const fn protocol(&self) -> OtlpProtocol {
+    self.protocol
+  }
+
+  /// Name of this service shown in the collected traces.
+  pub fn service_name(&self) -> &str {
+    &self.service_name
+  }
+
+  /// How long it waits for the collector to accept a batch of spans.
+  pub const fn timeout(&self) -> Duration {
+    Duration::from_secs(self.timeout_secs.get())
+  }
+}
+
+const DEFAULT_LOG_FILTER: &str = "debug";
+const DEFAULT_SERVICE_NAME: &str = "whim";
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+impl Telemetry {
+  // Required by serde
+  fn default_log_filter() -> String {
+    DEFAULT_LOG_FILTER.into()
+  }
+
+  // Required by validator
+  fn validate_log_filter(directives: &str) -> bool {
+    EnvFilter::builder().parse(directives).is_ok()
+  }
+}
+
+impl Otlp {
+  // Required by serde
+  fn default_service_name() -> String {
+    DEFAULT_SERVICE_NAME.into()
+  }
+
+  const fn default_timeout_secs() -> NonZeroU64 {
+    match NonZeroU64::new(DEFAULT_TIMEOUT_SECS) {
+      Some(v) => v,
+      None => panic!("DEFAULT_TIMEOUT_SECS is zero"),
+    }
+  }
+
+  // Required by validator
+  fn validate_endpoint(endpoint: &str) -> bool {
+    url::Url::parse(endpoint).is_ok_and(|v| matches!(v.scheme(), "http" | "https"))
+  }
+}
+
+impl Default for Telemetry {
+  fn default() -> Self {
+    Self {
+      log_format: LogFormat::default(),
+      log_filter: Self::default_log_filter(),
+      otlp: None,
+    }
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_deserialize() {
+    let telemetry = figment::Figment::new()
+      .merge(figment::providers::Serialized::default(
+        "log_format",
+        "json",
+      ))
+      .merge(figment::providers::Serialized::default(
+        "otlp.endpoint",
+        "http://localhost:4318",
+      ))
+      .merge(figment::providers::Serialized::default(
+        "otlp.protocol",
+        "http",
+      ))
+      .extract::<Telemetry>()
+      .unwrap();
+
+    assert_eq!(telemetry.log_format(), LogFormat::Json);
+    assert_eq!(telemetry.log_filter(), DEFAULT_LOG_FILTER);
+
+    let otlp = telemetry.otlp().unwrap();
+    assert_eq!(otlp.protocol(), OtlpProtocol::Http);
+    assert_eq!(otlp.service_name(), DEFAULT_SERVICE_NAME);
+    assert!(telemetry.validate().is_ok());
+  }
+
+  #[test]
+  fn test_validate() {
+    assert!(Telemetry::default().validate().is_ok());
+
+    let cfg = Telemetry {
+      log_filter: "info,whim=loud".into(),
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+
+    let cfg = Telemetry {
+      otlp: Some(Otlp {
+        endpoint: "localhost:4317".into(),
+        protocol: OtlpProtocol::Grpc,
+        service_name: DEFAULT_SERVICE_NAME.into(),
+        timeout_secs: Otlp::default_timeout_secs(),
+      }),
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+  }
+}
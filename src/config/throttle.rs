@@ -0,0 +1,241 @@
+use serde::Deserialize;
+use std::{
+  num::{NonZeroU32, NonZeroU64},
+  time::Duration,
+};
+use validator::{Validate, ValidateError};
+
+/// Configuration for slowing down repeated failed attempts
+/// of sensitive actions like logging in.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Throttle {
+  /// Where failed attempts are kept.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_STORAGE`
+  #[serde(default)]
+  pub(crate) storage: ThrottleStorage,
+  /// Policy applied to failed login attempts.
+  #[serde(default)]
+  #[validate(nested)]
+  pub(crate) login: LoginThrottle,
+}
+
+impl Throttle {
+  /// Gets where failed attempts are kept.
+  pub const fn storage(&self) -> ThrottleStorage {
+    self.storage
+  }
+
+  /// Gets the policy applied to failed login attempts.
+  pub const fn login(&self) -> &LoginThrottle {
+    &self.login
+  }
+}
+
+/// Storage of failed attempts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleStorage {
+  /// Failed attempts are kept in the primary database so
+  /// they are shared between multiple server instances.
+  #[default]
+  Database,
+  /// Failed attempts are kept in memory and lost after
+  /// the server restarts.
+  ///
+  /// This is only useful for testing and development.
+  Memory,
+}
+
+/// Policy applied to failed login attempts of an account and
+/// of a client's IP address separately.
+///
+/// After `free_attempts` failed attempts, every failed attempt
+/// blocks further attempts with an exponentially increasing delay
+/// starting from `backoff_base_secs` up to `backoff_max_secs`.
+/// Once `lockout_threshold` failed attempts are reached, further
+/// attempts are blocked for `lockout_secs`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginThrottle {
+  /// Number of failed attempts allowed without any delay.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_LOGIN_FREE_ATTEMPTS`
+  #[serde(default = "LoginThrottle::default_free_attempts")]
+  pub(crate) free_attempts: u32,
+  /// Delay (in seconds) after the first failed attempt
+  /// beyond the free attempts.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_LOGIN_BACKOFF_BASE_SECS`
+  #[serde(default = "LoginThrottle::default_backoff_base_secs")]
+  pub(crate) backoff_base_secs: NonZeroU64,
+  /// Maximum delay (in seconds) before the lockout kicks in.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_LOGIN_BACKOFF_MAX_SECS`
+  #[serde(default = "LoginThrottle::default_backoff_max_secs")]
+  pub(crate) backoff_max_secs: NonZeroU64,
+  /// Number of failed attempts until further attempts are locked out.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_LOGIN_LOCKOUT_THRESHOLD`
+  #[serde(default = "LoginThrottle::default_lockout_threshold")]
+  pub(crate) lockout_threshold: NonZeroU32,
+  /// How long (in seconds) a lockout lasts.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_LOGIN_LOCKOUT_SECS`
+  #[serde(default = "LoginThrottle::default_lockout_secs")]
+  pub(crate) lockout_secs: NonZeroU64,
+  /// How long (in seconds) failed attempts are remembered
+  /// since the last failed attempt.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_THROTTLE_LOGIN_WINDOW_SECS`
+  #[serde(default = "LoginThrottle::default_window_secs")]
+  pub(crate) window_secs: NonZeroU64,
+}
+
+impl LoginThrottle {
+  /// Number of failed attempts allowed without any delay.
+  pub const fn free_attempts(&self) -> u32 {
+    self.free_attempts
+  }
+
+  /// Delay after the first failed attempt beyond the free attempts.
+  pub const fn backoff_base(&self) -> Duration {
+    Duration::from_secs(self.backoff_base_secs.get())
+  }
+
+  /// Maximum delay before the lockout kicks in.
+  pub const fn backoff_max(&self) -> Duration {
+    Duration::from_secs(self.backoff_max_secs.get())
+  }
+
+  /// Number of failed attempts until further attempts are locked out.
+  pub const fn lockout_threshold(&self) -> u32 {
+    self.lockout_threshold.get()
+  }
+
+  /// How long a lockout lasts.
+  pub const fn lockout(&self) -> Duration {
+    Duration::from_secs(self.lockout_secs.get())
+  }
+
+  /// How long failed attempts are remembered since the last failed attempt.
+  pub const fn window(&self) -> Duration {
+    Duration::from_secs(self.window_secs.get())
+  }
+}
+
+impl LoginThrottle {
+  const DEFAULT_FREE_ATTEMPTS: u32 = 5;
+  const DEFAULT_BACKOFF_BASE_SECS: u64 = 1;
+  const DEFAULT_BACKOFF_MAX_SECS: u64 = 60 * 5;
+  const DEFAULT_LOCKOUT_THRESHOLD: u32 = 20;
+  const DEFAULT_LOCKOUT_SECS: u64 = 60 * 15;
+  const DEFAULT_WINDOW_SECS: u64 = 60 * 60;
+
+  // Required by serde
+  const fn default_free_attempts() -> u32 {
+    Self::DEFAULT_FREE_ATTEMPTS
+  }
+
+  const fn default_backoff_base_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_BACKOFF_BASE_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_BACKOFF_BASE_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_backoff_max_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_BACKOFF_MAX_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_BACKOFF_MAX_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_lockout_threshold() -> NonZeroU32 {
+    match NonZeroU32::new(Self::DEFAULT_LOCKOUT_THRESHOLD) {
+      Some(n) => n,
+      None => panic!("DEFAULT_LOCKOUT_THRESHOLD is accidentally set to 0"),
+    }
+  }
+
+  const fn default_lockout_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_LOCKOUT_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_LOCKOUT_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_window_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_WINDOW_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_WINDOW_SECS is accidentally set to 0"),
+    }
+  }
+}
+
+impl Default for LoginThrottle {
+  fn default() -> Self {
+    Self {
+      free_attempts: Self::default_free_attempts(),
+      backoff_base_secs: Self::default_backoff_base_secs(),
+      backoff_max_secs: Self::default_backoff_max_secs(),
+      lockout_threshold: Self::default_lockout_threshold(),
+      lockout_secs: Self::default_lockout_secs(),
+      window_secs: Self::default_window_secs(),
+    }
+  }
+}
+
+impl Validate for LoginThrottle {
+  /// It checks if the delays are in order and failed attempts are
+  /// remembered long enough for every delay to take effect.
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    if self.backoff_max_secs < self.backoff_base_secs {
+      let mut error = ValidateError::msg_builder();
+      error.insert("Maximum backoff delay must not be less than the base delay");
+      fields.insert("backoff_max_secs", error.build());
+    }
+    if self.lockout_threshold.get() <= self.free_attempts {
+      let mut error = ValidateError::msg_builder();
+      error.insert("Lockout threshold must be greater than the free attempts");
+      fields.insert("lockout_threshold", error.build());
+    }
+    if self.window_secs < self.lockout_secs || self.window_secs < self.backoff_max_secs {
+      let mut error = ValidateError::msg_builder();
+      error.insert("Window must not be shorter than the lockout and maximum backoff delay");
+      fields.insert("window_secs", error.build());
+    }
+    fields.build().into_result()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate() {
+    assert_eq!(LoginThrottle::default().validate(), Ok(()));
+
+    let cfg = LoginThrottle {
+      free_attempts: 20,
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+
+    let cfg = LoginThrottle {
+      window_secs: NonZeroU64::MIN,
+      ..Default::default()
+    };
+    assert!(cfg.validate().is_err());
+  }
+}
@@ -0,0 +1,142 @@
+use serde::Deserialize;
+use std::{num::NonZeroU64, time::Duration};
+use validator::Validate;
+
+use crate::types::username;
+
+/// Policies about users' accounts.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Users {
+  /// How long (in seconds) a user has to wait before
+  /// changing their username again.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_USERS_USERNAME_CHANGE_COOLDOWN_SECS`
+  #[serde(default = "Users::default_username_change_cooldown_secs")]
+  pub(crate) username_change_cooldown_secs: u64,
+  /// How long (in seconds) an old username redirects to its
+  /// previous owner's profile before anyone else can claim it.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_USERS_USERNAME_GRACE_PERIOD_SECS`
+  #[serde(default = "Users::default_username_grace_period_secs")]
+  pub(crate) username_grace_period_secs: u64,
+  /// Usernames nobody can register or change their username into.
+  ///
+  /// They are compared by their skeletons (see [`username::skeleton`]),
+  /// so reserving `admin` also reserves `Adm1n` for example.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_USERS_RESERVED_USERNAMES` (e.g. `[me,admin]`)
+  #[serde(default = "Users::default_reserved_usernames")]
+  pub(crate) reserved_usernames: Vec<String>,
+  /// How long (in seconds) users can cancel deleting
+  /// their account before it gets purged.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_USERS_DELETION_GRACE_PERIOD_SECS`
+  #[serde(default = "Users::default_deletion_grace_period_secs")]
+  pub(crate) deletion_grace_period_secs: u64,
+  /// How often (in seconds) accounts past their deletion
+  /// grace period are purged.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_USERS_DELETION_PURGE_INTERVAL_SECS`
+  #[serde(default = "Users::default_deletion_purge_interval_secs")]
+  pub(crate) deletion_purge_interval_secs: NonZeroU64,
+  /// How long (in seconds) a data export can be downloaded
+  /// after it is built.
+  ///
+  /// **Environment variables**:
+  /// - `WHIM_USERS_EXPORT_LIFETIME_SECS`
+  #[serde(default = "Users::default_export_lifetime_secs")]
+  pub(crate) export_lifetime_secs: NonZeroU64,
+}
+
+impl Users {
+  /// How long a user has to wait before changing their username again.
+  pub const fn username_change_cooldown(&self) -> Duration {
+    Duration::from_secs(self.username_change_cooldown_secs)
+  }
+
+  /// How long an old username stays reserved for its previous owner.
+  pub const fn username_grace_period(&self) -> Duration {
+    Duration::from_secs(self.username_grace_period_secs)
+  }
+
+  /// How long users can cancel deleting their account.
+  pub const fn deletion_grace_period(&self) -> Duration {
+    Duration::from_secs(self.deletion_grace_period_secs)
+  }
+
+  /// How often accounts past their deletion grace period are purged.
+  pub const fn deletion_purge_interval(&self) -> Duration {
+    Duration::from_secs(self.deletion_purge_interval_secs.get())
+  }
+
+  /// How long a data export can be downloaded after it is built.
+  pub const fn export_lifetime(&self) -> Duration {
+    Duration::from_secs(self.export_lifetime_secs.get())
+  }
+
+  /// Usernames nobody can take.
+  pub fn reserved_usernames(&self) -> &[String] {
+    &self.reserved_usernames
+  }
+}
+
+impl Users {
+  const DEFAULT_USERNAME_CHANGE_COOLDOWN_SECS: u64 = 60 * 60 * 24 * 30;
+  const DEFAULT_USERNAME_GRACE_PERIOD_SECS: u64 = 60 * 60 * 24 * 30;
+  const DEFAULT_DELETION_GRACE_PERIOD_SECS: u64 = 60 * 60 * 24 * 30;
+  const DEFAULT_DELETION_PURGE_INTERVAL_SECS: u64 = 60 * 60;
+  const DEFAULT_EXPORT_LIFETIME_SECS: u64 = 60 * 60 * 24 * 7;
+
+  // Required by serde
+  const fn default_username_change_cooldown_secs() -> u64 {
+    Self::DEFAULT_USERNAME_CHANGE_COOLDOWN_SECS
+  }
+
+  const fn default_username_grace_period_secs() -> u64 {
+    Self::DEFAULT_USERNAME_GRACE_PERIOD_SECS
+  }
+
+  const fn default_deletion_grace_period_secs() -> u64 {
+    Self::DEFAULT_DELETION_GRACE_PERIOD_SECS
+  }
+
+  const fn default_deletion_purge_interval_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_DELETION_PURGE_INTERVAL_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_DELETION_PURGE_INTERVAL_SECS is accidentally set to 0"),
+    }
+  }
+
+  const fn default_export_lifetime_secs() -> NonZeroU64 {
+    match NonZeroU64::new(Self::DEFAULT_EXPORT_LIFETIME_SECS) {
+      Some(n) => n,
+      None => panic!("DEFAULT_EXPORT_LIFETIME_SECS is accidentally set to 0"),
+    }
+  }
+
+  fn default_reserved_usernames() -> Vec<String> {
+    username::DEFAULT_RESERVED
+      .iter()
+      .map(ToString::to_string)
+      .collect()
+  }
+}
+
+impl Default for Users {
+  fn default() -> Self {
+    Self {
+      username_change_cooldown_secs: Self::default_username_change_cooldown_secs(),
+      username_grace_period_secs: Self::default_username_grace_period_secs(),
+      reserved_usernames: Self::default_reserved_usernames(),
+      deletion_grace_period_secs: Self::default_deletion_grace_period_secs(),
+      deletion_purge_interval_secs: Self::default_deletion_purge_interval_secs(),
+      export_lifetime_secs: Self::default_export_lifetime_secs(),
+    }
+  }
+}
@@ -57,6 +57,10 @@ pub type Result<T> = error_stack::Result<T, Error>;
 pub trait ErrorExt2 {
   fn is_unhealthy(&self) -> bool;
   fn is_readonly(&self) -> bool;
+
+  /// Gets the name of the unique constraint (or unique index)
+  /// that has been violated, if any.
+  fn unique_violation(&self) -> Option<&str>;
 }
 
 impl ErrorExt2 for error_stack::Report<Error> {
@@ -73,4 +77,11 @@ impl ErrorExt2 for error_stack::Report<Error> {
       .map(|v| matches!(v, Error::Readonly))
       .unwrap_or_default()
   }
+
+  fn unique_violation(&self) -> Option<&str> {
+    match self.downcast_ref::<Error>()? {
+      Error::Internal(sqlx::Error::Database(e)) if e.is_unique_violation() => e.constraint(),
+      _ => None,
+    }
+  }
 }
@@ -0,0 +1,100 @@
+use sqlx::migrate::Migrator;
+use std::collections::HashMap;
+
+use crate::database::{error::ErrorExt, Connection, Result};
+
+/// Migrations from the `migrations` directory which are
+/// embedded into the binary at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Status of the database migrations compared to the
+/// migrations known by this build of the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationStatus {
+  /// Migrations which are successfully applied.
+  pub applied: usize,
+  /// Migrations which are not yet applied to the database.
+  pub pending: usize,
+  /// Whether a migration has failed halfway and the database
+  /// needs to be repaired manually.
+  pub dirty: bool,
+}
+
+impl MigrationStatus {
+  /// Whether every known migration is applied successfully.
+  #[must_use]
+  pub const fn is_up_to_date(&self) -> bool {
+    self.pending == 0 && !self.dirty
+  }
+}
+
+/// Compares the applied migrations in the database with the
+/// embedded migrations. Every migration is considered pending
+/// if the database has never been migrated before.
+#[tracing::instrument(name = "db.migration_status", skip_all)]
+pub async fn status(conn: &mut Connection) -> Result<MigrationStatus> {
+  let exists = sqlx::query_scalar::<_, bool>("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+    .fetch_one(&mut *conn)
+    .await
+    .into_db_error()?;
+
+  let applied = if exists {
+    sqlx::query_as::<_, (i64, bool)>(r#"SELECT version, success FROM "_sqlx_migrations""#)
+      .fetch_all(&mut *conn)
+      .await
+      .into_db_error()?
+      .into_iter()
+      .collect::<HashMap<_, _>>()
+  } else {
+    HashMap::new()
+  };
+
+  Ok(compare(&MIGRATOR, &applied))
+}
+
+fn compare(migrator: &Migrator, applied: &HashMap<i64, bool>) -> MigrationStatus {
+  let mut status = MigrationStatus::default();
+  for migration in migrator
+    .iter()
+    .filter(|v| !v.migration_type.is_down_migration())
+  {
+    match applied.get(&migration.version) {
+      Some(true) => status.applied += 1,
+      Some(false) => status.dirty = true,
+      None => status.pending += 1,
+    }
+  }
+  status
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_compare() {
+    let versions = MIGRATOR
+      .iter()
+      .filter(|v| !v.migration_type.is_down_migration())
+      .map(|v| v.version)
+      .collect::<Vec<_>>();
+    assert!(!versions.is_empty());
+
+    let status = compare(&MIGRATOR, &HashMap::new());
+    assert_eq!(status.pending, versions.len());
+    assert!(!status.is_up_to_date());
+
+    let mut applied = versions
+      .iter()
+      .map(|v| (*v, true))
+      .collect::<HashMap<_, _>>();
+    let status = compare(&MIGRATOR, &applied);
+    assert_eq!(status.applied, versions.len());
+    assert!(status.is_up_to_date());
+
+    applied.insert(versions[versions.len() - 1], false);
+    let status = compare(&MIGRATOR, &applied);
+    assert!(status.dirty);
+    assert!(!status.is_up_to_date());
+  }
+}
@@ -1,6 +1,7 @@
 mod pool;
 
 pub mod error;
+pub mod migrations;
 pub use error::{Error, Result};
 pub use pool::Pool;
 
@@ -1,4 +1,5 @@
 use error_stack::{Report, ResultExt};
+use prometheus::{Histogram, HistogramTimer};
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
 use std::str::FromStr;
 
@@ -22,6 +23,7 @@ use crate::{
 #[derive(Clone)]
 pub struct Pool {
   pool: sqlx::PgPool,
+  acquire_duration: Histogram,
 }
 
 impl Pool {
@@ -30,7 +32,7 @@ impl Pool {
   pub async fn new(
     global_cfg: &config::Database,
     pool_cfg: &config::DbPoolConfig,
-    // time_to_obtain_connection_metric: Histogram,
+    acquire_duration: Histogram,
   ) -> Result<Self> {
     let mut pool_opts = PgPoolOptions::new()
       .acquire_timeout(global_cfg.timeout())
@@ -51,6 +53,7 @@ impl Pool {
 
     let pool = Self {
       pool: pool_opts.connect_lazy_with(connect_opts),
+      acquire_duration,
     };
 
     match pool.wait_until_healthy().await {
@@ -76,6 +79,11 @@ impl Pool {
     self.pool.size()
   }
 
+  /// Gets the idle connections of a database pool
+  pub fn idle_connections(&self) -> usize {
+    self.pool.num_idle()
+  }
+
   /// Checks if the database pool is healthy.
   ///
   /// This function uses `.connections()` method (a method
@@ -86,6 +94,19 @@ impl Pool {
     self.connections() > 0
   }
 
+  /// Closes every connection of the database pool and waits
+  /// until they're returned to the pool. Getting a connection
+  /// from a closed pool always fails.
+  #[tracing::instrument(name = "db.close", skip(self))]
+  pub async fn close(&self) {
+    self.pool.close().await;
+  }
+
+  /// Whether [`Pool::close`] has been called.
+  pub fn is_closed(&self) -> bool {
+    self.pool.is_closed()
+  }
+
   /// It attempts to start a database transaction and returns
   /// a connection with transaction is active until it is dropped.
   ///
@@ -94,27 +115,46 @@ impl Pool {
   #[doc(hidden)]
   #[tracing::instrument(name = "db.transaction", skip(self))]
   pub async fn begin(&self) -> Result<Transaction<'_>> {
-    if let Some(inner) = self.pool.try_begin().await.into_db_error()? {
-      Ok(inner)
-    } else if !self.is_healthy() {
-      Err(Error::UnhealthyPool.into())
-    } else {
-      let result = self.pool.begin().await;
-      result.map_err(|e| Report::new(Error::Internal(e)))
-    }
+    let timer = self.acquire_duration.start_timer();
+    let result = match self.pool.try_begin().await.into_db_error() {
+      Ok(Some(inner)) => Ok(inner),
+      Ok(None) if !self.is_healthy() => Err(Error::UnhealthyPool.into()),
+      Ok(None) => {
+        let result = self.pool.begin().await;
+        result.map_err(|e| Report::new(Error::Internal(e)))
+      }
+      Err(err) => Err(err),
+    };
+    observe_if_ok(timer, &result);
+    result
   }
 
   /// It attempts to get an active database connection.
   #[tracing::instrument(name = "db.connect", skip(self))]
   pub async fn get(&self) -> Result<PoolConnection> {
-    if let Some(inner) = self.pool.try_acquire() {
+    let timer = self.acquire_duration.start_timer();
+    let result = if let Some(inner) = self.pool.try_acquire() {
       Ok(inner)
     } else if !self.is_healthy() {
       Err(Error::UnhealthyPool.into())
     } else {
       let result = self.pool.acquire().await;
       result.map_err(|e| Report::new(Error::Internal(e)))
-    }
+    };
+    observe_if_ok(timer, &result);
+    result
+  }
+
+  /// Checks whether the database behind this pool only accepts
+  /// read-only transactions (a hot standby or a primary
+  /// under maintenance for example).
+  #[tracing::instrument(name = "db.is_read_only", skip(self))]
+  pub async fn is_read_only(&self) -> Result<bool> {
+    let mut conn = self.get().await?;
+    sqlx::query_scalar::<_, bool>("SELECT current_setting('transaction_read_only') = 'on'")
+      .fetch_one(&mut *conn)
+      .await
+      .into_db_error()
   }
 
   /// This function will try to wait for a database connection
@@ -133,3 +173,13 @@ impl Pool {
     }
   }
 }
+
+// Failed attempts are not observed, otherwise unhealthy pools
+// (which fail immediately) would make the latency look better.
+fn observe_if_ok<T>(timer: HistogramTimer, result: &Result<T>) {
+  if result.is_ok() {
+    timer.observe_duration();
+  } else {
+    timer.stop_and_discard();
+  }
+}
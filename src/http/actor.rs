@@ -1,30 +1,82 @@
-use actix_web::{http::header, web, FromRequest};
+use actix_web::{http::header, web, FromRequest, HttpMessage};
 use futures::future::{ready, LocalBoxFuture};
+use std::{collections::BTreeSet, time::Duration};
 use thiserror::Error;
 
-use crate::{schema::User, App};
+use crate::{
+  auth::{api_token, token},
+  schema::{ApiToken, Session, User},
+  types::Scope,
+  App,
+};
 
 use super::{Error, Jwt};
 
 #[derive(Debug)]
 pub enum Actor {
   Anonymous,
+  /// A user logged in with a JWT. They can access everything.
   User(User),
+  /// A user authenticated with one of their API tokens.
+  /// They can only access routes granted by the token's scopes.
+  ApiToken {
+    user: User,
+    scopes: BTreeSet<Scope>,
+  },
 }
 
 impl Actor {
+  /// Gets the user logged in with a JWT.
+  ///
+  /// API tokens are rejected. Routes accessible with API tokens
+  /// must use [`Actor::require_scope`] instead.
   pub fn get_user(self) -> Result<User, Error> {
     #[derive(Debug, Error)]
     #[error("Attempt to access user-only route")]
     struct Unauthorized;
+
+    #[derive(Debug, Error)]
+    #[error("Attempt to access route not accessible with API tokens")]
+    struct ApiTokenNotAllowed;
+
     match self {
       Self::User(n) => Ok(n),
+      Self::ApiToken { .. } => Err(Error::from_context(
+        crate::types::Error::Unauthorized,
+        ApiTokenNotAllowed,
+      )),
       Self::Anonymous => Err(Error::from_context(
         crate::types::Error::Unauthorized,
         Unauthorized,
       )),
     }
   }
+
+  /// Gets the user if they're logged in with a JWT or
+  /// with an API token granted with the `scope`.
+  pub fn require_scope(self, scope: Scope) -> Result<User, Error> {
+    #[derive(Debug, Error)]
+    #[error("API token is not granted with the required scope")]
+    struct MissingScope;
+
+    match self {
+      Self::ApiToken { user, scopes } if scopes.contains(&scope) => Ok(user),
+      Self::ApiToken { .. } => Err(Error::from_context(
+        crate::types::Error::MissingScope { scope },
+        MissingScope,
+      )),
+      actor => actor.get_user(),
+    }
+  }
+
+  /// Whether the actor can access routes which require the `scope`.
+  pub fn has_scope(&self, scope: Scope) -> bool {
+    match self {
+      Self::Anonymous => false,
+      Self::User(..) => true,
+      Self::ApiToken { scopes, .. } => scopes.contains(&scope),
+    }
+  }
 }
 
 impl FromRequest for Actor {
@@ -52,10 +104,45 @@ impl FromRequest for Actor {
         ))));
       };
 
+      if api_token::is_api_token(token) {
+        let app = app.clone();
+        let token_hash = token::hash(token);
+        let looked_up = req.extensions_mut().remove::<LookedUpApiToken>();
+        return Box::pin(async move {
+          let api_token = if let Some(LookedUpApiToken(api_token)) = looked_up {
+            api_token
+          } else {
+            let mut conn = app.db_read_prefer_primary().await?;
+            ApiToken::by_token_hash(&mut conn, &token_hash).await?
+          };
+          from_api_token(&app, api_token).await
+        });
+      }
+
+      let jwt = match Jwt::decode(token, app.config.auth()) {
+        Ok(jwt) => jwt,
+        Err(report) => {
+          return Box::pin(ready(Err(Error::from_report(
+            crate::types::Error::Unauthorized,
+            report,
+          ))));
+        }
+      };
+
       let app = app.clone();
-      let jwt = Jwt::decode(token, app.as_ref());
       Box::pin(async move {
+        #[derive(Debug, Error)]
+        #[error("Session of the access token has been revoked or expired")]
+        struct InactiveSession;
+
         let mut conn = app.db_read_prefer_primary().await?;
+        if !Session::is_active_by_id(&mut conn, jwt.session_id, jwt.user_id).await? {
+          return Err(Error::from_context(
+            crate::types::Error::Unauthorized,
+            InactiveSession,
+          ));
+        }
+
         if let Some(user) = User::by_id(&mut *conn, jwt.user_id).await? {
           Ok(Actor::User(user))
         } else {
@@ -67,3 +154,56 @@ impl FromRequest for Actor {
     }
   }
 }
+
+/// API token which has already been looked up by the
+/// [rate limiter](super::rate_limit) for the current request.
+#[derive(Debug)]
+pub(super) struct LookedUpApiToken(pub(super) Option<ApiToken>);
+
+async fn from_api_token(app: &App, api_token: Option<ApiToken>) -> Result<Actor, Error> {
+  #[derive(Debug, Error)]
+  #[error("Invalid or expired API token")]
+  struct InvalidApiToken;
+
+  // Writing on every request is wasteful, it only needs to be roughly accurate
+  const TOUCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+  let Some(api_token) = api_token else {
+    return Err(Error::from_context(
+      crate::types::Error::Unauthorized,
+      InvalidApiToken,
+    ));
+  };
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(user) = User::by_id(&mut conn, api_token.user_id).await? else {
+    return Ok(Actor::Anonymous);
+  };
+  drop(conn);
+
+  let touch_before = crate::util::ago(TOUCH_INTERVAL);
+  if api_token
+    .last_used_at
+    .map_or(true, |last_used_at| last_used_at < touch_before)
+  {
+    let result = async {
+      let mut conn = app.db_write().await?;
+      ApiToken::touch(&mut conn, api_token.id).await?;
+      Ok::<_, Error>(())
+    }
+    .await;
+
+    if let Err(error) = result {
+      tracing::warn!(%error, "failed to update last used time of an API token");
+    }
+  }
+
+  // Scopes removed from the server are ignored
+  let scopes = api_token
+    .scopes
+    .iter()
+    .filter_map(|v| v.parse::<Scope>().ok())
+    .collect();
+
+  Ok(Actor::ApiToken { user, scopes })
+}
@@ -0,0 +1,210 @@
+use actix_web::{
+  http::header::{self, HeaderMap},
+  web, FromRequest,
+};
+use futures::future::{ready, Ready};
+use std::{
+  convert::Infallible,
+  net::{IpAddr, SocketAddr},
+};
+
+use crate::{config::IpNetwork, App};
+
+/// Information about the client who sent the request.
+///
+/// The user agent is provided by the client so it should only be used
+/// for informational purposes. The IP address comes from the connection
+/// unless it is made by one of the [trusted proxies], which makes it
+/// suitable for rate limiting and throttling.
+///
+/// [trusted proxies]: crate::config::Http::trusted_proxies
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+  pub user_agent: Option<String>,
+  pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+  #[must_use]
+  pub fn from_request(req: &actix_web::HttpRequest) -> Self {
+    let user_agent = req
+      .headers()
+      .get(header::USER_AGENT)
+      .and_then(|v| v.to_str().ok())
+      .map(ToString::to_string);
+
+    let trusted_proxies = req
+      .app_data::<web::Data<App>>()
+      .map(|app| app.config.http().trusted_proxies())
+      .unwrap_or_default();
+
+    let peer = req.peer_addr().map(|addr| addr.ip());
+    let ip_address = resolve_ip_address(peer, &forwarded_for(req.headers()), trusted_proxies)
+      .map(|ip| ip.to_string());
+
+    Self {
+      user_agent,
+      ip_address,
+    }
+  }
+}
+
+impl FromRequest for ClientInfo {
+  type Error = Infallible;
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(
+    req: &actix_web::HttpRequest,
+    _payload: &mut actix_web::dev::Payload,
+  ) -> Self::Future {
+    ready(Ok(ClientInfo::from_request(req)))
+  }
+}
+
+/// Finds the client's address by walking the forwarded addresses
+/// backwards (from the nearest proxy) as long as the hop that
+/// added them is trusted.
+///
+/// A missing peer address means the request came from a Unix
+/// socket, which is always trusted.
+fn resolve_ip_address(
+  peer: Option<IpAddr>,
+  forwarded_for: &[Option<IpAddr>],
+  trusted_proxies: &[IpNetwork],
+) -> Option<IpAddr> {
+  let is_trusted = |ip: Option<IpAddr>| {
+    ip.map_or(true, |ip| {
+      trusted_proxies.iter().any(|network| network.contains(&ip))
+    })
+  };
+
+  let mut client = peer;
+  for hop in forwarded_for.iter().rev() {
+    if !is_trusted(client) {
+      break;
+    }
+    match hop {
+      Some(ip) => client = Some(*ip),
+      // Obfuscated or malformed, nothing after it can be trusted
+      None => break,
+    }
+  }
+  client
+}
+
+/// Addresses from the `Forwarded` header (or `X-Forwarded-For`
+/// if there is none), from the client to the nearest proxy.
+fn forwarded_for(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+  let forwarded = headers
+    .get_all(header::FORWARDED)
+    .filter_map(|v| v.to_str().ok())
+    .flat_map(|v| v.split(','))
+    .filter_map(|element| {
+      element.split(';').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("for").then_some(value)
+      })
+    })
+    .map(parse_node)
+    .collect::<Vec<_>>();
+
+  if !forwarded.is_empty() {
+    return forwarded;
+  }
+
+  headers
+    .get_all(header::X_FORWARDED_FOR)
+    .filter_map(|v| v.to_str().ok())
+    .flat_map(|v| v.split(','))
+    .map(parse_node)
+    .collect()
+}
+
+/// Parses a node like `192.0.2.1`, `"[2001:db8::1]:8080"` or `unknown`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+  let node = node.trim().trim_matches('"');
+  node
+    .parse::<IpAddr>()
+    .or_else(|_| node.parse::<SocketAddr>().map(|addr| addr.ip()))
+    .or_else(|_| node.trim_start_matches('[').trim_end_matches(']').parse())
+    .ok()
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use actix_web::http::header::HeaderValue;
+
+  fn ip(value: &str) -> IpAddr {
+    value.parse().unwrap()
+  }
+
+  fn network(value: &str) -> IpNetwork {
+    IpNetwork::try_from(value.to_string()).unwrap()
+  }
+
+  #[test]
+  fn test_parse_node() {
+    assert_eq!(parse_node(" 192.0.2.1"), Some(ip("192.0.2.1")));
+    assert_eq!(parse_node("192.0.2.1:4711"), Some(ip("192.0.2.1")));
+    assert_eq!(
+      parse_node("\"[2001:db8::1]:4711\""),
+      Some(ip("2001:db8::1"))
+    );
+    assert_eq!(parse_node("[2001:db8::1]"), Some(ip("2001:db8::1")));
+    assert_eq!(parse_node("unknown"), None);
+    assert_eq!(parse_node("_hidden"), None);
+  }
+
+  #[test]
+  fn test_forwarded_for() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      header::X_FORWARDED_FOR,
+      HeaderValue::from_static("203.0.113.1, 10.0.0.1"),
+    );
+    assert_eq!(
+      forwarded_for(&headers),
+      [Some(ip("203.0.113.1")), Some(ip("10.0.0.1"))]
+    );
+
+    // `Forwarded` takes precedence over `X-Forwarded-For`
+    headers.insert(
+      header::FORWARDED,
+      HeaderValue::from_static("for=198.51.100.1;proto=https, for=unknown"),
+    );
+    assert_eq!(forwarded_for(&headers), [Some(ip("198.51.100.1")), None]);
+  }
+
+  #[test]
+  fn test_resolve_ip_address() {
+    let trusted = [network("10.0.0.0/8")];
+    let forwarded = [Some(ip("203.0.113.1")), Some(ip("10.0.0.2"))];
+
+    // forwarded addresses from untrusted peers are ignored
+    let peer = Some(ip("198.51.100.1"));
+    assert_eq!(resolve_ip_address(peer, &forwarded, &trusted), peer);
+    assert_eq!(resolve_ip_address(peer, &forwarded, &[]), peer);
+
+    // only the hops added by trusted proxies are skipped
+    let peer = Some(ip("10.0.0.1"));
+    assert_eq!(
+      resolve_ip_address(peer, &forwarded, &trusted),
+      Some(ip("203.0.113.1"))
+    );
+    let spoofed = [Some(ip("192.0.2.1")), Some(ip("203.0.113.1"))];
+    assert_eq!(
+      resolve_ip_address(peer, &spoofed, &trusted),
+      Some(ip("203.0.113.1"))
+    );
+    assert_eq!(resolve_ip_address(peer, &[None], &trusted), peer);
+
+    // Unix sockets
+    assert_eq!(
+      resolve_ip_address(None, &forwarded, &trusted),
+      Some(ip("203.0.113.1"))
+    );
+    assert_eq!(resolve_ip_address(None, &[], &trusted), None);
+  }
+}
@@ -0,0 +1,3 @@
+mod refresh;
+
+pub use refresh::*;
@@ -0,0 +1,25 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use validator::Validate;
+
+use crate::{
+  http::{session, Error},
+  types::form::auth::refresh,
+  App,
+};
+
+#[tracing::instrument]
+pub async fn refresh(
+  app: web::Data<App>,
+  form: Json<refresh::Request>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  let tokens = session::refresh(&app, &form.refresh_token).await?;
+  Ok(HttpResponse::Ok().json(refresh::Response {
+    token: tokens.access_token,
+    refresh_token: tokens.refresh_token,
+  }))
+}
@@ -0,0 +1,83 @@
+use actix_web::{
+  http::header::{ContentDisposition, DispositionParam, DispositionType},
+  web, HttpResponse,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+  http::{data_export, error::ErrorStackContext, Error},
+  schema::DataExport,
+  types::{
+    id::{marker::DataExportMarker, Id},
+    Timestamp,
+  },
+  App,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadQuery {
+  expires: i64,
+  signature: String,
+}
+
+/// Downloads the archive of a data export through its signed URL.
+///
+/// The archive is deleted right after it is downloaded,
+/// so the URL only works once.
+#[tracing::instrument(skip(query))]
+pub async fn download(
+  app: web::Data<App>,
+  path: web::Path<Id<DataExportMarker>>,
+  query: web::Query<DownloadQuery>,
+) -> Result<HttpResponse, Error> {
+  #[derive(Debug, Error)]
+  #[error("Data export is not available")]
+  struct Unavailable;
+
+  let unavailable = || Error::from_context(crate::types::Error::NotFound, Unavailable);
+
+  let id = path.into_inner();
+  let now = Timestamp::now().timestamp();
+  if !data_export::verify_signature(app.config.auth(), id, query.expires, &query.signature, now) {
+    return Err(unavailable());
+  }
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(export) = DataExport::downloadable(&mut conn, id).await? else {
+    return Err(unavailable());
+  };
+  drop(conn);
+
+  // The archive is read before claiming the export so the
+  // URL still works if reading it fails
+  let data = match export.storage_key.as_deref() {
+    Some(key) => app.storage.get(key).await.into_http_result()?,
+    None => None,
+  };
+  let Some(data) = data else {
+    return Err(unavailable());
+  };
+
+  // Someone else might have downloaded it in the meantime
+  let mut conn = app.db_write().await?;
+  let Some(export) = DataExport::claim_download(&mut conn, id).await? else {
+    return Err(unavailable());
+  };
+  drop(conn);
+
+  // The export is already claimed, the cleanup job removes it later on failure
+  if let Err(error) = data_export::remove(&app, &export).await {
+    tracing::warn!(%error, "failed to remove downloaded data export");
+  }
+
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/zip")
+      .insert_header(ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![DispositionParam::Filename(format!("whim-export-{id}.zip"))],
+      })
+      .body(data),
+  )
+}
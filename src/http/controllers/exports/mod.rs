@@ -0,0 +1,3 @@
+mod download;
+
+pub use download::*;
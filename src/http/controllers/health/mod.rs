@@ -0,0 +1,3 @@
+mod probes;
+
+pub use probes::*;
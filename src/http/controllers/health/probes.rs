@@ -0,0 +1,93 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+  database::{self, migrations},
+  types::form::health::{Liveness, Migrations, PoolStatus, Readiness, Status},
+  App,
+};
+
+/// Reports whether the server process is running. It never
+/// touches the database, so an unhealthy database does not
+/// get the server restarted by its supervisor.
+#[tracing::instrument(skip_all)]
+pub async fn live() -> HttpResponse {
+  HttpResponse::Ok().json(Liveness { status: Status::Ok })
+}
+
+/// Reports whether the server is able to serve requests.
+///
+/// It responds with `503 Service Unavailable` if the primary
+/// database is unhealthy, even if the replica can still serve
+/// read-only requests.
+#[tracing::instrument(skip_all)]
+pub async fn ready(app: web::Data<App>) -> HttpResponse {
+  let primary_healthy = app.primary_db.wait_until_healthy().await.is_ok();
+  let replica_healthy = match app.replica_db.as_ref() {
+    Some(replica) => Some(replica.wait_until_healthy().await.is_ok()),
+    None => None,
+  };
+
+  let read_only = if app.config.db().primary().readonly() {
+    true
+  } else if primary_healthy {
+    match app.primary_db.is_read_only().await {
+      Ok(read_only) => read_only,
+      Err(error) => {
+        tracing::warn!(%error, "failed to check whether the primary database is read-only");
+        false
+      }
+    }
+  } else {
+    replica_healthy.unwrap_or(false)
+  };
+
+  // Migrations are replicated, so the replica can tell as well
+  let pool = match (primary_healthy, app.replica_db.as_ref()) {
+    (true, ..) => Some(&app.primary_db),
+    (false, Some(replica)) if replica_healthy == Some(true) => Some(replica),
+    _ => None,
+  };
+
+  let migrations = match pool {
+    Some(pool) => migration_status(pool).await.map(Migrations::from),
+    None => None,
+  };
+
+  let primary = PoolStatus::new(&app.primary_db, primary_healthy);
+  let replica = app
+    .replica_db
+    .as_ref()
+    .zip(replica_healthy)
+    .map(|(pool, healthy)| PoolStatus::new(pool, healthy));
+
+  let status = Readiness::status_of(&primary, replica.as_ref(), migrations.as_ref());
+  let mut response = if status == Status::Unavailable {
+    HttpResponse::ServiceUnavailable()
+  } else {
+    HttpResponse::Ok()
+  };
+
+  response.json(Readiness {
+    status,
+    read_only,
+    primary,
+    replica,
+    migrations,
+  })
+}
+
+async fn migration_status(pool: &database::Pool) -> Option<migrations::MigrationStatus> {
+  let result = async {
+    let mut conn = pool.get().await?;
+    migrations::status(&mut conn).await
+  }
+  .await;
+
+  match result {
+    Ok(status) => Some(status),
+    Err(error) => {
+      tracing::warn!(%error, "failed to check the status of database migrations");
+      None
+    }
+  }
+}
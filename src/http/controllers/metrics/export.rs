@@ -0,0 +1,22 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+  http::{error::ErrorStackContext, Error},
+  App,
+};
+
+/// Exports every metric in Prometheus' text format.
+#[tracing::instrument(skip_all)]
+pub async fn export(app: web::Data<App>) -> Result<HttpResponse, Error> {
+  app.metrics.update_pool("primary", &app.primary_db);
+  if let Some(replica) = app.replica_db.as_ref() {
+    app.metrics.update_pool("replica", replica);
+  }
+
+  let body = app.metrics.encode().into_http_result()?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type(prometheus::TEXT_FORMAT)
+      .body(body),
+  )
+}
@@ -1,12 +1,86 @@
 use actix_web::web;
 
+pub mod auth;
+pub mod exports;
+pub mod health;
+pub mod metrics;
+pub mod posts;
 pub mod users;
 
+/// Registers `/metrics` which is kept apart from [`configure`]
+/// as it may be served from a separate listener.
+pub fn configure_metrics(cfg: &mut web::ServiceConfig) {
+  cfg.route("/metrics", web::get().to(metrics::export));
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
+  cfg.service(web::scope("/auth").route("/refresh", web::post().to(auth::refresh)));
+  cfg.service(
+    web::scope("/health")
+      .route("/live", web::get().to(health::live))
+      .route("/ready", web::get().to(health::ready)),
+  );
+  cfg.route("/exports/{id}", web::get().to(exports::download));
+  cfg.route("/posts", web::post().to(posts::create_post));
+  cfg.service(
+    web::resource("/posts/{id}")
+      .route(web::get().to(posts::get_post))
+      .route(web::delete().to(posts::delete_post)),
+  );
   cfg.service(
     web::scope("/users")
+      .service(
+        web::resource("/@me/sessions")
+          .route(web::get().to(users::list_sessions))
+          .route(web::delete().to(users::revoke_all_sessions)),
+      )
+      .service(
+        web::resource("/@me/2fa/totp")
+          .route(web::post().to(users::enroll_totp))
+          .route(web::delete().to(users::disable_totp)),
+      )
+      .route("/@me/2fa/totp/confirm", web::post().to(users::confirm_totp))
+      .service(web::resource("/@me/sessions/{id}").route(web::delete().to(users::revoke_session)))
+      .route("/@me/export", web::post().to(users::request_export))
+      .route("/@me/export/{id}", web::get().to(users::get_export))
+      .service(
+        web::resource("/@me/tokens")
+          .route(web::get().to(users::list_tokens))
+          .route(web::post().to(users::create_token)),
+      )
+      .service(
+        web::resource("/@me/tokens/{id}")
+          .route(web::get().to(users::get_token))
+          .route(web::patch().to(users::update_token))
+          .route(web::delete().to(users::delete_token)),
+      )
+      .route("/@me/username", web::post().to(users::change_username))
+      .service(
+        web::resource("/@me")
+          .route(web::get().to(users::current_profile))
+          .route(web::patch().to(users::update_profile))
+          .route(web::delete().to(users::delete_account)),
+      )
       .service(web::resource("/@{name}").route(web::get().to(users::profile)))
+      .service(
+        web::resource("/@{name}/follow")
+          .route(web::put().to(users::follow_user))
+          .route(web::delete().to(users::unfollow_user)),
+      )
+      .route("/@{name}/followers", web::get().to(users::list_followers))
+      .route("/@{name}/following", web::get().to(users::list_following))
+      .route("/cancel-deletion", web::post().to(users::cancel_deletion))
+      .route("/forgot-password", web::post().to(users::forgot_password))
       .route("/login", web::post().to(users::login))
-      .route("/register", web::post().to(users::register)),
+      .route("/login/2fa", web::post().to(users::login_2fa))
+      .route("/register", web::post().to(users::register))
+      .route("/reset-password", web::post().to(users::reset_password))
+      .route("/verify-email", web::post().to(users::verify_email))
+      .route(
+        "/verify-email/resend",
+        web::post().to(users::resend_verification_email),
+      )
+      // Must be the last one, otherwise it shadows the routes above
+      .route("/{id}", web::get().to(users::profile_by_id)),
   );
 }
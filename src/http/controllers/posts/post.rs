@@ -0,0 +1,92 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use thiserror::Error;
+use validator::Validate;
+
+use crate::{
+  http::{Actor, Error},
+  schema::Post,
+  types::{
+    form::posts::post,
+    id::{marker::PostMarker, Id},
+    Scope,
+  },
+  App,
+};
+
+#[derive(Debug, Error)]
+#[error("Post not found")]
+struct NotFound;
+
+#[tracing::instrument]
+pub async fn create_post(
+  app: web::Data<App>,
+  actor: Actor,
+  form: Json<post::CreateRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.require_scope(Scope::WritePosts)?;
+  if !user.can_post(app.config.instance()) {
+    #[derive(Debug, Error)]
+    #[error("User has not verified their email address yet")]
+    struct UnverifiedEmail;
+    return Err(Error::from_context(
+      crate::types::Error::EmailNotVerified,
+      UnverifiedEmail,
+    ));
+  }
+
+  let mut conn = app.db_write().await?;
+  let id = app.id_generator.generate::<PostMarker>();
+  let created = Post::create(&mut conn, id, user.id, form.content()).await?;
+
+  Ok(HttpResponse::Created().json(post::Post::new(created)))
+}
+
+#[tracing::instrument]
+pub async fn get_post(
+  app: web::Data<App>,
+  path: web::Path<Id<PostMarker>>,
+) -> Result<HttpResponse, Error> {
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(post) = Post::by_id(&mut conn, path.into_inner()).await? else {
+    return Err(Error::from_context(crate::types::Error::NotFound, NotFound));
+  };
+
+  Ok(HttpResponse::Ok().json(post::Post::new(post)))
+}
+
+/// Deletes a post. Only its author can delete it.
+#[tracing::instrument]
+pub async fn delete_post(
+  app: web::Data<App>,
+  path: web::Path<Id<PostMarker>>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  #[derive(Debug, Error)]
+  #[error("Attempt to delete another user's post")]
+  struct NotAuthor;
+
+  let user = actor.require_scope(Scope::WritePosts)?;
+  let id = path.into_inner();
+
+  let mut conn = app.db_write().await?;
+  let Some(post) = Post::by_id(&mut conn, id).await? else {
+    return Err(Error::from_context(crate::types::Error::NotFound, NotFound));
+  };
+
+  if post.author_id != user.id {
+    return Err(Error::from_context(
+      crate::types::Error::Forbidden,
+      NotAuthor,
+    ));
+  }
+
+  if Post::delete(&mut conn, id, user.id).await? {
+    Ok(HttpResponse::NoContent().finish())
+  } else {
+    Err(Error::from_context(crate::types::Error::NotFound, NotFound))
+  }
+}
@@ -0,0 +1,113 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use sqlx::Connection;
+use thiserror::Error;
+use validator::Validate;
+
+use crate::{
+  auth::password,
+  database::error::ErrorExt,
+  http::{error::ErrorStackContext, Actor, ClientInfo, Error},
+  schema::{Session, User},
+  types::form::users::delete_account,
+  util::ago,
+  App,
+};
+
+use super::{
+  login::{confirm_password, invalid_credientials, release_throttle, throttle_keys, throttled},
+  username::field_error,
+};
+
+/// Schedules the account of the current user for deletion. It
+/// logs out every session and blocks logging in until the user
+/// cancels it within the configured grace period.
+#[tracing::instrument]
+pub async fn delete_account(
+  app: web::Data<App>,
+  actor: Actor,
+  client: ClientInfo,
+  form: Json<delete_account::Request>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.get_user()?;
+
+  let verification = confirm_password(&app, &user, &client, &form.password).await?;
+  if !verification.is_valid() {
+    return Err(field_error("password", "Invalid password"));
+  }
+
+  let mut conn = app.db_write().await?;
+  let mut tx = conn.begin().await.into_db_error()?;
+
+  let Some(requested_at) = User::request_deletion(&mut tx, user.id).await? else {
+    return Err(deleted());
+  };
+  Session::revoke_all(&mut tx, user.id).await?;
+  tx.commit().await.into_db_error()?;
+
+  let grace_period = app.config.users().deletion_grace_period();
+  let purge_at = requested_at.saturating_add(grace_period);
+
+  Ok(HttpResponse::Accepted().json(delete_account::Response { purge_at }))
+}
+
+/// Restores an account pending deletion. It requires the user's
+/// credentials since they cannot log in until it is restored.
+#[tracing::instrument]
+pub async fn cancel_deletion(
+  app: web::Data<App>,
+  client: ClientInfo,
+  form: Json<delete_account::CancelRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  let mut conn = app.db_write().await?;
+  let user = User::by_name_or_email_including_pending(&mut conn, &form.username_or_email).await?;
+
+  let throttle_keys = throttle_keys(user.as_ref(), &form.username_or_email, &client);
+  let attempt = app.login_throttle.attempt(&throttle_keys).await;
+  if let Some(retry_after) = attempt.retry_after() {
+    return Err(throttled(retry_after));
+  }
+
+  let Some(user) = user else {
+    app
+      .dummy_password_hash
+      .verify(app.config.auth().password(), &form.password)
+      .await;
+    return Err(invalid_credientials());
+  };
+
+  let verification = password::verify(
+    app.config.auth().password(),
+    &user.name,
+    &form.password,
+    &user.password_hash,
+  )
+  .await
+  .into_http_result()?;
+
+  if !verification.is_valid() {
+    return Err(invalid_credientials());
+  }
+  release_throttle(&app, &throttle_keys, &attempt).await;
+
+  if user.deletion_requested_at.is_some() {
+    let since = ago(app.config.users().deletion_grace_period());
+    if !User::cancel_deletion(&mut conn, user.id, since).await? {
+      return Err(deleted());
+    }
+  }
+
+  Ok(HttpResponse::NoContent().finish())
+}
+
+fn deleted() -> Error {
+  #[derive(Debug, Error)]
+  #[error("User has been deleted")]
+  struct Deleted;
+  Error::from_context(crate::types::Error::NotFound, Deleted)
+}
@@ -0,0 +1,80 @@
+use actix_web::{web, HttpResponse};
+use thiserror::Error;
+
+use crate::{
+  database::error::ErrorExt2,
+  http::{data_export, Actor, Error},
+  schema::{DataExport, DataExportStatus},
+  types::{
+    form::users::export,
+    id::{marker::DataExportMarker, Id},
+    Timestamp,
+  },
+  App,
+};
+
+#[derive(Debug, Error)]
+#[error("Data export not found")]
+struct NotFound;
+
+/// Requests an archive of the current user's personal data. Only
+/// one export can be active at a time, requesting it again returns
+/// the active export instead.
+#[tracing::instrument]
+pub async fn request_export(app: web::Data<App>, actor: Actor) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_write().await?;
+  if let Some(active) = DataExport::active(&mut conn, user.id).await? {
+    return Ok(HttpResponse::Ok().json(to_response(&app, &active)));
+  }
+
+  // Another request might have created one in the meantime
+  let created = match DataExport::create(&mut conn, user.id).await {
+    Ok(created) => created,
+    Err(report) if report.unique_violation() == Some(DataExport::PENDING_UNIQUE_INDEX) => {
+      let Some(active) = DataExport::active(&mut conn, user.id).await? else {
+        return Err(report.into());
+      };
+      return Ok(HttpResponse::Ok().json(to_response(&app, &active)));
+    }
+    Err(report) => return Err(report.into()),
+  };
+  drop(conn);
+
+  data_export::spawn_build(&app, user.id, created.id);
+  Ok(HttpResponse::Accepted().json(to_response(&app, &created)))
+}
+
+#[tracing::instrument]
+pub async fn get_export(
+  app: web::Data<App>,
+  path: web::Path<Id<DataExportMarker>>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(found) = DataExport::by_id(&mut conn, path.into_inner(), user.id).await? else {
+    return Err(Error::from_context(crate::types::Error::NotFound, NotFound));
+  };
+
+  Ok(HttpResponse::Ok().json(to_response(&app, &found)))
+}
+
+fn to_response(app: &App, export: &DataExport) -> export::Export {
+  let status = export.status(Timestamp::now());
+  let download_url = if status == DataExportStatus::Ready {
+    data_export::download_url(app.config.auth(), export)
+  } else {
+    None
+  };
+
+  export::Export {
+    id: export.id,
+    status: status.into(),
+    created_at: export.created_at,
+    expires_at: export.expires_at,
+    download_url,
+  }
+}
@@ -0,0 +1,100 @@
+use actix_web::{web, HttpResponse};
+use thiserror::Error;
+use validator::ValidateError;
+
+use crate::{
+  database::Connection,
+  http::{Actor, Error},
+  schema::{Follow, User},
+  types::form::users::follows,
+  App,
+};
+
+#[derive(Debug, Error)]
+#[error("User not found")]
+struct NotFound;
+
+async fn user_by_name(conn: &mut Connection, name: &str) -> Result<User, Error> {
+  match User::by_name(conn, name).await? {
+    Some(user) => Ok(user),
+    None => Err(Error::from_context(crate::types::Error::NotFound, NotFound)),
+  }
+}
+
+/// Follows a user. Following a user again does nothing.
+#[tracing::instrument]
+pub async fn follow_user(
+  app: web::Data<App>,
+  path: web::Path<String>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_write().await?;
+  let followee = user_by_name(&mut conn, path.as_str()).await?;
+  if followee.id == user.id {
+    let mut error = ValidateError::msg_builder();
+    error.insert("You cannot follow yourself");
+    return Err(error.build().into());
+  }
+
+  Follow::create(&mut conn, user.id, followee.id).await?;
+  Ok(HttpResponse::NoContent().finish())
+}
+
+/// Unfollows a user. Unfollowing a user who is
+/// not followed by the current user does nothing.
+#[tracing::instrument]
+pub async fn unfollow_user(
+  app: web::Data<App>,
+  path: web::Path<String>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_write().await?;
+  let followee = user_by_name(&mut conn, path.as_str()).await?;
+  Follow::delete(&mut conn, user.id, followee.id).await?;
+
+  Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument]
+pub async fn list_followers(
+  app: web::Data<App>,
+  path: web::Path<String>,
+  query: web::Query<follows::ListQuery>,
+) -> Result<HttpResponse, Error> {
+  let mut conn = app.db_read_prefer_primary().await?;
+  let user = user_by_name(&mut conn, path.as_str()).await?;
+
+  let limit = query.limit();
+  let users = Follow::list_followers(&mut conn, user.id, query.after, i64::from(limit)).await?;
+  Ok(HttpResponse::Ok().json(list_response(users, limit)))
+}
+
+#[tracing::instrument]
+pub async fn list_following(
+  app: web::Data<App>,
+  path: web::Path<String>,
+  query: web::Query<follows::ListQuery>,
+) -> Result<HttpResponse, Error> {
+  let mut conn = app.db_read_prefer_primary().await?;
+  let user = user_by_name(&mut conn, path.as_str()).await?;
+
+  let limit = query.limit();
+  let users = Follow::list_following(&mut conn, user.id, query.after, i64::from(limit)).await?;
+  Ok(HttpResponse::Ok().json(list_response(users, limit)))
+}
+
+fn list_response(users: Vec<User>, limit: u32) -> follows::ListResponse {
+  let next = match users.last() {
+    Some(last) if users.len() >= limit as usize => Some(last.id),
+    _ => None,
+  };
+
+  follows::ListResponse {
+    users: users.into_iter().map(follows::FollowUser::new).collect(),
+    next,
+  }
+}
@@ -2,59 +2,214 @@ use actix_web::{
   web::{self, Json},
   HttpResponse,
 };
-use sha2::Digest;
+use std::time::Duration;
 use validator::{Validate, ValidateError};
 
 use crate::{
-  http::{Error, Jwt},
-  schema::User,
+  auth::password::{self, Verification},
+  http::{error::ErrorStackContext, rate_limit, session, two_factor::Challenge, ClientInfo, Error},
+  metrics::LoginOutcome,
+  schema::{TotpSecret, User},
+  throttle::{Attempt, LoginThrottle},
   types::form::users::login,
+  util::Sensitive,
   App,
 };
 
 #[tracing::instrument]
-pub async fn login(app: web::Data<App>, form: Json<login::Request>) -> Result<HttpResponse, Error> {
+pub async fn login(
+  app: web::Data<App>,
+  client: ClientInfo,
+  form: Json<login::Request>,
+) -> Result<HttpResponse, Error> {
   form.validate()?;
 
   // We need to get the latest info as soon as possible
   let mut conn = app.db_read_prefer_primary().await?;
 
-  let Some(user) = User::by_name_or_email(&mut conn, &form.username_or_email).await? else {
-    let mut error = ValidateError::field_builder();
-    let mut contents = ValidateError::msg_builder();
-    contents.insert("Invalid credientials");
-    error.insert("username_or_email", contents.build());
-    return Err(error.build().into());
+  // Users pending deletion are told about it after logging in successfully
+  let user = User::by_name_or_email_including_pending(&mut conn, &form.username_or_email).await?;
+  let two_factor = match &user {
+    Some(user) => TotpSecret::is_enabled_for(&mut conn, user.id).await?,
+    None => false,
   };
-
   drop(conn);
 
-  // TODO: Not secure right now but we need to get this asap
-  let mut hasher = sha2::Sha512::default();
-  hasher.update(format!("{}:{}", user.name.as_str(), form.password.as_str()));
-
-  let attempt_password_hash = hex::encode(hasher.finalize());
-
-  let mut matched = true;
-  for (a, b) in user
-    .password_hash
-    .chars()
-    .zip(attempt_password_hash.chars())
-  {
-    matched = matched && (a == b);
-  }
-
-  if !matched {
-    let mut error = ValidateError::field_builder();
-    let mut contents = ValidateError::msg_builder();
-    contents.insert("Invalid credientials");
-    error.insert("username_or_email", contents.build());
-    Err(error.build().into())
-  } else {
-    let jwt = Jwt::encode(user.id, app.clone()).await;
-    Ok(HttpResponse::Ok().json(login::Response {
-      id: user.id,
-      token: jwt.into(),
-    }))
+  let throttle_keys = throttle_keys(user.as_ref(), &form.username_or_email, &client);
+  let attempt = app.login_throttle.attempt(&throttle_keys).await;
+  if let Some(retry_after) = attempt.retry_after() {
+    app.metrics.record_login(LoginOutcome::Throttled);
+    return Err(throttled(retry_after));
+  }
+
+  let Some(user) = user else {
+    app
+      .dummy_password_hash
+      .verify(app.config.auth().password(), &form.password)
+      .await;
+    app.metrics.record_login(LoginOutcome::Failure);
+    return Err(invalid_credientials());
+  };
+
+  let verification = password::verify(
+    app.config.auth().password(),
+    &user.name,
+    &form.password,
+    &user.password_hash,
+  )
+  .await
+  .into_http_result()?;
+
+  if !verification.is_valid() {
+    app.metrics.record_login(LoginOutcome::Failure);
+    return Err(invalid_credientials());
+  }
+  release_throttle(&app, &throttle_keys, &attempt).await;
+
+  if user.deletion_requested_at.is_some() {
+    #[derive(Debug, thiserror::Error)]
+    #[error("User has requested to delete their account")]
+    struct PendingDeletion;
+    return Err(Error::from_context(
+      crate::types::Error::AccountPendingDeletion,
+      PendingDeletion,
+    ));
+  }
+
+  if user.needs_email_verification() && !app.config.instance().allow_unverified_login() {
+    #[derive(Debug, thiserror::Error)]
+    #[error("User has not verified their email address yet")]
+    struct UnverifiedEmail;
+    return Err(Error::from_context(
+      crate::types::Error::EmailNotVerified,
+      UnverifiedEmail,
+    ));
+  }
+
+  if verification == Verification::NeedsRehash {
+    upgrade_password_hash(&app, &user, &form).await;
+  }
+
+  if two_factor {
+    let challenge_token = Challenge::new(user.id, app.config.auth())
+      .encode(app.config.auth())
+      .into_http_result()?;
+
+    app.metrics.record_login(LoginOutcome::TwoFactorRequired);
+    return Ok(HttpResponse::Ok().json(login::ChallengeResponse {
+      challenge_token: challenge_token.into(),
+    }));
+  }
+
+  let tokens = session::start(&app, user.id, &client).await?;
+  app.metrics.record_login(LoginOutcome::Success);
+  Ok(HttpResponse::Ok().json(login::Response {
+    id: user.id,
+    token: tokens.access_token,
+    refresh_token: tokens.refresh_token,
+  }))
+}
+
+/// Throttling keys of a login attempt, starting with the account's key.
+/// The account is throttled by its ID so logging in with its name and
+/// email address share the same failed attempts.
+pub(super) fn throttle_keys(
+  user: Option<&User>,
+  name_or_email: &str,
+  client: &ClientInfo,
+) -> Vec<String> {
+  let mut keys = vec![match user {
+    Some(user) => LoginThrottle::account_key(user.id),
+    None => LoginThrottle::unknown_account_key(name_or_email),
+  }];
+  if let Some(ip_address) = client.ip_address.as_deref() {
+    keys.push(LoginThrottle::ip_key(ip_address));
+  }
+  keys
+}
+
+/// Forgets failed attempts of the account after a successful attempt.
+///
+/// Failed attempts from the client's IP address are kept so an
+/// attacker cannot reset it with their own account.
+pub(super) async fn release_throttle(app: &App, keys: &[String], attempt: &Attempt) {
+  let Some((account_key, others)) = keys.split_first() else {
+    return;
+  };
+  app.login_throttle.reset(account_key).await;
+  for key in others {
+    app.login_throttle.release(attempt, key).await;
+  }
+}
+
+/// Checks the password of a signed in user before a sensitive
+/// action. It is throttled like logging in so a stolen access
+/// token cannot be used to guess the user's password.
+pub(super) async fn confirm_password(
+  app: &App,
+  user: &User,
+  client: &ClientInfo,
+  password: &Sensitive<String>,
+) -> Result<Verification, Error> {
+  let throttle_keys = throttle_keys(Some(user), &user.name, client);
+  let attempt = app.login_throttle.attempt(&throttle_keys).await;
+  if let Some(retry_after) = attempt.retry_after() {
+    return Err(throttled(retry_after));
+  }
+
+  let verification = password::verify(
+    app.config.auth().password(),
+    &user.name,
+    password,
+    &user.password_hash,
+  )
+  .await
+  .into_http_result()?;
+
+  if verification.is_valid() {
+    release_throttle(app, &throttle_keys, &attempt).await;
+  }
+  Ok(verification)
+}
+
+pub(super) fn throttled(retry_after: Duration) -> Error {
+  #[derive(Debug, thiserror::Error)]
+  #[error("Too many failed login attempts")]
+  struct Throttled;
+
+  Error::from_context(
+    crate::types::Error::LoginThrottled {
+      retry_after: rate_limit::ceil_secs(retry_after),
+    },
+    Throttled,
+  )
+}
+
+pub(super) fn invalid_credientials() -> Error {
+  let mut error = ValidateError::field_builder();
+  let mut contents = ValidateError::msg_builder();
+  contents.insert("Invalid credientials");
+  error.insert("username_or_email", contents.build());
+  error.build().into()
+}
+
+/// Replaces the user's legacy or outdated password hash with a
+/// new one. Failing to do so should not prevent the user from
+/// logging in (the database might be in read-only mode for example)
+/// so it will try again on their next login.
+async fn upgrade_password_hash(app: &App, user: &User, form: &login::Request) {
+  let result = async {
+    let password_hash = password::hash(app.config.auth().password(), &form.password)
+      .await
+      .into_http_result()?;
+
+    let mut conn = app.db_write().await?;
+    User::update_password_hash(&mut conn, user.id, &password_hash).await?;
+    Ok::<_, Error>(())
+  }
+  .await;
+
+  if let Err(error) = result {
+    tracing::warn!(%error, "failed to upgrade user's password hash");
   }
 }
@@ -1,7 +1,25 @@
+mod delete_account;
+mod export;
+mod follows;
 mod login;
+mod password_reset;
 mod profile;
 mod register;
+mod sessions;
+mod tokens;
+mod two_factor;
+mod username;
+mod verify_email;
 
+pub use delete_account::*;
+pub use export::*;
+pub use follows::*;
 pub use login::*;
+pub use password_reset::*;
 pub use profile::*;
 pub use register::*;
+pub use sessions::*;
+pub use tokens::*;
+pub use two_factor::*;
+pub use username::*;
+pub use verify_email::*;
@@ -0,0 +1,82 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use sqlx::Connection;
+use validator::{Validate, ValidateError};
+
+use crate::{
+  auth::{password, token},
+  database::error::ErrorExt,
+  http::{error::ErrorStackContext, Error},
+  schema::{PasswordReset, Session, User},
+  types::form::users::{forgot_password, reset_password},
+  App,
+};
+
+/// Sends a password reset email. It always responds the same way
+/// regardless if the user exists to avoid account enumeration.
+#[tracing::instrument]
+pub async fn forgot_password(
+  app: web::Data<App>,
+  form: Json<forgot_password::Request>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  // Looking up the user and sending the email are done in the
+  // background so the response time does not reveal whether
+  // the user exists or not.
+  let task_app = app.clone();
+  let form = form.into_inner();
+  app.tasks.spawn(async move {
+    let app = task_app;
+    let result = async {
+      let mut conn = app.db_read_prefer_primary().await?;
+      let user = User::by_name_or_email(&mut conn, &form.username_or_email).await?;
+      drop(conn);
+
+      if let Some(user) = user {
+        crate::http::password_reset::send(&app, &user).await?;
+      }
+      Ok::<_, Error>(())
+    }
+    .await;
+
+    if let Err(error) = result {
+      tracing::warn!(%error, "failed to send password reset email");
+    }
+  });
+
+  Ok(HttpResponse::Accepted().finish())
+}
+
+#[tracing::instrument]
+pub async fn reset_password(
+  app: web::Data<App>,
+  form: Json<reset_password::Request>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  let password_hash = password::hash(app.config.auth().password(), &form.password)
+    .await
+    .into_http_result()?;
+
+  let mut conn = app.db_write().await?;
+  let mut tx = conn.begin().await.into_db_error()?;
+
+  let Some(reset) = PasswordReset::consume(&mut tx, &token::hash(&form.token)).await? else {
+    let mut error = ValidateError::field_builder();
+    let mut contents = ValidateError::msg_builder();
+    contents.insert("Invalid or expired password reset token");
+    error.insert("token", contents.build());
+    return Err(error.build().into());
+  };
+
+  User::update_password_hash(&mut tx, reset.user_id, &password_hash).await?;
+
+  // Whoever has the old password might have logged in already
+  Session::revoke_all(&mut tx, reset.user_id).await?;
+
+  tx.commit().await.into_db_error()?;
+  Ok(HttpResponse::NoContent().finish())
+}
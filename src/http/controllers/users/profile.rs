@@ -1,43 +1,140 @@
-use actix_web::{web, HttpResponse};
-use serde_json::json;
+use actix_web::{
+  http::header,
+  web::{self, Json},
+  HttpRequest, HttpResponse,
+};
 use thiserror::Error;
+use validator::Validate;
 
 use crate::{
+  database::{error::ErrorExt2, Connection},
   http::{Actor, Error},
-  schema::User,
+  schema::{Follow, User, UsernameHistory},
+  types::{
+    form::users::profile,
+    id::{marker::UserMarker, Id},
+    Scope,
+  },
   App,
 };
 
+/// Old names of users who recently changed their username are
+/// redirected to their current profile until the grace period ends.
 #[tracing::instrument]
 pub async fn profile(
   app: web::Data<App>,
+  req: HttpRequest,
   path: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+  // TODO: Remove the need of report
+  #[derive(Debug, Error)]
+  #[error("User not found")]
+  struct ResourceError;
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  if let Some(user) = User::by_name(&mut *conn, path.as_str()).await? {
+    return Ok(HttpResponse::Ok().json(load_profile(&mut conn, user).await?));
+  }
+
+  let since = crate::util::ago(app.config.users().username_grace_period());
+  let released = UsernameHistory::released_since(&mut conn, path.as_str(), since).await?;
+  let user = match released {
+    Some(entry) => User::by_id(&mut conn, entry.user_id).await?,
+    None => None,
+  };
+
+  let Some(user) = user else {
+    return Err(Error::from_context(
+      crate::types::Error::NotFound,
+      ResourceError,
+    ));
+  };
+
+  let location = match req.path().rsplit_once('/') {
+    Some((base, _)) => format!("{base}/@{}", user.name),
+    None => format!("@{}", user.name),
+  };
+
+  // Not permanent since the old name can be claimed by
+  // someone else once the grace period ends
+  Ok(
+    HttpResponse::TemporaryRedirect()
+      .insert_header((header::LOCATION, location))
+      .finish(),
+  )
+}
+
+/// Looks up a user's profile by their ID in either
+/// decimal, base62 or base36 form.
+#[tracing::instrument]
+pub async fn profile_by_id(
+  app: web::Data<App>,
+  path: web::Path<Id<UserMarker>>,
+) -> Result<HttpResponse, Error> {
+  #[derive(Debug, Error)]
+  #[error("User not found")]
+  struct ResourceError;
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(user) = User::by_id(&mut conn, path.into_inner()).await? else {
+    return Err(Error::from_context(
+      crate::types::Error::NotFound,
+      ResourceError,
+    ));
+  };
+
+  Ok(HttpResponse::Ok().json(load_profile(&mut conn, user).await?))
+}
+
+#[tracing::instrument]
+pub async fn current_profile(app: web::Data<App>, actor: Actor) -> Result<HttpResponse, Error> {
+  let user = actor.require_scope(Scope::ReadProfile)?;
+  let mut conn = app.db_read_prefer_primary().await?;
+  Ok(HttpResponse::Ok().json(load_profile(&mut conn, user).await?))
+}
+
+#[tracing::instrument]
+pub async fn update_profile(
+  app: web::Data<App>,
   actor: Actor,
+  form: Json<profile::UpdateRequest>,
 ) -> Result<HttpResponse, Error> {
-  // TODO: Restrict users from signing up using `me` as their username
-  let user = if path.as_str() == "me" {
-    actor.get_user()?
-  } else {
-    // TODO: Remove the need of report
-    #[derive(Debug, Error)]
-    #[error("User not found")]
-    struct ResourceError;
+  form.validate()?;
+  let user = actor.require_scope(Scope::WriteProfile)?;
 
+  if form.is_empty() {
     let mut conn = app.db_read_prefer_primary().await?;
-    if let Some(user) = User::by_name(&mut *conn, path.as_str()).await? {
-      user
-    } else {
-      return Err(Error::from_context(
-        crate::types::Error::NotFound,
-        ResourceError,
+    return Ok(HttpResponse::Ok().json(load_profile(&mut conn, user).await?));
+  }
+
+  // Writes are not possible while the primary database is down
+  // and only the read-only replica is available.
+  let mut conn = match app.db_write().await {
+    Ok(conn) => conn,
+    Err(report) if report.is_unhealthy() && app.replica_db.is_some() => {
+      return Err(Error::from_report(
+        crate::types::Error::ReadonlyMode,
+        report,
       ));
     }
+    Err(report) => return Err(report.into()),
+  };
+
+  let Some(updated) = User::update_profile(&mut conn, user.id, &form.to_update()).await? else {
+    #[derive(Debug, Error)]
+    #[error("User has been deleted")]
+    struct Deleted;
+    return Err(Error::from_context(crate::types::Error::NotFound, Deleted));
   };
 
-  Ok(HttpResponse::Ok().json(json!({
-    "id": user.id,
-    "created_at": user.created_at,
-    "name": user.name,
-    "display_name": user.display_name,
-  })))
+  Ok(HttpResponse::Ok().json(load_profile(&mut conn, updated).await?))
+}
+
+/// Builds the public profile of a user along with their follow counts.
+pub(super) async fn load_profile(
+  conn: &mut Connection,
+  user: User,
+) -> Result<profile::Profile, Error> {
+  let counts = Follow::counts(conn, user.id).await?;
+  Ok(profile::Profile::new(user, counts))
 }
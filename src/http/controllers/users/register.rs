@@ -2,15 +2,19 @@ use actix_web::{
   web::{self, Json},
   HttpResponse,
 };
-use futures::StreamExt;
-use sha2::Digest;
-use sqlx::FromRow;
-use validator::{Validate, ValidateError};
+use validator::Validate;
 
 use crate::{
-  database::error::ErrorExt, http::Error, schema::User, types::form::users::register, App,
+  auth::password,
+  database::error::{ErrorExt, ErrorExt2},
+  http::{error::ErrorStackContext, verification, Error},
+  schema::User,
+  types::{form::users::register, id::marker::UserMarker, validation},
+  App,
 };
 
+use super::username::{self, field_error};
+
 #[tracing::instrument]
 pub async fn register(
   app: web::Data<App>,
@@ -18,85 +22,58 @@ pub async fn register(
 ) -> Result<HttpResponse, Error> {
   form.validate()?;
 
-  #[derive(Debug, FromRow)]
-  struct Query {
-    name_exists: bool,
-    email_exists: bool,
-  }
-
-  let mut conn = app.db_write().await?;
-  let mut query = sqlx::QueryBuilder::new("select (name = $1) as name_exists");
-
-  if form.email.is_some() {
-    query.push(", (email = $2) as email_exists");
-  }
-  query.push(" from users where name = $1");
-  if form.email.is_some() {
-    query.push(" or email = $2");
-  }
-
-  let mut query = query.build_query_as::<Query>().bind(form.username.as_str());
-  if let Some(email) = form.email.as_deref() {
-    query = query.bind(email);
+  // Reserved, confusable or recently released names. Users registered
+  // in the meantime are caught by the unique constraints when inserting.
+  let mut conn = app.db_read_prefer_primary().await?;
+  if !username::is_name_available(&app, &mut conn, &form.username, None).await? {
+    return Err(field_error("username", "This username is not available"));
   }
+  drop(conn);
 
-  let mut count = 0;
-  let mut email_exists = false;
-  let mut username_exists = false;
-
-  let mut stream = query.fetch(&mut *conn);
-  while let Some(row) = stream.next().await {
-    if count == 2 {
-      // this is to avoid DDOS
-      break;
-    }
-
-    let row = row.into_db_error()?;
-    email_exists = email_exists || row.email_exists;
-    username_exists = username_exists || row.name_exists;
-    count += 1;
-  }
-  drop(stream);
-
-  if email_exists || username_exists {
-    let mut err = ValidateError::field_builder();
-    if email_exists {
-      let mut msg = ValidateError::msg_builder();
-      msg.insert("This email address exists");
-      err.insert("email", msg.build());
-    }
-    if username_exists {
-      let mut msg = ValidateError::msg_builder();
-      msg.insert("This username exists");
-      err.insert("username", msg.build());
-    }
-    return Err(err.build().into());
-  }
-
-  // TODO: Not secure right now but we need to get this asap
-  let mut hasher = sha2::Sha512::default();
-  hasher.update(format!(
-    "{}:{}",
-    form.username.as_str(),
-    form.password.as_str()
-  ));
-
-  let password_hash = hex::encode(hasher.finalize());
+  // The connection is not held while hashing so a burst of
+  // registrations cannot take every connection in the pool.
+  let password_hash = password::hash(app.config.auth().password(), &form.password)
+    .await
+    .into_http_result()?;
 
   // Attempting to insert user right now!
-  let _new_user = sqlx::query_as::<_, User>(
-    r#"INSERT INTO "users" (name, email, password_hash)
-       VALUES ($1, $2, $3)
+  let mut conn = app.db_write().await?;
+  let email = form.email.as_deref().map(validation::normalize_email);
+  let result = sqlx::query_as::<_, User>(
+    r#"INSERT INTO "users" (id, name, name_skeleton, email, password_hash)
+       VALUES ($1, $2, $3, $4, $5)
        RETURNING *"#,
   )
+  .bind(app.id_generator.generate::<UserMarker>())
   .bind(form.username.as_str())
-  .bind(form.email.as_deref())
+  .bind(crate::types::username::skeleton(&form.username))
+  .bind(email)
   .bind(password_hash)
   .fetch_one(&mut *conn)
   .await
-  .into_db_error()?;
+  .into_db_error();
+  drop(conn);
+
+  let new_user = match result {
+    Ok(user) => user,
+    Err(report) => match report.unique_violation() {
+      Some(User::NAME_UNIQUE_INDEX) => return Err(field_error("username", "This username exists")),
+      Some(User::EMAIL_UNIQUE_INDEX) => {
+        return Err(field_error("email", "This email address exists"))
+      }
+      _ => return Err(report.into()),
+    },
+  };
+
+  // The user is already registered at this point, they can
+  // request another verification email if this one fails.
+  if new_user.needs_email_verification() {
+    if let Err(error) = verification::send(&app, &new_user).await {
+      tracing::warn!(%error, "failed to send verification email");
+    }
+  }
 
   Ok(HttpResponse::Created().json(register::Response {
-    verification_required: form.email.is_some(),
+    verification_required: new_user.needs_email_verification(),
   }))
 }
@@ -0,0 +1,64 @@
+use actix_web::{web, HttpResponse};
+use thiserror::Error;
+
+use crate::{
+  http::{Actor, Error, Jwt},
+  schema::Session,
+  types::{
+    form::users::sessions,
+    id::{marker::SessionMarker, Id},
+  },
+  App,
+};
+
+#[tracing::instrument]
+pub async fn list_sessions(
+  app: web::Data<App>,
+  actor: Actor,
+  jwt: Option<Jwt>,
+) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+  let current = jwt.map(|v| v.session_id);
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let sessions = Session::list_active(&mut conn, user.id)
+    .await?
+    .into_iter()
+    .map(|session| {
+      let is_current = Some(session.id) == current;
+      sessions::Session::new(session, is_current)
+    })
+    .collect();
+
+  Ok(HttpResponse::Ok().json(sessions::Response { sessions }))
+}
+
+#[tracing::instrument]
+pub async fn revoke_session(
+  app: web::Data<App>,
+  path: web::Path<Id<SessionMarker>>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  #[derive(Debug, Error)]
+  #[error("Session not found")]
+  struct ResourceError;
+
+  let user = actor.get_user()?;
+  let mut conn = app.db_write().await?;
+  if Session::revoke(&mut conn, path.into_inner(), user.id).await? {
+    Ok(HttpResponse::NoContent().finish())
+  } else {
+    Err(Error::from_context(
+      crate::types::Error::NotFound,
+      ResourceError,
+    ))
+  }
+}
+
+#[tracing::instrument]
+pub async fn revoke_all_sessions(app: web::Data<App>, actor: Actor) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+  let mut conn = app.db_write().await?;
+  let revoked = Session::revoke_all(&mut conn, user.id).await?;
+  Ok(HttpResponse::Ok().json(sessions::RevokeAllResponse { revoked }))
+}
@@ -0,0 +1,143 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use thiserror::Error;
+use validator::{Validate, ValidateError};
+
+use crate::{
+  auth::{api_token, token},
+  http::{Actor, Error},
+  schema::ApiToken,
+  types::{
+    form::users::tokens,
+    id::{marker::ApiTokenMarker, Id},
+  },
+  App,
+};
+
+#[derive(Debug, Error)]
+#[error("API token not found")]
+struct NotFound;
+
+#[tracing::instrument]
+pub async fn list_tokens(app: web::Data<App>, actor: Actor) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let tokens = ApiToken::list(&mut conn, user.id)
+    .await?
+    .into_iter()
+    .map(tokens::ApiToken::new)
+    .collect();
+
+  Ok(HttpResponse::Ok().json(tokens::ListResponse { tokens }))
+}
+
+#[tracing::instrument]
+pub async fn create_token(
+  app: web::Data<App>,
+  actor: Actor,
+  form: Json<tokens::CreateRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_write().await?;
+  if ApiToken::count(&mut conn, user.id).await? >= ApiToken::MAX_PER_USER {
+    let mut error = ValidateError::msg_builder();
+    error.insert("Too many API tokens, delete unused tokens first");
+    return Err(error.build().into());
+  }
+
+  let raw_token = api_token::generate();
+  let scopes = form
+    .scopes
+    .iter()
+    .map(|v| v.as_str().to_string())
+    .collect::<Vec<_>>();
+
+  let expires_at = form
+    .expires_in_secs
+    .map(|secs| token::expiry(std::time::Duration::from_secs(secs)));
+
+  let created = ApiToken::create(
+    &mut conn,
+    user.id,
+    &form.name,
+    api_token::visible_prefix(&raw_token),
+    &token::hash(&raw_token),
+    &scopes,
+    expires_at,
+  )
+  .await?;
+
+  Ok(HttpResponse::Created().json(tokens::CreateResponse {
+    token: raw_token,
+    info: tokens::ApiToken::new(created),
+  }))
+}
+
+#[tracing::instrument]
+pub async fn get_token(
+  app: web::Data<App>,
+  path: web::Path<Id<ApiTokenMarker>>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(api_token) = ApiToken::by_id(&mut conn, path.into_inner(), user.id).await? else {
+    return Err(Error::from_context(crate::types::Error::NotFound, NotFound));
+  };
+
+  Ok(HttpResponse::Ok().json(tokens::ApiToken::new(api_token)))
+}
+
+#[tracing::instrument]
+pub async fn update_token(
+  app: web::Data<App>,
+  path: web::Path<Id<ApiTokenMarker>>,
+  actor: Actor,
+  form: Json<tokens::UpdateRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.get_user()?;
+
+  let scopes = form
+    .scopes
+    .as_ref()
+    .map(|v| v.iter().map(|v| v.as_str().to_string()).collect::<Vec<_>>());
+
+  let mut conn = app.db_write().await?;
+  let updated = ApiToken::update(
+    &mut conn,
+    path.into_inner(),
+    user.id,
+    form.name.as_deref(),
+    scopes.as_deref(),
+  )
+  .await?;
+
+  let Some(updated) = updated else {
+    return Err(Error::from_context(crate::types::Error::NotFound, NotFound));
+  };
+
+  Ok(HttpResponse::Ok().json(tokens::ApiToken::new(updated)))
+}
+
+#[tracing::instrument]
+pub async fn delete_token(
+  app: web::Data<App>,
+  path: web::Path<Id<ApiTokenMarker>>,
+  actor: Actor,
+) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_write().await?;
+  if ApiToken::delete(&mut conn, path.into_inner(), user.id).await? {
+    Ok(HttpResponse::NoContent().finish())
+  } else {
+    Err(Error::from_context(crate::types::Error::NotFound, NotFound))
+  }
+}
@@ -0,0 +1,191 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use sqlx::Connection;
+use thiserror::Error;
+use validator::{Validate, ValidateError};
+
+use crate::{
+  auth::{recovery_code, totp},
+  database::error::ErrorExt,
+  http::{
+    error::ErrorStackContext,
+    session,
+    two_factor::{self, Challenge},
+    Actor, ClientInfo, Error,
+  },
+  metrics::LoginOutcome,
+  schema::{RecoveryCode, TotpSecret},
+  throttle::LoginThrottle,
+  types::{
+    form::users::{login, two_factor as form},
+    Timestamp,
+  },
+  App,
+};
+
+/// Starts enrolling TOTP two-factor authentication. Calling it again
+/// before confirming the enrollment replaces the pending secret.
+#[tracing::instrument]
+pub async fn enroll_totp(app: web::Data<App>, actor: Actor) -> Result<HttpResponse, Error> {
+  let user = actor.get_user()?;
+
+  let secret = totp::generate_secret();
+  let encrypted = two_factor::cipher(&app)
+    .encrypt(secret.as_ref())
+    .into_http_result()?;
+
+  let mut conn = app.db_write().await?;
+  if TotpSecret::upsert_pending(&mut conn, user.id, &encrypted)
+    .await?
+    .is_none()
+  {
+    return Err(msg_error("Two-factor authentication is already enabled"));
+  }
+
+  Ok(HttpResponse::Ok().json(form::EnrollResponse {
+    secret: totp::encode_secret(secret.as_ref()),
+    uri: totp::uri(app.config.auth().jwt_issuer(), &user.name, secret.as_ref()),
+  }))
+}
+
+/// Enables TOTP two-factor authentication after the user proves
+/// their authenticator app is set up correctly.
+#[tracing::instrument]
+pub async fn confirm_totp(
+  app: web::Data<App>,
+  actor: Actor,
+  form: Json<form::ConfirmRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.get_user()?;
+
+  let mut conn = app.db_write().await?;
+  let secret = TotpSecret::by_user_id(&mut conn, user.id).await?;
+  let Some(secret) = secret.filter(|v| !v.is_enabled()) else {
+    return Err(msg_error(
+      "There is no pending two-factor authentication enrollment",
+    ));
+  };
+
+  let decrypted = two_factor::cipher(&app)
+    .decrypt(&secret.encrypted_secret)
+    .into_http_result()?;
+
+  let now = Timestamp::now().timestamp();
+  let Some(step) = totp::verify(decrypted.as_ref(), &form.code, now, None) else {
+    return Err(invalid_code());
+  };
+
+  let recovery_codes = recovery_code::generate();
+  let hashes = recovery_codes
+    .iter()
+    .map(|v| recovery_code::hash(v))
+    .collect::<Vec<_>>();
+
+  let mut tx = conn.begin().await.into_db_error()?;
+  if !TotpSecret::enable(&mut tx, user.id, step).await? {
+    return Err(msg_error(
+      "There is no pending two-factor authentication enrollment",
+    ));
+  }
+  RecoveryCode::replace_all(&mut tx, user.id, &hashes).await?;
+  tx.commit().await.into_db_error()?;
+
+  Ok(HttpResponse::Ok().json(form::ConfirmResponse { recovery_codes }))
+}
+
+#[tracing::instrument]
+pub async fn disable_totp(
+  app: web::Data<App>,
+  actor: Actor,
+  client: ClientInfo,
+  form: Json<form::DisableRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.get_user()?;
+
+  let verification = super::login::confirm_password(&app, &user, &client, &form.password).await?;
+  if !verification.is_valid() {
+    let mut error = ValidateError::field_builder();
+    let mut contents = ValidateError::msg_builder();
+    contents.insert("Invalid password");
+    error.insert("password", contents.build());
+    return Err(error.build().into());
+  }
+
+  let mut conn = app.db_write().await?;
+  let mut tx = conn.begin().await.into_db_error()?;
+  let deleted = TotpSecret::delete(&mut tx, user.id).await?;
+  RecoveryCode::delete_all(&mut tx, user.id).await?;
+  tx.commit().await.into_db_error()?;
+
+  if !deleted {
+    #[derive(Debug, Error)]
+    #[error("User has not enabled two-factor authentication")]
+    struct NotEnabled;
+    return Err(Error::from_context(
+      crate::types::Error::NotFound,
+      NotEnabled,
+    ));
+  }
+
+  Ok(HttpResponse::NoContent().finish())
+}
+
+/// Completes logging in of a user who has enabled two-factor
+/// authentication with a challenge token given by [`super::login`].
+#[tracing::instrument]
+pub async fn login_2fa(
+  app: web::Data<App>,
+  client: ClientInfo,
+  form: Json<form::LoginRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  let challenge = Challenge::decode(&form.challenge_token, app.config.auth())
+    .change_type(crate::types::Error::Unauthorized)?;
+
+  let mut throttle_keys = vec![LoginThrottle::two_factor_key(challenge.user_id)];
+  if let Some(ip_address) = client.ip_address.as_deref() {
+    throttle_keys.push(LoginThrottle::ip_key(ip_address));
+  }
+
+  let attempt = app.login_throttle.attempt(&throttle_keys).await;
+  if let Some(retry_after) = attempt.retry_after() {
+    app.metrics.record_login(LoginOutcome::Throttled);
+    return Err(super::login::throttled(retry_after));
+  }
+
+  let mut conn = app.db_write().await?;
+  if !two_factor::verify_code(&app, &mut conn, challenge.user_id, &form.code).await? {
+    app.metrics.record_login(LoginOutcome::Failure);
+    return Err(invalid_code());
+  }
+  drop(conn);
+
+  super::login::release_throttle(&app, &throttle_keys, &attempt).await;
+
+  let tokens = session::start(&app, challenge.user_id, &client).await?;
+  app.metrics.record_login(LoginOutcome::Success);
+  Ok(HttpResponse::Ok().json(login::Response {
+    id: challenge.user_id,
+    token: tokens.access_token,
+    refresh_token: tokens.refresh_token,
+  }))
+}
+
+fn invalid_code() -> Error {
+  let mut error = ValidateError::field_builder();
+  let mut contents = ValidateError::msg_builder();
+  contents.insert("Invalid two-factor authentication code");
+  error.insert("code", contents.build());
+  error.build().into()
+}
+
+fn msg_error(message: &'static str) -> Error {
+  let mut error = ValidateError::msg_builder();
+  error.insert(message);
+  error.build().into()
+}
@@ -0,0 +1,129 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use sqlx::Connection;
+use validator::{Validate, ValidateError};
+
+use crate::{
+  auth::password::{self, Verification},
+  database::{
+    self,
+    error::{ErrorExt, ErrorExt2},
+  },
+  http::{error::ErrorStackContext, Actor, ClientInfo, Error},
+  schema::{User, UsernameHistory},
+  types::{
+    self,
+    form::users::username,
+    id::{marker::UserMarker, Id},
+  },
+  util::ago,
+  App,
+};
+
+#[tracing::instrument]
+pub async fn change_username(
+  app: web::Data<App>,
+  actor: Actor,
+  client: ClientInfo,
+  form: Json<username::ChangeRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+  let user = actor.get_user()?;
+
+  if form.username.as_str() == user.name {
+    let mut conn = app.db_read_prefer_primary().await?;
+    return Ok(HttpResponse::Ok().json(super::load_profile(&mut conn, user).await?));
+  }
+
+  let verification = super::login::confirm_password(&app, &user, &client, &form.password).await?;
+  if !verification.is_valid() {
+    return Err(field_error("password", "Invalid password"));
+  }
+
+  // Legacy password hashes are salted with the user's name, so
+  // they would stop working once the user gets renamed.
+  let password_hash = if verification == Verification::NeedsRehash {
+    let hash = password::hash(app.config.auth().password(), &form.password)
+      .await
+      .into_http_result()?;
+    Some(hash)
+  } else {
+    None
+  };
+
+  let users_cfg = app.config.users();
+  let mut conn = app.db_write().await?;
+  let mut tx = conn.begin().await.into_db_error()?;
+
+  if let Some(latest) = UsernameHistory::latest(&mut tx, user.id).await? {
+    if latest.changed_at > ago(users_cfg.username_change_cooldown()) {
+      return Err(field_error(
+        "username",
+        "You have changed your username recently, please try again later",
+      ));
+    }
+  }
+
+  if !is_name_available(&app, &mut tx, &form.username, Some(user.id)).await? {
+    return Err(field_error("username", "This username is not available"));
+  }
+
+  UsernameHistory::create(&mut tx, user.id, &user.name, &form.username).await?;
+  let result = User::update_name(&mut tx, user.id, &form.username).await;
+  if let Err(report) = result {
+    if report.unique_violation() == Some(User::NAME_UNIQUE_INDEX) {
+      return Err(field_error("username", "This username is not available"));
+    }
+    return Err(report.into());
+  }
+  if let Some(password_hash) = password_hash.as_deref() {
+    User::update_password_hash(&mut tx, user.id, password_hash).await?;
+  }
+
+  let Some(updated) = User::by_id(&mut tx, user.id).await? else {
+    #[derive(Debug, thiserror::Error)]
+    #[error("User has been deleted")]
+    struct Deleted;
+    return Err(Error::from_context(crate::types::Error::NotFound, Deleted));
+  };
+
+  let profile = super::load_profile(&mut tx, updated).await?;
+  tx.commit().await.into_db_error()?;
+  Ok(HttpResponse::Ok().json(profile))
+}
+
+/// Checks whether the name is neither reserved nor owned by anybody,
+/// including users who released it within the configured grace period.
+///
+/// Names which are confusable with another user's name (see
+/// [`types::username::skeleton`]) are not available as well.
+///
+/// `user_id` is allowed to take back their own released name.
+pub(super) async fn is_name_available(
+  app: &App,
+  conn: &mut database::Connection,
+  name: &str,
+  user_id: Option<Id<UserMarker>>,
+) -> Result<bool, Error> {
+  if types::username::check(name, app.config.users().reserved_usernames()).is_err() {
+    return Ok(false);
+  }
+
+  if User::name_conflicts(&mut *conn, name, user_id).await? {
+    return Ok(false);
+  }
+
+  let since = ago(app.config.users().username_grace_period());
+  let released = UsernameHistory::released_since(conn, name, since).await?;
+  Ok(released.map_or(true, |v| Some(v.user_id) == user_id))
+}
+
+pub(super) fn field_error(field: &'static str, message: &'static str) -> Error {
+  let mut error = ValidateError::field_builder();
+  let mut contents = ValidateError::msg_builder();
+  contents.insert(message);
+  error.insert(field, contents.build());
+  error.build().into()
+}
@@ -0,0 +1,83 @@
+use actix_web::{
+  web::{self, Json},
+  HttpResponse,
+};
+use sqlx::Connection;
+use validator::{Validate, ValidateError};
+
+use crate::{
+  auth::token,
+  database::error::ErrorExt,
+  http::{verification, Error},
+  schema::{EmailVerification, User},
+  types::form::users::verify_email,
+  App,
+};
+
+#[tracing::instrument]
+pub async fn verify_email(
+  app: web::Data<App>,
+  form: Json<verify_email::Request>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  let mut conn = app.db_write().await?;
+  let mut tx = conn.begin().await.into_db_error()?;
+
+  let Some(verification) = EmailVerification::consume(&mut tx, &token::hash(&form.token)).await?
+  else {
+    return Err(invalid_token());
+  };
+
+  // The user might have changed their email address after the token is sent
+  if !User::mark_email_verified(&mut tx, verification.user_id, &verification.email).await? {
+    return Err(invalid_token());
+  }
+
+  tx.commit().await.into_db_error()?;
+  Ok(HttpResponse::NoContent().finish())
+}
+
+/// Sends a new verification email. It always responds the same way
+/// regardless if the email address exists to avoid account enumeration.
+#[tracing::instrument]
+pub async fn resend_verification_email(
+  app: web::Data<App>,
+  form: Json<verify_email::ResendRequest>,
+) -> Result<HttpResponse, Error> {
+  form.validate()?;
+
+  // Looking up the user and sending the email are done in the
+  // background so the response time does not reveal whether
+  // the email address exists or not.
+  let task_app = app.clone();
+  let form = form.into_inner();
+  app.tasks.spawn(async move {
+    let app = task_app;
+    let result = async {
+      let mut conn = app.db_read_prefer_primary().await?;
+      let user = User::by_email(&mut conn, &form.email).await?;
+      drop(conn);
+
+      if let Some(user) = user.filter(User::needs_email_verification) {
+        verification::send(&app, &user).await?;
+      }
+      Ok::<_, Error>(())
+    }
+    .await;
+
+    if let Err(error) = result {
+      tracing::warn!(%error, "failed to resend verification email");
+    }
+  });
+
+  Ok(HttpResponse::Accepted().finish())
+}
+
+fn invalid_token() -> Error {
+  let mut error = ValidateError::field_builder();
+  let mut contents = ValidateError::msg_builder();
+  contents.insert("Invalid or expired verification token");
+  error.insert("token", contents.build());
+  error.build().into()
+}
@@ -0,0 +1,257 @@
+use error_stack::ResultExt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::io::Write;
+use thiserror::Error;
+
+use super::{error::ErrorStackContext, Error};
+use crate::{
+  config,
+  schema::{ApiToken, DataExport, Follow, Post, Session, User, UsernameHistory},
+  types::{
+    form::{
+      posts::post,
+      users::{
+        export::{Account, Archive, UsernameChange},
+        follows::FollowUser,
+        sessions, tokens,
+      },
+    },
+    id::{
+      marker::{DataExportMarker, UserMarker},
+      Id,
+    },
+  },
+  App,
+};
+
+#[derive(Debug, Error)]
+#[error("failed to build data export archive")]
+pub struct BuildError;
+
+/// Builds the archive of a data export in the background and
+/// stores it, marking the export as failed if anything goes wrong
+/// or the server shuts down before it is done.
+pub fn spawn_build(app: &App, user_id: Id<UserMarker>, id: Id<DataExportMarker>) {
+  let task_app = app.clone();
+  app.tasks.spawn(async move {
+    let app = task_app;
+    tokio::select! {
+      result = build_and_store(&app, user_id, id) => {
+        let Err(error) = result else {
+          return;
+        };
+        tracing::warn!(%error, "failed to build data export");
+      }
+      () = app.tasks.shutdown_token().cancelled() => {
+        tracing::warn!("data export is interrupted by shutting down");
+      }
+    }
+
+    let result = async {
+      let mut conn = app.db_write().await?;
+      DataExport::fail(&mut conn, id).await?;
+      Ok::<_, Error>(())
+    }
+    .await;
+
+    if let Err(error) = result {
+      tracing::warn!(%error, "failed to mark data export as failed");
+    }
+  });
+}
+
+#[tracing::instrument(skip_all)]
+async fn build_and_store(
+  app: &App,
+  user_id: Id<UserMarker>,
+  id: Id<DataExportMarker>,
+) -> Result<(), Error> {
+  let mut conn = app.db_read_prefer_primary().await?;
+  let Some(user) = User::by_id(&mut conn, user_id).await? else {
+    return Err(Error::from_context(
+      crate::types::Error::NotFound,
+      BuildError,
+    ));
+  };
+
+  let archive = Archive {
+    account: Account::new(user),
+    sessions: Session::list_active(&mut conn, user_id)
+      .await?
+      .into_iter()
+      .map(|v| sessions::Session::new(v, false))
+      .collect(),
+    api_tokens: ApiToken::list(&mut conn, user_id)
+      .await?
+      .into_iter()
+      .map(tokens::ApiToken::new)
+      .collect(),
+    username_history: UsernameHistory::list(&mut conn, user_id)
+      .await?
+      .into_iter()
+      .map(UsernameChange::new)
+      .collect(),
+    posts: Post::list_by_author(&mut conn, user_id)
+      .await?
+      .into_iter()
+      .map(post::Post::new)
+      .collect(),
+    // Every user at once rather than a page of them
+    following: Follow::list_following(&mut conn, user_id, None, i64::MAX)
+      .await?
+      .into_iter()
+      .map(FollowUser::new)
+      .collect(),
+    followers: Follow::list_followers(&mut conn, user_id, None, i64::MAX)
+      .await?
+      .into_iter()
+      .map(FollowUser::new)
+      .collect(),
+  };
+  drop(conn);
+
+  let data = tokio::task::spawn_blocking(move || zip_archive(&archive))
+    .await
+    .change_context(BuildError)
+    .into_http_result()?
+    .into_http_result()?;
+
+  let key = storage_key(user_id, id);
+  app.storage.put(&key, data).await.into_http_result()?;
+
+  let expires_at = crate::auth::token::expiry(app.config.users().export_lifetime());
+  let mut conn = app.db_write().await?;
+  DataExport::complete(&mut conn, id, &key, expires_at).await?;
+
+  Ok(())
+}
+
+fn zip_archive(archive: &Archive) -> error_stack::Result<Vec<u8>, BuildError> {
+  let json = serde_json::to_vec_pretty(archive).change_context(BuildError)?;
+
+  let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+  let options =
+    zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+  writer
+    .start_file("data.json", options)
+    .change_context(BuildError)?;
+  writer.write_all(&json).change_context(BuildError)?;
+
+  let cursor = writer.finish().change_context(BuildError)?;
+  Ok(cursor.into_inner())
+}
+
+fn storage_key(user_id: Id<UserMarker>, id: Id<DataExportMarker>) -> String {
+  format!("exports/{user_id}/{id}.zip")
+}
+
+/// Gets the signed URL to download a ready export.
+#[must_use]
+pub fn download_url(cfg: &config::Auth, export: &DataExport) -> Option<String> {
+  let expires = export.expires_at?.timestamp();
+  let signature = sign(cfg, export.id, expires);
+  Some(format!(
+    "/exports/{}?expires={expires}&signature={signature}",
+    export.id
+  ))
+}
+
+/// Checks whether a download URL is signed by this server and
+/// has not expired yet.
+#[must_use]
+pub fn verify_signature(
+  cfg: &config::Auth,
+  id: Id<DataExportMarker>,
+  expires: i64,
+  signature: &str,
+  now: i64,
+) -> bool {
+  if expires <= now {
+    return false;
+  }
+  let Ok(signature) = hex::decode(signature) else {
+    return false;
+  };
+  mac(cfg, id, expires).verify_slice(&signature).is_ok()
+}
+
+fn sign(cfg: &config::Auth, id: Id<DataExportMarker>, expires: i64) -> String {
+  hex::encode(mac(cfg, id, expires).finalize().into_bytes())
+}
+
+fn mac(cfg: &config::Auth, id: Id<DataExportMarker>, expires: i64) -> Hmac<Sha256> {
+  #[allow(clippy::expect_used)]
+  let mut mac = Hmac::<Sha256>::new_from_slice(cfg.jwt_key().value().as_ref().as_bytes())
+    .expect("HMAC accepts keys of any size");
+  mac.update(format!("data-export:{id}:{expires}").as_bytes());
+  mac
+}
+
+/// Deletes the archive of an export from the storage
+/// and then the export itself.
+#[tracing::instrument(skip_all)]
+pub async fn remove(app: &App, export: &DataExport) -> Result<(), Error> {
+  if let Some(key) = export.storage_key.as_deref() {
+    app.storage.delete(key).await.into_http_result()?;
+  }
+  let mut conn = app.db_write().await?;
+  DataExport::delete(&mut conn, export.id).await?;
+  Ok(())
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_signature() {
+    let cfg = config::Auth::default();
+    let id = Id::new(1);
+    let signature = sign(&cfg, id, 200);
+
+    assert!(verify_signature(&cfg, id, 200, &signature, 100));
+    // expired
+    assert!(!verify_signature(&cfg, id, 200, &signature, 200));
+    // tampered
+    assert!(!verify_signature(&cfg, id, 300, &signature, 100));
+    assert!(!verify_signature(&cfg, Id::new(2), 200, &signature, 100));
+    assert!(!verify_signature(&cfg, id, 200, "not hex", 100));
+
+    let other_cfg = config::Auth::default();
+    assert!(!verify_signature(&other_cfg, id, 200, &signature, 100));
+  }
+
+  #[test]
+  fn test_zip_archive() {
+    let archive = Archive {
+      account: Account {
+        id: Id::new(1),
+        name: "memothelemo".into(),
+        display_name: None,
+        email: None,
+        email_verified_at: None,
+        bio: None,
+        location: None,
+        website: None,
+        created_at: crate::types::Timestamp::now(),
+        updated_at: None,
+      },
+      sessions: Vec::new(),
+      api_tokens: Vec::new(),
+      username_history: Vec::new(),
+      posts: Vec::new(),
+      following: Vec::new(),
+      followers: Vec::new(),
+    };
+
+    let data = zip_archive(&archive).unwrap();
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+    let file = zip.by_name("data.json").unwrap();
+    let value: serde_json::Value = serde_json::from_reader(file).unwrap();
+    assert_eq!(value["account"]["name"], "memothelemo");
+    assert!(value["account"].get("password_hash").is_none());
+  }
+}
@@ -1,4 +1,8 @@
-use actix_web::{body::BoxBody, http::StatusCode, HttpResponse};
+use actix_web::{
+  body::BoxBody,
+  http::{header, StatusCode},
+  HttpResponse,
+};
 use error_stack::Report;
 
 use super::Error;
@@ -12,11 +16,24 @@ impl actix_web::ResponseError for Error {
       ErrorType::ReadonlyMode => StatusCode::SERVICE_UNAVAILABLE,
       ErrorType::InvalidFormBody(..) => StatusCode::BAD_REQUEST,
       ErrorType::Unauthorized => StatusCode::UNAUTHORIZED,
+      ErrorType::Forbidden
+      | ErrorType::EmailNotVerified
+      | ErrorType::AccountPendingDeletion
+      | ErrorType::MissingScope { .. } => StatusCode::FORBIDDEN,
+      ErrorType::LoginThrottled { .. } | ErrorType::RateLimited { .. } => {
+        StatusCode::TOO_MANY_REQUESTS
+      }
     }
   }
 
   fn error_response(&self) -> HttpResponse<BoxBody> {
-    HttpResponse::build(self.status_code()).json(&self.error_type)
+    let mut response = HttpResponse::build(self.status_code());
+    if let ErrorType::LoginThrottled { retry_after } | ErrorType::RateLimited { retry_after } =
+      &self.error_type
+    {
+      response.insert_header((header::RETRY_AFTER, *retry_after));
+    }
+    response.json(&self.error_type)
   }
 }
 
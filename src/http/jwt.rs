@@ -1,88 +1,260 @@
 use actix_web::{http::header, web, FromRequest};
-use chrono::{NaiveDateTime, Utc};
-use futures::Future;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::pin::Pin;
+use error_stack::{Report, Result};
+use futures::future::{ready, Ready};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
-  types::id::{marker::UserMarker, Id},
+  config,
+  types::{
+    id::{
+      marker::{SessionMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
   App,
 };
 
+/// Claims of a JWT issued by this server.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Jwt {
-  pub created_at: NaiveDateTime,
-  pub issuer: String,
-  pub exp_secs_until: u64,
+  /// The user who owns this token.
+  #[serde(rename = "sub")]
   pub user_id: Id<UserMarker>,
+  /// The session where this token was issued from.
+  #[serde(rename = "sid")]
+  pub session_id: Id<SessionMarker>,
+  #[serde(rename = "iss")]
+  pub issuer: String,
+  /// When this token was issued (in UNIX seconds).
+  #[serde(rename = "iat")]
+  pub issued_at: i64,
+  /// When this token will be expired (in UNIX seconds).
+  #[serde(rename = "exp")]
+  pub expires_at: i64,
+}
+
+/// The reason why a JWT is rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DecodeError {
+  #[error("token has expired")]
+  Expired,
+  #[error("token has an invalid signature")]
+  InvalidSignature,
+  #[error("token was issued by an unknown issuer")]
+  InvalidIssuer,
+  #[error("malformed token")]
+  Malformed,
 }
 
+#[derive(Debug, Error)]
+#[error("failed to encode JWT")]
+pub struct EncodeError;
+
 impl FromRequest for Jwt {
-  type Error = actix_web::Error;
-  type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+  type Error = super::Error;
+  type Future = Ready<std::result::Result<Self, Self::Error>>;
 
   fn from_request(
     req: &actix_web::HttpRequest,
     _payload: &mut actix_web::dev::Payload,
   ) -> Self::Future {
+    #[derive(Debug, Error)]
+    #[error("Authentication required")]
+    struct MissingToken;
+
+    #[derive(Debug, Error)]
+    #[error("The web app has no available configuration")]
+    struct NoConfig;
+
     let token = req
       .headers()
       .get(header::AUTHORIZATION)
-      .and_then(|v| v.to_str().ok());
-
-    let token = match token {
-      Some(n) => n,
-      None => {
-        return Box::pin(async {
-          Err(actix_web::error::ErrorUnauthorized(json!({
-              "message": "Authentication required",
-          })))
-        })
-      }
+      .and_then(|v| v.to_str().ok())
+      .and_then(|v| v.strip_prefix("Bearer "));
+
+    let Some(token) = token else {
+      return ready(Err(super::Error::from_context(
+        crate::types::Error::Unauthorized,
+        MissingToken,
+      )));
     };
 
-    let app = req
-      .app_data::<web::Data<App>>()
-      .expect("web::Data<App> is missing")
-      .clone();
+    let Some(app) = req.app_data::<web::Data<App>>() else {
+      return ready(Err(super::Error::from_context(
+        crate::types::Error::Internal,
+        NoConfig,
+      )));
+    };
 
-    let jwt = Jwt::decode(token, &app);
-    Box::pin(async move { Ok(jwt) })
+    ready(
+      Jwt::decode(token, app.config.auth())
+        .map_err(|e| super::Error::from_report(crate::types::Error::Unauthorized, e)),
+    )
   }
 }
 
+const ALGORITHM: Algorithm = Algorithm::HS512;
+
 impl Jwt {
-  #[tracing::instrument(skip(token))]
-  pub fn decode(token: &str, app: &App) -> Self {
-    let key = DecodingKey::from_secret(app.config.auth().jwt_key().value().as_ref().as_bytes());
-    let mut validation = Validation::new(Algorithm::HS512);
-    validation.validate_exp = false;
-    validation.required_spec_claims = Default::default();
-
-    jsonwebtoken::decode::<Self>(&token, &key, &validation)
-      .expect("failed to decode jwt")
-      .claims
+  /// Creates claims for a new token of a user's session
+  /// which expires after the configured JWT lifetime.
+  #[must_use]
+  pub fn new(user_id: Id<UserMarker>, session_id: Id<SessionMarker>, cfg: &config::Auth) -> Self {
+    let issued_at = Timestamp::now().timestamp();
+    let lifetime = i64::try_from(cfg.jwt_lifetime().as_secs()).unwrap_or(i64::MAX);
+    Self {
+      user_id,
+      session_id,
+      issuer: cfg.jwt_issuer().to_string(),
+      issued_at,
+      expires_at: issued_at.saturating_add(lifetime),
+    }
   }
 
-  #[tracing::instrument(skip(user_id))]
-  pub async fn encode(user_id: Id<UserMarker>, app: web::Data<App>) -> String {
-    tokio::task::spawn_blocking(move || {
-      let header = Header {
-        alg: Algorithm::HS512,
-        ..Default::default()
-      };
-      let claims = Self {
-        created_at: Utc::now().naive_utc(),
-        issuer: "server".into(),
-        exp_secs_until: 1_000_000,
-        user_id,
+  /// Decodes and validates a JWT.
+  ///
+  /// The token must be signed with the configured JWT secret key,
+  /// issued by this server and not yet expired (with the configured
+  /// leeway to tolerate clock skew).
+  #[tracing::instrument(skip_all)]
+  pub fn decode(token: &str, cfg: &config::Auth) -> Result<Self, DecodeError> {
+    decode_claims(token, cfg, &validation(cfg))
+  }
+
+  /// Signs these claims with the configured JWT secret key.
+  #[tracing::instrument(skip_all)]
+  pub fn encode(&self, cfg: &config::Auth) -> Result<String, EncodeError> {
+    encode_claims(self, cfg)
+  }
+}
+
+/// Default validation rules of every JWT issued by this server.
+pub(crate) fn validation(cfg: &config::Auth) -> Validation {
+  let mut validation = Validation::new(ALGORITHM);
+  validation.leeway = cfg.jwt_leeway().as_secs();
+  validation.set_issuer(&[cfg.jwt_issuer()]);
+  validation.set_required_spec_claims(&["exp", "iat", "iss", "sub"]);
+  validation
+}
+
+pub(crate) fn decode_claims<T: DeserializeOwned>(
+  token: &str,
+  cfg: &config::Auth,
+  validation: &Validation,
+) -> Result<T, DecodeError> {
+  let key = DecodingKey::from_secret(cfg.jwt_key().value().as_ref().as_bytes());
+  jsonwebtoken::decode::<T>(token, &key, validation)
+    .map(|v| v.claims)
+    .map_err(|e| {
+      let context = match e.kind() {
+        ErrorKind::ExpiredSignature => DecodeError::Expired,
+        ErrorKind::InvalidSignature | ErrorKind::InvalidAlgorithm => DecodeError::InvalidSignature,
+        ErrorKind::InvalidIssuer => DecodeError::InvalidIssuer,
+        _ => DecodeError::Malformed,
       };
-      let key = EncodingKey::from_secret(app.config.auth().jwt_key().value().as_ref().as_bytes());
-      jsonwebtoken::encode(&header, &claims, &key).expect("failed to create jwt")
+      Report::new(e).change_context(context)
     })
-    .await
-    .unwrap()
+}
+
+pub(crate) fn encode_claims<T: Serialize>(
+  claims: &T,
+  cfg: &config::Auth,
+) -> Result<String, EncodeError> {
+  let header = Header::new(ALGORITHM);
+  let key = EncodingKey::from_secret(cfg.jwt_key().value().as_ref().as_bytes());
+  jsonwebtoken::encode(&header, claims, &key)
+    .map_err(|e| Report::new(e).change_context(EncodeError))
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn user_id() -> Id<UserMarker> {
+    Id::new(1)
+  }
+
+  fn session_id() -> Id<SessionMarker> {
+    Id::new(1)
+  }
+
+  #[test]
+  fn test_roundtrip() {
+    let cfg = config::Auth::default();
+    let token = Jwt::new(user_id(), session_id(), &cfg)
+      .encode(&cfg)
+      .unwrap();
+
+    let jwt = Jwt::decode(&token, &cfg).unwrap();
+    assert_eq!(jwt.user_id, user_id());
+    assert_eq!(jwt.issuer, cfg.jwt_issuer());
+  }
+
+  #[test]
+  fn test_expired() {
+    let cfg = config::Auth::default();
+    let leeway = i64::try_from(cfg.jwt_leeway().as_secs()).unwrap();
+
+    let mut jwt = Jwt::new(user_id(), session_id(), &cfg);
+    jwt.issued_at -= 3600;
+    jwt.expires_at = Timestamp::now().timestamp() - leeway - 1;
+
+    let token = jwt.encode(&cfg).unwrap();
+    let error = Jwt::decode(&token, &cfg).unwrap_err();
+    assert_eq!(error.current_context(), &DecodeError::Expired);
+
+    // within the leeway
+    jwt.expires_at = Timestamp::now().timestamp() - leeway / 2;
+    let token = jwt.encode(&cfg).unwrap();
+    assert!(Jwt::decode(&token, &cfg).is_ok());
+  }
+
+  #[test]
+  fn test_tampered() {
+    let cfg = config::Auth::default();
+    let token = Jwt::new(user_id(), session_id(), &cfg)
+      .encode(&cfg)
+      .unwrap();
+
+    // swapping the payload with another user's claims
+    let other = Jwt::new(Id::new(2), session_id(), &cfg)
+      .encode(&cfg)
+      .unwrap();
+    let mut parts = token.split('.').collect::<Vec<_>>();
+    parts[1] = other.split('.').nth(1).unwrap();
+
+    let error = Jwt::decode(&parts.join("."), &cfg).unwrap_err();
+    assert_eq!(error.current_context(), &DecodeError::InvalidSignature);
+
+    // signed with a different key
+    let other_cfg = config::Auth::default();
+    let error = Jwt::decode(&token, &other_cfg).unwrap_err();
+    assert_eq!(error.current_context(), &DecodeError::InvalidSignature);
+  }
+
+  #[test]
+  fn test_invalid_issuer() {
+    let cfg = config::Auth::default();
+    let mut jwt = Jwt::new(user_id(), session_id(), &cfg);
+    jwt.issuer = "someone else".into();
+
+    let token = jwt.encode(&cfg).unwrap();
+    let error = Jwt::decode(&token, &cfg).unwrap_err();
+    assert_eq!(error.current_context(), &DecodeError::InvalidIssuer);
+  }
+
+  #[test]
+  fn test_garbage() {
+    let cfg = config::Auth::default();
+    for token in ["", "hello", "a.b.c", "eyJhbGciOiJIUzUxMiJ9..", "...."] {
+      assert!(Jwt::decode(token, &cfg).is_err(), "token = {token:?}");
+    }
+
+    let error = Jwt::decode("hello", &cfg).unwrap_err();
+    assert_eq!(error.current_context(), &DecodeError::Malformed);
   }
 }
@@ -0,0 +1,119 @@
+use actix_web::{
+  dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+  http::Method,
+  web,
+};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::time::Instant;
+
+use crate::App;
+
+/// Route label of requests which don't match any route, so
+/// scanners cannot flood the metrics with arbitrary paths.
+const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// Method label of requests with extension methods, for the same reason.
+const OTHER_METHOD: &str = "OTHER";
+
+/// Middleware recording the count and latency of every
+/// request by its method, route and response status.
+///
+/// It must wrap [`RateLimit`](super::rate_limit::RateLimit) so
+/// rate limited requests are recorded as well.
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Transform = RequestMetricsMiddleware<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(RequestMetricsMiddleware { service }))
+  }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+  service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let app = req
+      .app_data::<web::Data<App>>()
+      .filter(|app| app.config.metrics().enabled())
+      .cloned();
+
+    let Some(app) = app else {
+      return Box::pin(self.service.call(req));
+    };
+
+    let started_at = Instant::now();
+    let method = method_label(req.method());
+    let route = req
+      .match_pattern()
+      .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let future = self.service.call(req);
+    Box::pin(async move {
+      let result = future.await;
+      let status = match &result {
+        Ok(response) => response.status(),
+        Err(error) => error.as_response_error().status_code(),
+      };
+
+      app
+        .metrics
+        .record_http_request(method, &route, status.as_u16(), started_at.elapsed());
+
+      result
+    })
+  }
+}
+
+fn method_label(method: &Method) -> &'static str {
+  match *method {
+    Method::GET => "GET",
+    Method::POST => "POST",
+    Method::PUT => "PUT",
+    Method::DELETE => "DELETE",
+    Method::HEAD => "HEAD",
+    Method::OPTIONS => "OPTIONS",
+    Method::CONNECT => "CONNECT",
+    Method::PATCH => "PATCH",
+    Method::TRACE => "TRACE",
+    _ => OTHER_METHOD,
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_method_label() {
+    assert_eq!(method_label(&Method::GET), "GET");
+    assert_eq!(method_label(&Method::PATCH), "PATCH");
+
+    let extension = Method::from_bytes(b"FOO1").unwrap();
+    assert_eq!(method_label(&extension), OTHER_METHOD);
+  }
+}
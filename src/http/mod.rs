@@ -1,9 +1,19 @@
 pub mod actor;
+pub mod client;
 pub mod controllers;
+pub mod data_export;
 pub mod error;
 pub mod jwt;
+pub mod metrics;
+pub mod password_reset;
+pub mod rate_limit;
+pub mod session;
+pub mod tls;
+pub mod two_factor;
 pub mod util;
+pub mod verification;
 
 pub use actor::Actor;
+pub use client::ClientInfo;
 pub use error::Error;
 pub use jwt::Jwt;
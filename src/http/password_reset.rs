@@ -0,0 +1,54 @@
+use super::{error::ErrorStackContext, Error};
+use crate::{
+  auth::token,
+  mailer::Mail,
+  schema::{PasswordReset, User},
+  App,
+};
+
+/// Sends a new password reset token to the user's email address.
+///
+/// Previously sent tokens cannot be used anymore after
+/// calling this function.
+#[tracing::instrument(skip_all)]
+pub async fn send(app: &App, user: &User) -> Result<(), Error> {
+  let Some(email) = user.email.as_deref() else {
+    return Ok(());
+  };
+
+  let token = token::generate();
+  let lifetime = app.config.auth().password_reset_lifetime();
+
+  let mut conn = app.db_write().await?;
+  PasswordReset::delete_unused(&mut conn, user.id).await?;
+  PasswordReset::create(
+    &mut conn,
+    user.id,
+    &token::hash(&token),
+    token::expiry(lifetime),
+  )
+  .await?;
+  drop(conn);
+
+  let minutes = lifetime.as_secs() / 60;
+  let body = format!(
+    "Hello {name},\n\n\
+     Someone has requested to reset the password of your account. \
+     Use the following token to set a new password:\n\n\
+     {token}\n\n\
+     This token will expire in {minutes} minute(s). If you did not request \
+     a password reset, you can safely ignore this email.\n",
+    name = user.name,
+    token = token.as_str(),
+  );
+
+  app
+    .mailer
+    .send(Mail {
+      to: email.to_string(),
+      subject: "Reset your password".into(),
+      body,
+    })
+    .await
+    .into_http_result()
+}
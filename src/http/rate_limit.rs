@@ -0,0 +1,391 @@
+use actix_web::{
+  body::EitherBody,
+  dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+  http::header::{self, HeaderMap, HeaderName, HeaderValue},
+  web, HttpMessage, HttpRequest,
+};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use hashlink::LinkedHashMap;
+use std::{
+  rc::Rc,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+use thiserror::Error;
+
+use super::{actor::LookedUpApiToken, ClientInfo, Error, Jwt};
+use crate::{
+  auth::{api_token, token},
+  config,
+  schema::ApiToken,
+  types::id::{marker::UserMarker, Id},
+  App,
+};
+
+/// Outcome of counting a request against its rate limit policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+  pub allowed: bool,
+  /// Maximum number of requests allowed at once.
+  pub limit: u32,
+  /// Number of requests left at once.
+  pub remaining: u32,
+  /// Time until all of the requests are regained.
+  pub reset: Duration,
+  /// Time until the next request is allowed if it is denied.
+  pub retry_after: Duration,
+}
+
+/// Keeps the state of every rate limited client in memory with
+/// the [generic cell rate algorithm][gcra].
+///
+/// Each server instance has its own state, so the effective limits
+/// are multiplied by the number of server instances.
+///
+/// [gcra]: https://en.wikipedia.org/wiki/Generic_cell_rate_algorithm
+#[derive(Debug)]
+pub struct RateLimiter {
+  // Theoretical arrival time of each key, least recently counted first
+  states: Mutex<LinkedHashMap<String, Instant>>,
+  max_keys: usize,
+}
+
+impl Default for RateLimiter {
+  fn default() -> Self {
+    Self::with_max_keys(Self::MAX_KEYS)
+  }
+}
+
+impl RateLimiter {
+  // The least recently counted keys are forgotten once there
+  // are too many of them, so flooding the server from many
+  // addresses cannot grow the state indefinitely.
+  const MAX_KEYS: usize = 100_000;
+
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn with_max_keys(max_keys: usize) -> Self {
+    Self {
+      states: Mutex::new(LinkedHashMap::new()),
+      max_keys,
+    }
+  }
+
+  /// Counts a request of a key against a policy.
+  pub fn check(&self, key: &str, policy: &config::RateLimitPolicy) -> Decision {
+    self.check_at(key, policy, Instant::now())
+  }
+
+  fn check_at(&self, key: &str, policy: &config::RateLimitPolicy, now: Instant) -> Decision {
+    let interval = policy.emission_interval();
+    let limit = policy.burst();
+    let tolerance = interval * limit;
+
+    let mut states = match self.states.lock() {
+      Ok(states) => states,
+      Err(poisoned) => poisoned.into_inner(),
+    };
+
+    // Keys which regained all of their requests are removed. It
+    // stops at the first key still being limited so each request
+    // only removes the keys which have expired since the last one.
+    while states.front().is_some_and(|(_, tat)| *tat <= now) {
+      states.pop_front();
+    }
+
+    let tat = states.get(key).copied().unwrap_or(now).max(now);
+    let new_tat = tat + interval;
+    let pending = new_tat - now;
+
+    if pending > tolerance {
+      return Decision {
+        allowed: false,
+        limit,
+        remaining: 0,
+        reset: tat - now,
+        retry_after: pending.saturating_sub(tolerance),
+      };
+    }
+
+    states.insert(key.to_string(), new_tat);
+    if states.len() > self.max_keys {
+      states.pop_front();
+    }
+    let remaining = tolerance.saturating_sub(pending).as_nanos() / interval.as_nanos().max(1);
+    Decision {
+      allowed: true,
+      limit,
+      remaining: u32::try_from(remaining).unwrap_or(u32::MAX),
+      reset: pending,
+      retry_after: Duration::ZERO,
+    }
+  }
+}
+
+/// Middleware limiting requests according to the
+/// [rate limit config](config::RateLimit).
+///
+/// Every response has `RateLimit-Limit`, `RateLimit-Remaining`
+/// and `RateLimit-Reset` headers describing the client's limit.
+pub struct RateLimit;
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = actix_web::Error;
+  type Transform = RateLimitMiddleware<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(RateLimitMiddleware {
+      service: Rc::new(service),
+    }))
+  }
+}
+
+pub struct RateLimitMiddleware<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = actix_web::Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let service = Rc::clone(&self.service);
+    Box::pin(async move {
+      let decision = match req.app_data::<web::Data<App>>().cloned() {
+        Some(app) => check_request(&app, &req).await,
+        None => None,
+      };
+
+      let Some(decision) = decision else {
+        return Ok(service.call(req).await?.map_into_left_body());
+      };
+
+      if !decision.allowed {
+        #[derive(Debug, Error)]
+        #[error("Client has exceeded the rate limit")]
+        struct RateLimited;
+
+        let error = Error::from_context(
+          crate::types::Error::RateLimited {
+            retry_after: ceil_secs(decision.retry_after),
+          },
+          RateLimited,
+        );
+
+        let mut response = req.error_response(error);
+        insert_headers(response.headers_mut(), &decision);
+        return Ok(response.map_into_right_body());
+      }
+
+      let mut response = service.call(req).await?;
+      insert_headers(response.headers_mut(), &decision);
+      Ok(response.map_into_left_body())
+    })
+  }
+}
+
+async fn check_request(app: &App, req: &ServiceRequest) -> Option<Decision> {
+  let cfg = app.config.rate_limit();
+  // Health checks and metrics are polled frequently by load balancers
+  // and monitoring systems, they shouldn't be limited along with clients.
+  if !cfg.enabled() || req.path().starts_with("/health/") || req.path() == "/metrics" {
+    return None;
+  }
+
+  let (scope, policy) = cfg
+    .scope_of(req.path())
+    .unwrap_or(("*", cfg.default_policy()));
+
+  let token = req
+    .headers()
+    .get(header::AUTHORIZATION)
+    .and_then(|v| v.to_str().ok())
+    .and_then(|v| v.strip_prefix("Bearer "));
+
+  let user_id = match token {
+    Some(token) if api_token::is_api_token(token) => api_token_user_id(app, req, token).await,
+    // Only the JWT's signature is checked, there is no need to look
+    // up whether its session is still active just to count requests.
+    Some(token) => Jwt::decode(token, app.config.auth())
+      .ok()
+      .map(|jwt| jwt.user_id),
+    None => None,
+  };
+
+  let key = if let Some(user_id) = user_id {
+    format!("{scope}:user:{user_id}")
+  } else {
+    anonymous_key(scope, req.request())
+  };
+
+  Some(app.rate_limiter.check(&key, policy))
+}
+
+/// Looks up the owner of an API token. The token is kept in the
+/// request so it does not have to be looked up again for the
+/// [`Actor`](super::Actor). Invalid tokens are limited like
+/// anonymous clients.
+async fn api_token_user_id(app: &App, req: &ServiceRequest, token: &str) -> Option<Id<UserMarker>> {
+  let result = async {
+    let mut conn = app.db_read_prefer_primary().await?;
+    let api_token = ApiToken::by_token_hash(&mut conn, &token::hash(token)).await?;
+    Ok::<_, Error>(api_token)
+  }
+  .await;
+
+  match result {
+    Ok(api_token) => {
+      let user_id = api_token.as_ref().map(|v| v.user_id);
+      req.extensions_mut().insert(LookedUpApiToken(api_token));
+      user_id
+    }
+    Err(error) => {
+      tracing::warn!(%error, "failed to look up API token for rate limiting");
+      None
+    }
+  }
+}
+
+/// Anonymous clients are limited by their IP address, which is only
+/// taken from forwarding headers if they're sent by a trusted proxy.
+fn anonymous_key(scope: &str, req: &HttpRequest) -> String {
+  let client = ClientInfo::from_request(req);
+  let ip_address = client.ip_address.as_deref().unwrap_or("unknown");
+  format!("{scope}:ip:{ip_address}")
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &Decision) {
+  const LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+  const REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+  const RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+  headers.insert(LIMIT, HeaderValue::from(decision.limit));
+  headers.insert(REMAINING, HeaderValue::from(decision.remaining));
+  headers.insert(RESET, HeaderValue::from(ceil_secs(decision.reset)));
+}
+
+/// Rounds up a duration to seconds so clients do not retry too early.
+pub(crate) fn ceil_secs(duration: Duration) -> u64 {
+  duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn policy(requests: u32, period_secs: u64, burst: Option<u32>) -> config::RateLimitPolicy {
+    config::RateLimitPolicy {
+      requests: requests.try_into().unwrap_or(std::num::NonZeroU32::MIN),
+      period_secs: period_secs.try_into().unwrap_or(std::num::NonZeroU64::MIN),
+      burst: burst.and_then(|v| v.try_into().ok()),
+    }
+  }
+
+  #[test]
+  fn test_burst_and_replenish() {
+    let limiter = RateLimiter::new();
+    let policy = policy(3, 3, None);
+    let now = Instant::now();
+
+    for remaining in (0..3).rev() {
+      let decision = limiter.check_at("a", &policy, now);
+      assert!(decision.allowed);
+      assert_eq!(decision.remaining, remaining);
+    }
+
+    let decision = limiter.check_at("a", &policy, now);
+    assert!(!decision.allowed);
+    assert_eq!(decision.retry_after, Duration::from_secs(1));
+
+    // other keys are not affected
+    assert!(limiter.check_at("b", &policy, now).allowed);
+
+    // one request is regained every second
+    let later = now + Duration::from_secs(1);
+    assert!(limiter.check_at("a", &policy, later).allowed);
+    assert!(!limiter.check_at("a", &policy, later).allowed);
+  }
+
+  #[test]
+  fn test_custom_burst() {
+    let limiter = RateLimiter::new();
+    let policy = policy(60, 60, Some(1));
+    let now = Instant::now();
+
+    assert!(limiter.check_at("a", &policy, now).allowed);
+    assert!(!limiter.check_at("a", &policy, now).allowed);
+    assert!(
+      limiter
+        .check_at("a", &policy, now + Duration::from_secs(1))
+        .allowed
+    );
+  }
+
+  #[test]
+  fn test_forget_keys() {
+    let limiter = RateLimiter::with_max_keys(2);
+    let policy = policy(1, 60, None);
+    let now = Instant::now();
+
+    assert!(limiter.check_at("a", &policy, now).allowed);
+    assert!(limiter.check_at("b", &policy, now).allowed);
+    assert!(!limiter.check_at("a", &policy, now).allowed);
+
+    // the least recently counted key is forgotten
+    assert!(limiter.check_at("c", &policy, now).allowed);
+    assert!(limiter.check_at("a", &policy, now).allowed);
+    assert!(!limiter.check_at("c", &policy, now).allowed);
+
+    // keys which regained their requests are removed
+    let later = now + Duration::from_secs(60);
+    assert!(limiter.check_at("d", &policy, later).allowed);
+    assert_eq!(limiter.states.lock().map(|v| v.len()).ok(), Some(1));
+  }
+
+  #[test]
+  fn test_spoofed_forwarded_for() {
+    use actix_web::test::TestRequest;
+
+    let limiter = RateLimiter::new();
+    let policy = policy(1, 60, None);
+    let request = |forwarded_for: &str| {
+      TestRequest::default()
+        .peer_addr(([198, 51, 100, 1], 4711).into())
+        .insert_header((header::X_FORWARDED_FOR, forwarded_for))
+        .to_http_request()
+    };
+
+    let key = anonymous_key("login", &request("203.0.113.1"));
+    assert_eq!(key, "login:ip:198.51.100.1");
+    assert!(limiter.check(&key, &policy).allowed);
+
+    let key = anonymous_key("login", &request("203.0.113.2"));
+    assert!(!limiter.check(&key, &policy).allowed);
+  }
+
+  #[test]
+  fn test_ceil_secs() {
+    assert_eq!(ceil_secs(Duration::ZERO), 0);
+    assert_eq!(ceil_secs(Duration::from_millis(1)), 1);
+    assert_eq!(ceil_secs(Duration::from_secs(2)), 2);
+  }
+}
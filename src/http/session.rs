@@ -0,0 +1,128 @@
+use thiserror::Error;
+
+use super::{error::ErrorStackContext, ClientInfo, Error, Jwt};
+use crate::{
+  auth::token,
+  schema::Session,
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+  util::Sensitive,
+  App,
+};
+
+/// Tokens given to the client after logging in or refreshing a session.
+#[derive(Debug)]
+pub struct IssuedTokens {
+  pub session: Session,
+  pub access_token: Sensitive<String>,
+  pub refresh_token: Sensitive<String>,
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid or expired refresh token")]
+struct InvalidRefreshToken;
+
+#[derive(Debug, Error)]
+#[error("Refresh token has been reused")]
+struct RefreshTokenReused;
+
+/// Starts a new session for a user.
+#[tracing::instrument(skip_all)]
+pub async fn start(
+  app: &App,
+  user_id: Id<UserMarker>,
+  client: &ClientInfo,
+) -> Result<IssuedTokens, Error> {
+  let refresh_token = token::generate();
+  let expires_at = token::expiry(app.config.auth().refresh_token_lifetime());
+
+  let mut conn = app.db_write().await?;
+  let session = Session::create(
+    &mut conn,
+    user_id,
+    &token::hash(&refresh_token),
+    expires_at,
+    client.user_agent.as_deref(),
+    client.ip_address.as_deref(),
+  )
+  .await?;
+  drop(conn);
+
+  let access_token = Jwt::new(user_id, session.id, app.config.auth())
+    .encode(app.config.auth())
+    .into_http_result()?;
+
+  Ok(IssuedTokens {
+    session,
+    access_token: access_token.into(),
+    refresh_token,
+  })
+}
+
+/// Exchanges a refresh token for a new access token and a new
+/// refresh token. The old refresh token cannot be used anymore.
+///
+/// If an already rotated refresh token is presented, the session
+/// will be revoked as either the client or an attacker holds
+/// a stolen copy of the token.
+#[tracing::instrument(skip_all)]
+pub async fn refresh(app: &App, refresh_token: &str) -> Result<IssuedTokens, Error> {
+  let old_hash = token::hash(refresh_token);
+  let mut conn = app.db_write().await?;
+
+  let Some(session) = Session::by_refresh_token_hash(&mut conn, &old_hash).await? else {
+    return Err(Error::from_context(
+      crate::types::Error::Unauthorized,
+      InvalidRefreshToken,
+    ));
+  };
+
+  if !session.is_active(Timestamp::now()) {
+    return Err(Error::from_context(
+      crate::types::Error::Unauthorized,
+      InvalidRefreshToken,
+    ));
+  }
+
+  if session.refresh_token_hash != old_hash {
+    tracing::warn!(session.id = %session.id, "refresh token reuse detected; revoking session");
+    Session::revoke(&mut conn, session.id, session.user_id).await?;
+    return Err(Error::from_context(
+      crate::types::Error::Unauthorized,
+      RefreshTokenReused,
+    ));
+  }
+
+  let new_refresh_token = token::generate();
+  let rotated = Session::rotate(
+    &mut conn,
+    session.id,
+    &old_hash,
+    &token::hash(&new_refresh_token),
+    token::expiry(app.config.auth().refresh_token_lifetime()),
+  )
+  .await?;
+
+  // Someone else has rotated the refresh token at the same time
+  let Some(session) = rotated else {
+    tracing::warn!(session.id = %session.id, "concurrent refresh token use detected; revoking session");
+    Session::revoke(&mut conn, session.id, session.user_id).await?;
+    return Err(Error::from_context(
+      crate::types::Error::Unauthorized,
+      RefreshTokenReused,
+    ));
+  };
+  drop(conn);
+
+  let access_token = Jwt::new(session.user_id, session.id, app.config.auth())
+    .encode(app.config.auth())
+    .into_http_result()?;
+
+  Ok(IssuedTokens {
+    session,
+    access_token: access_token.into(),
+    refresh_token: new_refresh_token,
+  })
+}
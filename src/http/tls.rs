@@ -0,0 +1,87 @@
+use error_stack::{Report, Result, ResultExt};
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use std::{fs::File, io::BufReader, path::Path};
+use thiserror::Error;
+
+use crate::config;
+
+#[derive(Debug, Error)]
+#[error("failed to load TLS certificate and private key")]
+pub struct LoadError;
+
+/// Loads the certificate chain and private key from the
+/// [TLS config](config::Tls) to serve HTTPS.
+pub fn load(cfg: &config::Tls) -> Result<ServerConfig, LoadError> {
+  let certs = rustls_pemfile::certs(&mut open(cfg.cert_path())?)
+    .change_context(LoadError)
+    .attach_printable_lazy(|| format!("with certificate: {}", cfg.cert_path().display()))?;
+
+  if certs.is_empty() {
+    return Err(Report::new(LoadError).attach_printable(format!(
+      "no certificates found in {}",
+      cfg.cert_path().display()
+    )));
+  }
+
+  let key = rustls_pemfile::read_all(&mut open(cfg.key_path())?)
+    .change_context(LoadError)
+    .attach_printable_lazy(|| format!("with private key: {}", cfg.key_path().display()))?
+    .into_iter()
+    .find_map(|item| match item {
+      rustls_pemfile::Item::PKCS8Key(key)
+      | rustls_pemfile::Item::RSAKey(key)
+      | rustls_pemfile::Item::ECKey(key) => Some(key),
+      _ => None,
+    });
+
+  let Some(key) = key else {
+    return Err(Report::new(LoadError).attach_printable(format!(
+      "no private key found in {}",
+      cfg.key_path().display()
+    )));
+  };
+
+  ServerConfig::builder()
+    .with_safe_defaults()
+    .with_no_client_auth()
+    .with_single_cert(
+      certs.into_iter().map(Certificate).collect(),
+      PrivateKey(key),
+    )
+    .change_context(LoadError)
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, LoadError> {
+  File::open(path)
+    .map(BufReader::new)
+    .change_context(LoadError)
+    .attach_printable_lazy(|| format!("with file: {}", path.display()))
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_load_errors() {
+    let dir = std::env::temp_dir().join(format!("whim-tls-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let cfg = config::Tls {
+      cert_path: dir.join("missing.pem"),
+      key_path: dir.join("missing.pem"),
+    };
+    assert!(load(&cfg).is_err());
+
+    let garbage = dir.join("garbage.pem");
+    std::fs::write(&garbage, "not a certificate").unwrap();
+    let cfg = config::Tls {
+      cert_path: garbage.clone(),
+      key_path: garbage,
+    };
+    assert!(load(&cfg).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  error::ErrorStackContext,
+  jwt::{self, DecodeError, EncodeError},
+  Error,
+};
+use crate::{
+  auth::{cipher::Cipher, recovery_code, totp},
+  config,
+  database::Connection,
+  schema::{RecoveryCode, TotpSecret},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+  App,
+};
+
+/// Claims of a short-lived token given to a user who has entered
+/// their password correctly but not their two-factor authentication
+/// code yet.
+///
+/// It cannot be used as an access token because it has no session.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Challenge {
+  #[serde(rename = "sub")]
+  pub user_id: Id<UserMarker>,
+  #[serde(rename = "iss")]
+  pub issuer: String,
+  #[serde(rename = "aud")]
+  pub audience: String,
+  #[serde(rename = "iat")]
+  pub issued_at: i64,
+  #[serde(rename = "exp")]
+  pub expires_at: i64,
+}
+
+impl Challenge {
+  const AUDIENCE: &'static str = "2fa";
+
+  /// Creates claims for a new challenge token which expires after
+  /// the configured two-factor challenge lifetime.
+  #[must_use]
+  pub fn new(user_id: Id<UserMarker>, cfg: &config::Auth) -> Self {
+    let issued_at = Timestamp::now().timestamp();
+    let lifetime = i64::try_from(cfg.two_factor_challenge_lifetime().as_secs()).unwrap_or(i64::MAX);
+    Self {
+      user_id,
+      issuer: cfg.jwt_issuer().to_string(),
+      audience: Self::AUDIENCE.into(),
+      issued_at,
+      expires_at: issued_at.saturating_add(lifetime),
+    }
+  }
+
+  /// Decodes and validates a challenge token with the
+  /// same rules as [access tokens](super::Jwt::decode).
+  #[tracing::instrument(skip_all)]
+  pub fn decode(token: &str, cfg: &config::Auth) -> error_stack::Result<Self, DecodeError> {
+    let mut validation = jwt::validation(cfg);
+    validation.set_audience(&[Self::AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "iat", "iss", "sub", "aud"]);
+    jwt::decode_claims(token, cfg, &validation)
+  }
+
+  #[tracing::instrument(skip_all)]
+  pub fn encode(&self, cfg: &config::Auth) -> error_stack::Result<String, EncodeError> {
+    jwt::encode_claims(self, cfg)
+  }
+}
+
+/// Gets the cipher used to encrypt and decrypt users' TOTP secrets.
+pub fn cipher(app: &App) -> Cipher {
+  Cipher::new(app.config.auth().totp_key().into_inner())
+}
+
+/// Verifies either a TOTP code or an unused recovery code of a user
+/// who has enabled two-factor authentication.
+///
+/// Accepted codes cannot be used again.
+#[tracing::instrument(skip_all)]
+pub async fn verify_code(
+  app: &App,
+  conn: &mut Connection,
+  user_id: Id<UserMarker>,
+  code: &str,
+) -> Result<bool, Error> {
+  let secret = TotpSecret::by_user_id(&mut *conn, user_id).await?;
+  let Some(secret) = secret.filter(TotpSecret::is_enabled) else {
+    return Ok(false);
+  };
+
+  let decrypted = cipher(app)
+    .decrypt(&secret.encrypted_secret)
+    .into_http_result()?;
+
+  let now = Timestamp::now().timestamp();
+  if let Some(step) = totp::verify(decrypted.as_ref(), code, now, secret.last_used_step) {
+    return Ok(TotpSecret::use_step(conn, user_id, step).await?);
+  }
+
+  Ok(RecoveryCode::consume(conn, user_id, &recovery_code::hash(code)).await?)
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http::Jwt;
+
+  #[test]
+  fn test_roundtrip() {
+    let cfg = config::Auth::default();
+    let token = Challenge::new(Id::new(1), &cfg).encode(&cfg).unwrap();
+    let challenge = Challenge::decode(&token, &cfg).unwrap();
+    assert_eq!(challenge.user_id, Id::new(1));
+  }
+
+  #[test]
+  fn test_not_interchangeable_with_access_tokens() {
+    let cfg = config::Auth::default();
+    let token = Challenge::new(Id::new(1), &cfg).encode(&cfg).unwrap();
+    assert!(Jwt::decode(&token, &cfg).is_err());
+
+    let token = Jwt::new(Id::new(1), Id::new(1), &cfg).encode(&cfg).unwrap();
+    assert!(Challenge::decode(&token, &cfg).is_err());
+  }
+}
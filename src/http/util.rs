@@ -3,9 +3,14 @@ use actix_web::{dev::ServiceResponse, middleware::ErrorHandlerResponse, HttpResp
 use super::Error;
 use crate::types;
 
-use actix_web::{http::StatusCode, ResponseError};
+use actix_web::{
+  http::{header::HeaderMap, header::HeaderName, StatusCode},
+  ResponseError,
+};
+use opentelemetry::{propagation::Extractor, trace::TraceContextExt};
 use tracing::Span;
 use tracing_actix_web::RootSpanBuilder;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 // Code in this module adapted from DefaultRootSpanBuilder
 // https://github.com/LukeMathWalker/tracing-actix-web/blob/main/src/root_span_builder.rs
@@ -18,7 +23,7 @@ impl RootSpanBuilder for QuieterRootSpanBuilder {
   fn on_request_start(request: &actix_web::dev::ServiceRequest) -> Span {
     let request_id = tracing_actix_web::root_span_macro::private::get_request_id(request);
 
-    tracing::info_span!(
+    let span = tracing::info_span!(
         "HTTP request",
         http.method = %request.method(),
         http.scheme = request.connection_info().scheme(),
@@ -32,7 +37,10 @@ impl RootSpanBuilder for QuieterRootSpanBuilder {
         exception.message = tracing::field::Empty,
         // Not proper OpenTelemetry, but their terminology is fairly exception-centric
         exception.details = tracing::field::Empty,
-    )
+    );
+
+    set_remote_parent(request, &span);
+    span
   }
 
   fn on_request_end<B>(
@@ -58,6 +66,33 @@ impl RootSpanBuilder for QuieterRootSpanBuilder {
   }
 }
 
+/// Continues the trace of the client (from the W3C `traceparent`
+/// header) if there's any, so this request shows up under it.
+fn set_remote_parent(request: &actix_web::dev::ServiceRequest, span: &Span) {
+  let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+    propagator.extract(&HeaderExtractor(request.headers()))
+  });
+  span.set_parent(parent);
+
+  // It is invalid if traces are not exported
+  let span_context = span.context().span().span_context().clone();
+  if span_context.is_valid() {
+    span.record("trace_id", tracing::field::display(span_context.trace_id()));
+  }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+  fn get(&self, key: &str) -> Option<&str> {
+    self.0.get(key).and_then(|v| v.to_str().ok())
+  }
+
+  fn keys(&self) -> Vec<&str> {
+    self.0.keys().map(HeaderName::as_str).collect()
+  }
+}
+
 fn handle_error(span: Span, status_code: StatusCode, response_error: &dyn ResponseError) {
   let code: i32 = status_code.as_u16().into();
 
@@ -0,0 +1,54 @@
+use super::{error::ErrorStackContext, Error};
+use crate::{
+  auth::token,
+  mailer::Mail,
+  schema::{EmailVerification, User},
+  App,
+};
+
+/// Sends a new email verification token to the user's email address.
+///
+/// Previously sent tokens cannot be used anymore after
+/// calling this function.
+#[tracing::instrument(skip_all)]
+pub async fn send(app: &App, user: &User) -> Result<(), Error> {
+  let Some(email) = user.email.as_deref() else {
+    return Ok(());
+  };
+
+  let token = token::generate();
+  let lifetime = app.config.auth().email_verification_lifetime();
+
+  let mut conn = app.db_write().await?;
+  EmailVerification::delete_unused(&mut conn, user.id).await?;
+  EmailVerification::create(
+    &mut conn,
+    user.id,
+    email,
+    &token::hash(&token),
+    token::expiry(lifetime),
+  )
+  .await?;
+  drop(conn);
+
+  let hours = lifetime.as_secs() / 3600;
+  let body = format!(
+    "Hello {name},\n\n\
+     Use the following token to verify your email address:\n\n\
+     {token}\n\n\
+     This token will expire in {hours} hour(s). If you did not create \
+     an account, you can safely ignore this email.\n",
+    name = user.name,
+    token = token.as_str(),
+  );
+
+  app
+    .mailer
+    .send(Mail {
+      to: email.to_string(),
+      subject: "Verify your email address".into(),
+      body,
+    })
+    .await
+    .into_http_result()
+}
@@ -0,0 +1,89 @@
+//! Background jobs running alongside the web server.
+use std::time::Duration;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+  http::{data_export, Error},
+  schema::{DataExport, User},
+  util::ago,
+  App,
+};
+
+/// Spawns every background job of the server.
+///
+/// Jobs stop once `shutdown` is cancelled. A job which is
+/// currently running is allowed to finish its work first,
+/// so the returned handles should be awaited before closing
+/// the database pools.
+pub fn spawn(app: &App, shutdown: &CancellationToken) -> Vec<JoinHandle<()>> {
+  vec![
+    tokio::spawn(purge_deleted_users(app.clone(), shutdown.clone())),
+    tokio::spawn(remove_stale_exports(app.clone(), shutdown.clone())),
+  ]
+}
+
+/// Periodically deletes accounts whose deletion grace period is over.
+async fn purge_deleted_users(app: App, shutdown: CancellationToken) {
+  let users_cfg = app.config.users();
+  let mut interval = tokio::time::interval(users_cfg.deletion_purge_interval());
+  interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+  loop {
+    tokio::select! {
+      () = shutdown.cancelled() => break,
+      _ = interval.tick() => {}
+    }
+
+    let result = async {
+      let before = ago(users_cfg.deletion_grace_period());
+      let mut conn = app.db_write().await?;
+      let purged = User::purge_deleted(&mut conn, before).await?;
+      Ok::<_, Error>(purged)
+    }
+    .await;
+
+    match result {
+      Ok(0) => {}
+      Ok(purged) => tracing::info!(purged, "purged deleted users"),
+      Err(error) => tracing::warn!(%error, "failed to purge deleted users"),
+    }
+  }
+}
+
+/// How often archives of stale data exports are removed.
+const REMOVE_STALE_EXPORTS_INTERVAL: Duration = Duration::from_secs(60 * 15);
+
+/// Exports which have been pending for this long are
+/// assumed to be interrupted (by restarting the server).
+const PENDING_EXPORT_TIMEOUT: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Periodically removes archives of data exports which have been
+/// downloaded, expired, failed or belong to purged users.
+async fn remove_stale_exports(app: App, shutdown: CancellationToken) {
+  let mut interval = tokio::time::interval(REMOVE_STALE_EXPORTS_INTERVAL);
+  interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+  loop {
+    tokio::select! {
+      () = shutdown.cancelled() => break,
+      _ = interval.tick() => {}
+    }
+
+    let result = async {
+      let mut conn = app.db_read_prefer_primary().await?;
+      let stale = DataExport::list_stale(&mut conn, ago(PENDING_EXPORT_TIMEOUT)).await?;
+      drop(conn);
+
+      for export in &stale {
+        data_export::remove(&app, export).await?;
+      }
+      Ok::<_, Error>(())
+    }
+    .await;
+
+    if let Err(error) = result {
+      tracing::warn!(%error, "failed to remove stale data exports");
+    }
+  }
+}
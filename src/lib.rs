@@ -1,8 +1,16 @@
 pub mod app;
+pub mod auth;
 pub mod config;
 pub mod database;
 pub mod http;
+pub mod jobs;
+pub mod mailer;
+pub mod metrics;
 pub mod schema;
+pub mod shutdown;
+pub mod storage;
+pub mod telemetry;
+pub mod throttle;
 pub mod types;
 pub mod util;
 
@@ -0,0 +1,64 @@
+use error_stack::{Result, ResultExt};
+use futures::future::BoxFuture;
+use lettre::{message::Mailbox, AsyncFileTransport, AsyncTransport, Tokio1Executor};
+use std::path::Path;
+
+use super::{Error, Mail, Mailer};
+
+/// Writes emails as `.eml` files into a directory.
+#[derive(Debug)]
+pub struct FileMailer {
+  from: Mailbox,
+  transport: AsyncFileTransport<Tokio1Executor>,
+}
+
+impl FileMailer {
+  #[must_use]
+  pub fn new(from: Mailbox, path: &Path) -> Self {
+    Self {
+      from,
+      transport: AsyncFileTransport::new(path),
+    }
+  }
+}
+
+impl Mailer for FileMailer {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), Error>> {
+    Box::pin(async move {
+      let message = super::build_message(&self.from, mail)?;
+      self
+        .transport
+        .send(message)
+        .await
+        .change_context(Error::Send)?;
+
+      Ok(())
+    })
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_file_mailer() {
+    let dir = std::env::temp_dir().join(format!("whim-mailer-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mailer = FileMailer::new("Whim <noreply@localhost>".parse().unwrap(), &dir);
+    mailer
+      .send(Mail {
+        to: "gush@gmail.com".into(),
+        subject: "Hello".into(),
+        body: "World".into(),
+      })
+      .await
+      .unwrap();
+
+    let files = std::fs::read_dir(&dir).unwrap().count();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(files, 1);
+  }
+}
@@ -0,0 +1,58 @@
+use error_stack::Result;
+use futures::future::BoxFuture;
+use std::sync::{Arc, Mutex};
+
+use super::{Error, Mail, Mailer};
+
+/// Keeps all sent emails in memory instead of delivering them.
+///
+/// This is only useful for testing and development.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMailer {
+  mails: Arc<Mutex<Vec<Mail>>>,
+}
+
+impl MemoryMailer {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Gets all of the sent emails.
+  #[must_use]
+  pub fn mails(&self) -> Vec<Mail> {
+    match self.mails.lock() {
+      Ok(mails) => mails.clone(),
+      Err(poisoned) => poisoned.into_inner().clone(),
+    }
+  }
+}
+
+impl Mailer for MemoryMailer {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), Error>> {
+    tracing::debug!(subject = %mail.subject, "email is kept in memory and will not be delivered");
+    match self.mails.lock() {
+      Ok(mut mails) => mails.push(mail),
+      Err(poisoned) => poisoned.into_inner().push(mail),
+    }
+    Box::pin(async { Ok(()) })
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_memory_mailer() {
+    let mailer = MemoryMailer::new();
+    let mail = Mail {
+      to: "gush@gmail.com".into(),
+      subject: "Hello".into(),
+      body: "World".into(),
+    };
+    mailer.send(mail.clone()).await.unwrap();
+    assert_eq!(mailer.mails(), vec![mail]);
+  }
+}
@@ -0,0 +1,79 @@
+use error_stack::{Report, Result, ResultExt};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::config;
+
+mod file;
+mod memory;
+mod smtp;
+
+pub use file::FileMailer;
+pub use memory::MemoryMailer;
+pub use smtp::SmtpMailer;
+
+/// Mailer related errors
+#[derive(Debug, Error)]
+pub enum Error {
+  /// The mailer config is invalid (the sender's mailbox for example).
+  #[error("invalid mailer configuration")]
+  InvalidConfig,
+  /// The recipient's email address cannot be parsed.
+  #[error("invalid recipient address")]
+  InvalidRecipient,
+  /// The email cannot be built or delivered.
+  #[error("failed to send email")]
+  Send,
+}
+
+/// A plain text email to be sent to a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mail {
+  pub to: String,
+  pub subject: String,
+  pub body: String,
+}
+
+/// Delivers emails to users.
+///
+/// Implementations must be cheap to share between threads
+/// as the same mailer is used for the entire server.
+pub trait Mailer: std::fmt::Debug + Send + Sync {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), Error>>;
+}
+
+/// Creates a mailer from the [mailer config](config::Mailer).
+pub fn from_config(cfg: &config::Mailer) -> Result<Arc<dyn Mailer>, Error> {
+  let from = cfg
+    .from()
+    .parse::<lettre::message::Mailbox>()
+    .change_context(Error::InvalidConfig)
+    .attach_printable("invalid sender mailbox")?;
+
+  let mailer: Arc<dyn Mailer> = match cfg.transport() {
+    config::MailTransport::Memory => Arc::new(MemoryMailer::new()),
+    config::MailTransport::File { path } => Arc::new(FileMailer::new(from, path)),
+    config::MailTransport::Smtp(smtp) => Arc::new(SmtpMailer::new(from, smtp)?),
+  };
+
+  Ok(mailer)
+}
+
+/// Builds a plain text email message from a [`Mail`].
+fn build_message(from: &lettre::message::Mailbox, mail: Mail) -> Result<lettre::Message, Error> {
+  use lettre::message::header::ContentType;
+
+  let to = mail
+    .to
+    .parse::<lettre::message::Mailbox>()
+    .map_err(|e| Report::new(e).change_context(Error::InvalidRecipient))?;
+
+  lettre::Message::builder()
+    .from(from.clone())
+    .to(to)
+    .subject(mail.subject)
+    .header(ContentType::TEXT_PLAIN)
+    .body(mail.body)
+    .change_context(Error::Send)
+}
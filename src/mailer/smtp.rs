@@ -0,0 +1,69 @@
+use error_stack::{Result, ResultExt};
+use futures::future::BoxFuture;
+use lettre::{
+  message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+  AsyncTransport, Tokio1Executor,
+};
+
+use super::{Error, Mail, Mailer};
+use crate::config::{SmtpEncryption, SmtpTransport};
+
+/// Delivers emails through an SMTP server.
+#[derive(Clone)]
+pub struct SmtpMailer {
+  from: Mailbox,
+  transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl std::fmt::Debug for SmtpMailer {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("SmtpMailer")
+      .field("from", &self.from)
+      .finish_non_exhaustive()
+  }
+}
+
+impl SmtpMailer {
+  pub fn new(from: Mailbox, cfg: &SmtpTransport) -> Result<Self, Error> {
+    type Transport = AsyncSmtpTransport<Tokio1Executor>;
+
+    let mut builder = match cfg.encryption() {
+      SmtpEncryption::Starttls => Transport::starttls_relay(cfg.host()),
+      SmtpEncryption::Tls => Transport::relay(cfg.host()),
+      SmtpEncryption::None => Ok(Transport::builder_dangerous(cfg.host())),
+    }
+    .change_context(Error::InvalidConfig)
+    .attach_printable_lazy(|| format!("with SMTP host: {}", cfg.host()))?;
+
+    if let Some(port) = cfg.port() {
+      builder = builder.port(port);
+    }
+
+    if let Some((username, password)) = cfg.credentials() {
+      builder = builder.credentials(Credentials::new(
+        username.to_string(),
+        password.as_str().to_string(),
+      ));
+    }
+
+    Ok(Self {
+      from,
+      transport: builder.build(),
+    })
+  }
+}
+
+impl Mailer for SmtpMailer {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), Error>> {
+    Box::pin(async move {
+      let message = super::build_message(&self.from, mail)?;
+      self
+        .transport
+        .send(message)
+        .await
+        .change_context(Error::Send)?;
+
+      Ok(())
+    })
+  }
+}
@@ -0,0 +1,227 @@
+//! Prometheus metrics exported at `/metrics`.
+use error_stack::{Report, Result};
+use prometheus::{
+  Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts,
+  Registry, TextEncoder,
+};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::database;
+
+#[derive(Debug, Error)]
+#[error("failed to register metrics")]
+pub struct RegisterError;
+
+#[derive(Debug, Error)]
+#[error("failed to encode metrics")]
+pub struct EncodeError;
+
+/// Outcome of a login attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+  /// A session is started for the user.
+  Success,
+  /// The user's credentials are correct but they
+  /// still need to complete the two-factor challenge.
+  TwoFactorRequired,
+  /// Invalid credentials or two-factor authentication code.
+  Failure,
+  /// The attempt is rejected by the login throttle.
+  Throttled,
+}
+
+impl LoginOutcome {
+  const fn as_str(self) -> &'static str {
+    match self {
+      Self::Success => "success",
+      Self::TwoFactorRequired => "two_factor_required",
+      Self::Failure => "failure",
+      Self::Throttled => "throttled",
+    }
+  }
+}
+
+/// Every metric collected by the server.
+///
+/// Database pool gauges are only updated when metrics are
+/// encoded, so they're always up to date when scraped.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+  registry: Registry,
+  http_requests: IntCounterVec,
+  http_request_duration: HistogramVec,
+  db_acquire_duration: HistogramVec,
+  db_connections: IntGaugeVec,
+  db_idle_connections: IntGaugeVec,
+  db_replica_fallbacks: IntCounter,
+  logins: IntCounterVec,
+}
+
+impl Metrics {
+  pub fn new() -> Result<Self, RegisterError> {
+    let registry = Registry::new_custom(Some("whim".into()), None).map_err(register_error)?;
+
+    let http_requests = IntCounterVec::new(
+      Opts::new("http_requests_total", "Total number of HTTP requests"),
+      &["method", "route", "status"],
+    )
+    .map_err(register_error)?;
+
+    let http_request_duration = HistogramVec::new(
+      HistogramOpts::new(
+        "http_request_duration_seconds",
+        "Time taken to respond to HTTP requests",
+      ),
+      &["method", "route", "status"],
+    )
+    .map_err(register_error)?;
+
+    let db_acquire_duration = HistogramVec::new(
+      HistogramOpts::new(
+        "db_acquire_duration_seconds",
+        "Time taken to obtain a connection from a database pool",
+      ),
+      &["pool"],
+    )
+    .map_err(register_error)?;
+
+    let db_connections = IntGaugeVec::new(
+      Opts::new(
+        "db_connections",
+        "Number of open connections of a database pool",
+      ),
+      &["pool"],
+    )
+    .map_err(register_error)?;
+
+    let db_idle_connections = IntGaugeVec::new(
+      Opts::new(
+        "db_idle_connections",
+        "Number of idle connections of a database pool",
+      ),
+      &["pool"],
+    )
+    .map_err(register_error)?;
+
+    let db_replica_fallbacks = IntCounter::new(
+      "db_replica_fallbacks_total",
+      "Number of reads served by the primary database because the replica is unhealthy",
+    )
+    .map_err(register_error)?;
+
+    let logins = IntCounterVec::new(
+      Opts::new("logins_total", "Total number of login attempts"),
+      &["outcome"],
+    )
+    .map_err(register_error)?;
+
+    registry
+      .register(Box::new(http_requests.clone()))
+      .map_err(register_error)?;
+    registry
+      .register(Box::new(http_request_duration.clone()))
+      .map_err(register_error)?;
+    registry
+      .register(Box::new(db_acquire_duration.clone()))
+      .map_err(register_error)?;
+    registry
+      .register(Box::new(db_connections.clone()))
+      .map_err(register_error)?;
+    registry
+      .register(Box::new(db_idle_connections.clone()))
+      .map_err(register_error)?;
+    registry
+      .register(Box::new(db_replica_fallbacks.clone()))
+      .map_err(register_error)?;
+    registry
+      .register(Box::new(logins.clone()))
+      .map_err(register_error)?;
+
+    Ok(Self {
+      registry,
+      http_requests,
+      http_request_duration,
+      db_acquire_duration,
+      db_connections,
+      db_idle_connections,
+      db_replica_fallbacks,
+      logins,
+    })
+  }
+
+  /// Histogram of how long it takes to obtain a
+  /// connection from the database pool named `pool`.
+  #[must_use]
+  pub fn db_acquire_duration(&self, pool: &str) -> Histogram {
+    self.db_acquire_duration.with_label_values(&[pool])
+  }
+
+  /// Records a handled HTTP request. `route` must be the matched route
+  /// pattern (like `/users/@{name}`) rather than the requested path
+  /// to keep the number of label values bounded.
+  pub fn record_http_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+    let status = status.to_string();
+    let labels = [method, route, status.as_str()];
+    self.http_requests.with_label_values(&labels).inc();
+    self
+      .http_request_duration
+      .with_label_values(&labels)
+      .observe(elapsed.as_secs_f64());
+  }
+
+  pub fn record_replica_fallback(&self) {
+    self.db_replica_fallbacks.inc();
+  }
+
+  pub fn record_login(&self, outcome: LoginOutcome) {
+    self.logins.with_label_values(&[outcome.as_str()]).inc();
+  }
+
+  /// Updates the connection gauges of the database pool named `pool`.
+  pub fn update_pool(&self, pool: &str, db: &database::Pool) {
+    self
+      .db_connections
+      .with_label_values(&[pool])
+      .set(i64::from(db.connections()));
+    self
+      .db_idle_connections
+      .with_label_values(&[pool])
+      .set(i64::try_from(db.idle_connections()).unwrap_or(i64::MAX));
+  }
+
+  /// Encodes every metric in Prometheus' text format.
+  pub fn encode(&self) -> Result<String, EncodeError> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+      .encode(&self.registry.gather(), &mut buffer)
+      .map_err(|e| Report::new(e).change_context(EncodeError))?;
+
+    String::from_utf8(buffer).map_err(|e| Report::new(e).change_context(EncodeError))
+  }
+}
+
+fn register_error(error: prometheus::Error) -> Report<RegisterError> {
+  Report::new(error).change_context(RegisterError)
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode() {
+    let metrics = Metrics::new().unwrap();
+    metrics.record_http_request("GET", "/users/@{name}", 200, Duration::from_millis(5));
+    metrics.record_login(LoginOutcome::Failure);
+    metrics.record_replica_fallback();
+
+    let output = metrics.encode().unwrap();
+    assert!(output
+      .contains(r#"whim_http_requests_total{method="GET",route="/users/@{name}",status="200"} 1"#));
+    assert!(output.contains("whim_http_request_duration_seconds_bucket"));
+    assert!(output.contains(r#"whim_logins_total{outcome="failure"} 1"#));
+    assert!(output.contains("whim_db_replica_fallbacks_total 1"));
+  }
+}
@@ -0,0 +1,164 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{
+      marker::{ApiTokenMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
+};
+
+/// A personal access token allowing bots and integrations
+/// to act on behalf of a user with limited scopes.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct ApiToken {
+  pub id: Id<ApiTokenMarker>,
+  pub user_id: Id<UserMarker>,
+  pub name: String,
+  pub prefix: String,
+  pub token_hash: String,
+  pub scopes: Vec<String>,
+  pub created_at: Timestamp,
+  pub expires_at: Option<Timestamp>,
+  pub last_used_at: Option<Timestamp>,
+}
+
+impl ApiToken {
+  /// Maximum number of API tokens a user can have.
+  pub const MAX_PER_USER: i64 = 50;
+}
+
+impl ApiToken {
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    name: &str,
+    prefix: &str,
+    token_hash: &str,
+    scopes: &[String],
+    expires_at: Option<Timestamp>,
+  ) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "api_tokens" (user_id, name, prefix, token_hash, scopes, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(prefix)
+    .bind(token_hash)
+    .bind(scopes)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Finds an unexpired API token by its hash.
+  #[tracing::instrument(skip_all)]
+  pub async fn by_token_hash(conn: &mut Connection, token_hash: &str) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "api_tokens"
+         WHERE token_hash = $1
+           AND (expires_at IS NULL OR expires_at > now())"#,
+    )
+    .bind(token_hash)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn by_id(
+    conn: &mut Connection,
+    id: Id<ApiTokenMarker>,
+    user_id: Id<UserMarker>,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(r#"SELECT * FROM "api_tokens" WHERE id = $1 AND user_id = $2"#)
+      .bind(id)
+      .bind(user_id)
+      .fetch_optional(conn)
+      .await
+      .into_db_error()
+  }
+
+  /// Lists all of the API tokens of a user, newest first.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn list(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Vec<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "api_tokens" WHERE user_id = $1 ORDER BY created_at DESC, id DESC"#,
+    )
+    .bind(user_id)
+    .fetch_all(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn count(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<i64> {
+    sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*) FROM "api_tokens" WHERE user_id = $1"#)
+      .bind(user_id)
+      .fetch_one(conn)
+      .await
+      .into_db_error()
+  }
+
+  /// Updates the name and/or the scopes of an API token. Fields
+  /// set to `None` are left unchanged.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn update(
+    conn: &mut Connection,
+    id: Id<ApiTokenMarker>,
+    user_id: Id<UserMarker>,
+    name: Option<&str>,
+    scopes: Option<&[String]>,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"UPDATE "api_tokens"
+         SET name = COALESCE($3, name), scopes = COALESCE($4, scopes)
+         WHERE id = $1 AND user_id = $2
+         RETURNING *"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(name)
+    .bind(scopes)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Records that the API token has been used just now.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn touch(conn: &mut Connection, id: Id<ApiTokenMarker>) -> Result<()> {
+    sqlx::query(r#"UPDATE "api_tokens" SET last_used_at = now() WHERE id = $1"#)
+      .bind(id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(())
+  }
+
+  /// Deletes an API token of a user. It returns `false`
+  /// if the API token does not exist.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn delete(
+    conn: &mut Connection,
+    id: Id<ApiTokenMarker>,
+    user_id: Id<UserMarker>,
+  ) -> Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM "api_tokens" WHERE id = $1 AND user_id = $2"#)
+      .bind(id)
+      .bind(user_id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
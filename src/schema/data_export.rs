@@ -0,0 +1,209 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{
+      marker::{DataExportMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
+};
+
+/// An archive of a user's personal data requested by themselves.
+///
+/// The archive is built in the background and can only be
+/// downloaded once before it expires.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct DataExport {
+  pub id: Id<DataExportMarker>,
+  /// It becomes `None` if the user has been purged.
+  pub user_id: Option<Id<UserMarker>>,
+  pub created_at: Timestamp,
+  pub storage_key: Option<String>,
+  pub completed_at: Option<Timestamp>,
+  pub failed_at: Option<Timestamp>,
+  pub expires_at: Option<Timestamp>,
+  pub downloaded_at: Option<Timestamp>,
+}
+
+/// The state of a [`DataExport`] at some point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataExportStatus {
+  Pending,
+  Ready,
+  Failed,
+  Downloaded,
+  Expired,
+}
+
+impl DataExport {
+  #[must_use]
+  pub fn status(&self, now: Timestamp) -> DataExportStatus {
+    if self.failed_at.is_some() {
+      DataExportStatus::Failed
+    } else if self.downloaded_at.is_some() {
+      DataExportStatus::Downloaded
+    } else if self.completed_at.is_none() {
+      DataExportStatus::Pending
+    } else if self.expires_at.map_or(true, |v| v <= now) {
+      DataExportStatus::Expired
+    } else {
+      DataExportStatus::Ready
+    }
+  }
+}
+
+impl DataExport {
+  /// Unique index allowing only one pending export per user.
+  pub const PENDING_UNIQUE_INDEX: &'static str = "data_exports_pending_user_id_key";
+
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn create(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "data_exports" (user_id) VALUES ($1)
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn by_id(
+    conn: &mut Connection,
+    id: Id<DataExportMarker>,
+    user_id: Id<UserMarker>,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(r#"SELECT * FROM "data_exports" WHERE id = $1 AND user_id = $2"#)
+      .bind(id)
+      .bind(user_id)
+      .fetch_optional(conn)
+      .await
+      .into_db_error()
+  }
+
+  /// Gets the export of a user which is either being built or
+  /// ready to be downloaded.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn active(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "data_exports"
+         WHERE user_id = $1 AND failed_at IS NULL AND downloaded_at IS NULL
+           AND (completed_at IS NULL OR expires_at > now())
+         ORDER BY created_at DESC
+         LIMIT 1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn complete(
+    conn: &mut Connection,
+    id: Id<DataExportMarker>,
+    storage_key: &str,
+    expires_at: Timestamp,
+  ) -> Result<()> {
+    sqlx::query(
+      r#"UPDATE "data_exports"
+         SET storage_key = $2, expires_at = $3,
+             completed_at = now()
+         WHERE id = $1"#,
+    )
+    .bind(id)
+    .bind(storage_key)
+    .bind(expires_at)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(())
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn fail(conn: &mut Connection, id: Id<DataExportMarker>) -> Result<()> {
+    sqlx::query(
+      r#"UPDATE "data_exports" SET failed_at = now()
+         WHERE id = $1"#,
+    )
+    .bind(id)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(())
+  }
+
+  /// Gets an export if it is ready to be downloaded.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn downloadable(
+    conn: &mut Connection,
+    id: Id<DataExportMarker>,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "data_exports"
+         WHERE id = $1 AND user_id IS NOT NULL
+           AND completed_at IS NOT NULL AND downloaded_at IS NULL
+           AND expires_at > now()"#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Marks a ready export as downloaded. It returns `None` if the
+  /// export has been downloaded already, expired or not ready yet.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn claim_download(
+    conn: &mut Connection,
+    id: Id<DataExportMarker>,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"UPDATE "data_exports" SET downloaded_at = now()
+         WHERE id = $1 AND user_id IS NOT NULL
+           AND completed_at IS NOT NULL AND downloaded_at IS NULL
+           AND expires_at > now()
+         RETURNING *"#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Lists exports whose archives are no longer needed: downloaded,
+  /// expired, failed, left pending since `stale_before` or belonging
+  /// to purged users.
+  #[tracing::instrument(skip_all)]
+  pub async fn list_stale(conn: &mut Connection, stale_before: Timestamp) -> Result<Vec<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "data_exports"
+         WHERE user_id IS NULL
+            OR downloaded_at IS NOT NULL
+            OR failed_at IS NOT NULL
+            OR expires_at <= now()
+            OR (completed_at IS NULL AND created_at <= $1)"#,
+    )
+    .bind(stale_before)
+    .fetch_all(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn delete(conn: &mut Connection, id: Id<DataExportMarker>) -> Result<()> {
+    sqlx::query(r#"DELETE FROM "data_exports" WHERE id = $1"#)
+      .bind(id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,76 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A single-use token sent to a user's email address
+/// to prove that they own the email address.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct EmailVerification {
+  pub id: i64,
+  pub user_id: Id<UserMarker>,
+  pub email: String,
+  pub token_hash: String,
+  pub created_at: Timestamp,
+  pub expires_at: Timestamp,
+  pub used_at: Option<Timestamp>,
+}
+
+impl EmailVerification {
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    email: &str,
+    token_hash: &str,
+    expires_at: Timestamp,
+  ) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "email_verifications" (user_id, email, token_hash, expires_at)
+         VALUES ($1, $2, $3, $4)
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .bind(email)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Marks an unused and unexpired token as used. It returns
+  /// `None` if the token does not exist, has expired or has
+  /// already been used.
+  #[tracing::instrument(skip_all)]
+  pub async fn consume(conn: &mut Connection, token_hash: &str) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"UPDATE "email_verifications" SET used_at = now()
+         WHERE token_hash = $1 AND used_at IS NULL
+           AND expires_at > now()
+         RETURNING *"#,
+    )
+    .bind(token_hash)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Deletes all of the unused tokens of a user so only
+  /// the most recently sent token can be used.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn delete_unused(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<()> {
+    sqlx::query(r#"DELETE FROM "email_verifications" WHERE user_id = $1 AND used_at IS NULL"#)
+      .bind(user_id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(())
+  }
+}
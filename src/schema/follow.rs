@@ -0,0 +1,139 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  schema::User,
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A user following another user.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct Follow {
+  pub follower_id: Id<UserMarker>,
+  pub followee_id: Id<UserMarker>,
+  pub created_at: Timestamp,
+}
+
+/// How many users are following a user and how many
+/// users they follow. Users pending deletion are not counted.
+#[derive(Debug, Default, Clone, Copy, FromRow, PartialEq, Eq)]
+pub struct FollowCounts {
+  pub followers: i64,
+  pub following: i64,
+}
+
+impl Follow {
+  /// Follows `followee_id` as `follower_id`. It returns `false` if
+  /// they're already following them, so concurrent requests never
+  /// create duplicate rows.
+  ///
+  /// Following oneself is rejected by the database.
+  #[tracing::instrument(skip_all, fields(follower_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    follower_id: Id<UserMarker>,
+    followee_id: Id<UserMarker>,
+  ) -> Result<bool> {
+    let result = sqlx::query(
+      r#"INSERT INTO "follows" (follower_id, followee_id)
+         VALUES ($1, $2)
+         ON CONFLICT DO NOTHING"#,
+    )
+    .bind(follower_id)
+    .bind(followee_id)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Unfollows `followee_id`. It returns `false` if
+  /// `follower_id` is not following them.
+  #[tracing::instrument(skip_all, fields(follower_id = "<hidden>"))]
+  pub async fn delete(
+    conn: &mut Connection,
+    follower_id: Id<UserMarker>,
+    followee_id: Id<UserMarker>,
+  ) -> Result<bool> {
+    let result =
+      sqlx::query(r#"DELETE FROM "follows" WHERE follower_id = $1 AND followee_id = $2"#)
+        .bind(follower_id)
+        .bind(followee_id)
+        .execute(conn)
+        .await
+        .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn counts(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<FollowCounts> {
+    sqlx::query_as::<_, FollowCounts>(
+      r#"SELECT
+           (SELECT count(*) FROM "follows"
+            JOIN "users" ON "users".id = "follows".follower_id
+            WHERE followee_id = $1 AND "users".deletion_requested_at IS NULL) AS followers,
+           (SELECT count(*) FROM "follows"
+            JOIN "users" ON "users".id = "follows".followee_id
+            WHERE follower_id = $1 AND "users".deletion_requested_at IS NULL) AS following"#,
+    )
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Lists users following `user_id` ordered by their IDs,
+  /// starting after the user with the ID of `after`.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn list_followers(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    after: Option<Id<UserMarker>>,
+    limit: i64,
+  ) -> Result<Vec<User>> {
+    sqlx::query_as::<_, User>(
+      r#"SELECT "users".* FROM "follows"
+         JOIN "users" ON "users".id = "follows".follower_id
+         WHERE followee_id = $1 AND "users".deletion_requested_at IS NULL
+           AND ($2::bigint IS NULL OR "users".id > $2)
+         ORDER BY "users".id
+         LIMIT $3"#,
+    )
+    .bind(user_id)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Lists users followed by `user_id` ordered by their IDs,
+  /// starting after the user with the ID of `after`.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn list_following(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    after: Option<Id<UserMarker>>,
+    limit: i64,
+  ) -> Result<Vec<User>> {
+    sqlx::query_as::<_, User>(
+      r#"SELECT "users".* FROM "follows"
+         JOIN "users" ON "users".id = "follows".followee_id
+         WHERE follower_id = $1 AND "users".deletion_requested_at IS NULL
+           AND ($2::bigint IS NULL OR "users".id > $2)
+         ORDER BY "users".id
+         LIMIT $3"#,
+    )
+    .bind(user_id)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .into_db_error()
+  }
+}
@@ -0,0 +1,93 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::Timestamp,
+};
+
+/// Failed login attempts of either an account or an IP address.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct LoginAttempt {
+  pub key: String,
+  pub failures: i32,
+  pub last_failed_at: Timestamp,
+}
+
+impl LoginAttempt {
+  #[tracing::instrument(skip_all)]
+  pub async fn by_key(conn: &mut Connection, key: &str) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(r#"SELECT * FROM "login_attempts" WHERE key = $1"#)
+      .bind(key)
+      .fetch_optional(conn)
+      .await
+      .into_db_error()
+  }
+
+  /// Records a failed attempt at `now`. Previous failed attempts
+  /// are forgotten if the last one happened before `forget_before`.
+  #[tracing::instrument(skip_all)]
+  pub async fn record_failure(
+    conn: &mut Connection,
+    key: &str,
+    now: Timestamp,
+    forget_before: Timestamp,
+  ) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "login_attempts" (key, failures, last_failed_at)
+         VALUES ($1, 1, $2)
+         ON CONFLICT (key) DO UPDATE
+           SET failures = CASE
+                 WHEN "login_attempts".last_failed_at < $3 THEN 1
+                 ELSE "login_attempts".failures + 1
+               END,
+               last_failed_at = EXCLUDED.last_failed_at
+         RETURNING *"#,
+    )
+    .bind(key)
+    .bind(now)
+    .bind(forget_before)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Takes back a failed attempt recorded at `failed_at`. The time of
+  /// the last failed attempt goes back to `previous` unless another
+  /// attempt has failed since then.
+  #[tracing::instrument(skip_all)]
+  pub async fn release(
+    conn: &mut Connection,
+    key: &str,
+    failed_at: Timestamp,
+    previous: Option<Timestamp>,
+  ) -> Result<()> {
+    sqlx::query(
+      r#"UPDATE "login_attempts"
+         SET failures = failures - 1,
+             last_failed_at = CASE
+               WHEN last_failed_at = $2 THEN COALESCE($3, last_failed_at)
+               ELSE last_failed_at
+             END
+         WHERE key = $1 AND failures > 0"#,
+    )
+    .bind(key)
+    .bind(failed_at)
+    .bind(previous)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(())
+  }
+
+  #[tracing::instrument(skip_all)]
+  pub async fn delete(conn: &mut Connection, key: &str) -> Result<()> {
+    sqlx::query(r#"DELETE FROM "login_attempts" WHERE key = $1"#)
+      .bind(key)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(())
+  }
+}
@@ -1,2 +1,25 @@
+mod api_token;
+mod data_export;
+mod email_verification;
+mod follow;
+mod login_attempt;
+mod password_reset;
+mod post;
+mod recovery_code;
+mod session;
+mod totp_secret;
 mod user;
-pub use user::User;
+mod username_history;
+
+pub use api_token::ApiToken;
+pub use data_export::{DataExport, DataExportStatus};
+pub use email_verification::EmailVerification;
+pub use follow::{Follow, FollowCounts};
+pub use login_attempt::LoginAttempt;
+pub use password_reset::PasswordReset;
+pub use post::Post;
+pub use recovery_code::RecoveryCode;
+pub use session::Session;
+pub use totp_secret::TotpSecret;
+pub use user::{ProfileUpdate, User};
+pub use username_history::UsernameHistory;
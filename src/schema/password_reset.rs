@@ -0,0 +1,73 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A single-use token sent to a user's email address
+/// allowing them to set a new password.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct PasswordReset {
+  pub id: i64,
+  pub user_id: Id<UserMarker>,
+  pub token_hash: String,
+  pub created_at: Timestamp,
+  pub expires_at: Timestamp,
+  pub used_at: Option<Timestamp>,
+}
+
+impl PasswordReset {
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    token_hash: &str,
+    expires_at: Timestamp,
+  ) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "password_resets" (user_id, token_hash, expires_at)
+         VALUES ($1, $2, $3)
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Marks an unused and unexpired token as used. It returns
+  /// `None` if the token does not exist, has expired or has
+  /// already been used.
+  #[tracing::instrument(skip_all)]
+  pub async fn consume(conn: &mut Connection, token_hash: &str) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"UPDATE "password_resets" SET used_at = now()
+         WHERE token_hash = $1 AND used_at IS NULL
+           AND expires_at > now()
+         RETURNING *"#,
+    )
+    .bind(token_hash)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Deletes all of the unused tokens of a user so only
+  /// the most recently sent token can be used.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn delete_unused(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<()> {
+    sqlx::query(r#"DELETE FROM "password_resets" WHERE user_id = $1 AND used_at IS NULL"#)
+      .bind(user_id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,89 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{
+      marker::{PostMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
+};
+
+/// A text post written by a user.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct Post {
+  pub id: Id<PostMarker>,
+  pub author_id: Id<UserMarker>,
+  pub created_at: Timestamp,
+  pub content: String,
+}
+
+impl Post {
+  #[tracing::instrument(skip_all, fields(author_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    id: Id<PostMarker>,
+    author_id: Id<UserMarker>,
+    content: &str,
+  ) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "posts" (id, author_id, content)
+         VALUES ($1, $2, $3)
+         RETURNING *"#,
+    )
+    .bind(id)
+    .bind(author_id)
+    .bind(content)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Finds a post by its ID. Posts of users pending
+  /// deletion are hidden along with their accounts.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn by_id(conn: &mut Connection, id: Id<PostMarker>) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT "posts".* FROM "posts"
+         JOIN "users" ON "users".id = "posts".author_id
+         WHERE "posts".id = $1 AND "users".deletion_requested_at IS NULL"#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Lists every post written by a user, oldest first.
+  #[tracing::instrument(skip_all, fields(author_id = "<hidden>"))]
+  pub async fn list_by_author(
+    conn: &mut Connection,
+    author_id: Id<UserMarker>,
+  ) -> Result<Vec<Self>> {
+    sqlx::query_as::<_, Self>(r#"SELECT * FROM "posts" WHERE author_id = $1 ORDER BY id"#)
+      .bind(author_id)
+      .fetch_all(conn)
+      .await
+      .into_db_error()
+  }
+
+  /// Deletes a post written by `author_id`. It returns `false`
+  /// if the post does not exist or is written by someone else.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn delete(
+    conn: &mut Connection,
+    id: Id<PostMarker>,
+    author_id: Id<UserMarker>,
+  ) -> Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM "posts" WHERE id = $1 AND author_id = $2"#)
+      .bind(id)
+      .bind(author_id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
@@ -0,0 +1,75 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A hashed one-time code allowing a user to pass two-factor
+/// authentication without their authenticator app.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct RecoveryCode {
+  pub id: i64,
+  pub user_id: Id<UserMarker>,
+  pub code_hash: String,
+  pub created_at: Timestamp,
+  pub used_at: Option<Timestamp>,
+}
+
+impl RecoveryCode {
+  /// Replaces all of the recovery codes of a user.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn replace_all(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    code_hashes: &[String],
+  ) -> Result<()> {
+    Self::delete_all(&mut *conn, user_id).await?;
+    sqlx::query(
+      r#"INSERT INTO "recovery_codes" (user_id, code_hash)
+         SELECT $1, * FROM UNNEST($2::text[])"#,
+    )
+    .bind(user_id)
+    .bind(code_hashes)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(())
+  }
+
+  /// Marks an unused recovery code of a user as used. It returns
+  /// `false` if the code does not exist or has already been used.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn consume(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    code_hash: &str,
+  ) -> Result<bool> {
+    let result = sqlx::query(
+      r#"UPDATE "recovery_codes" SET used_at = now()
+         WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL"#,
+    )
+    .bind(user_id)
+    .bind(code_hash)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn delete_all(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<()> {
+    sqlx::query(r#"DELETE FROM "recovery_codes" WHERE user_id = $1"#)
+      .bind(user_id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(())
+  }
+}
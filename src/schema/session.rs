@@ -0,0 +1,212 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{
+      marker::{SessionMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
+};
+
+/// A logged in device of a user.
+///
+/// Each session holds a hashed refresh token which can be exchanged
+/// for a new access token (and a new refresh token) until the session
+/// expires or is revoked.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct Session {
+  pub id: Id<SessionMarker>,
+  pub user_id: Id<UserMarker>,
+  pub created_at: Timestamp,
+  pub last_used_at: Timestamp,
+  pub expires_at: Timestamp,
+  pub revoked_at: Option<Timestamp>,
+  pub refresh_token_hash: String,
+  pub previous_refresh_token_hash: Option<String>,
+  pub user_agent: Option<String>,
+  pub ip_address: Option<String>,
+}
+
+impl Session {
+  pub const MAX_USER_AGENT_LEN: usize = 512;
+
+  /// Whether this session is neither revoked nor expired.
+  pub fn is_active(&self, now: Timestamp) -> bool {
+    self.revoked_at.is_none() && self.expires_at > now
+  }
+}
+
+impl Session {
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    refresh_token_hash: &str,
+    expires_at: Timestamp,
+    user_agent: Option<&str>,
+    ip_address: Option<&str>,
+  ) -> Result<Self> {
+    let user_agent = user_agent.map(|v| truncate(v, Self::MAX_USER_AGENT_LEN));
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "sessions" (user_id, refresh_token_hash, expires_at, user_agent, ip_address)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .bind(refresh_token_hash)
+    .bind(expires_at)
+    .bind(user_agent)
+    .bind(ip_address)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Finds a session by either its current or previous refresh token hash.
+  ///
+  /// The caller is responsible for checking whether the refresh
+  /// token has been reused by comparing the hash with
+  /// [`Session::previous_refresh_token_hash`].
+  #[tracing::instrument(skip_all)]
+  pub async fn by_refresh_token_hash(conn: &mut Connection, hash: &str) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "sessions"
+         WHERE refresh_token_hash = $1 OR previous_refresh_token_hash = $1"#,
+    )
+    .bind(hash)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Lists all of the active sessions of a user, recently used first.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn list_active(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Vec<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "sessions"
+         WHERE user_id = $1 AND revoked_at IS NULL
+           AND expires_at > now()
+         ORDER BY last_used_at DESC"#,
+    )
+    .bind(user_id)
+    .fetch_all(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Checks whether a session of a user is neither revoked nor expired.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn is_active_by_id(
+    conn: &mut Connection,
+    id: Id<SessionMarker>,
+    user_id: Id<UserMarker>,
+  ) -> Result<bool> {
+    sqlx::query_scalar::<_, bool>(
+      r#"SELECT EXISTS (
+           SELECT 1 FROM "sessions"
+           WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+             AND expires_at > now()
+         )"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Replaces the refresh token hash of a session and remembers
+  /// the old one to detect refresh token reuse.
+  ///
+  /// It returns `None` if the session has been rotated by another
+  /// request or revoked in the meantime.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn rotate(
+    conn: &mut Connection,
+    id: Id<SessionMarker>,
+    old_hash: &str,
+    new_hash: &str,
+    expires_at: Timestamp,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"UPDATE "sessions"
+         SET refresh_token_hash = $3,
+             previous_refresh_token_hash = refresh_token_hash,
+             expires_at = $4,
+             last_used_at = now()
+         WHERE id = $1 AND refresh_token_hash = $2 AND revoked_at IS NULL
+         RETURNING *"#,
+    )
+    .bind(id)
+    .bind(old_hash)
+    .bind(new_hash)
+    .bind(expires_at)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Revokes a session of a user. It returns `false` if the
+  /// session does not exist or has already been revoked.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn revoke(
+    conn: &mut Connection,
+    id: Id<SessionMarker>,
+    user_id: Id<UserMarker>,
+  ) -> Result<bool> {
+    let result = sqlx::query(
+      r#"UPDATE "sessions" SET revoked_at = now()
+         WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Revokes all of the sessions of a user and returns
+  /// how many sessions are revoked.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn revoke_all(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<u64> {
+    let result = sqlx::query(
+      r#"UPDATE "sessions" SET revoked_at = now()
+         WHERE user_id = $1 AND revoked_at IS NULL"#,
+    )
+    .bind(user_id)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected())
+  }
+}
+
+fn truncate(value: &str, max_len: usize) -> &str {
+  if value.len() <= max_len {
+    return value;
+  }
+  let mut end = max_len;
+  while !value.is_char_boundary(end) {
+    end -= 1;
+  }
+  &value[..end]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::truncate;
+
+  #[test]
+  fn test_truncate() {
+    assert_eq!(truncate("hello", 10), "hello");
+    assert_eq!(truncate("hello", 3), "hel");
+    // `é` takes two bytes in UTF-8
+    assert_eq!(truncate("héllo", 2), "h");
+  }
+}
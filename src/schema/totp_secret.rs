@@ -0,0 +1,132 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// An encrypted TOTP secret of a user.
+///
+/// Two-factor authentication is only enabled once the user
+/// confirms their enrollment with a valid code.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct TotpSecret {
+  pub user_id: Id<UserMarker>,
+  pub encrypted_secret: String,
+  pub created_at: Timestamp,
+  pub enabled_at: Option<Timestamp>,
+  pub last_used_step: Option<i64>,
+}
+
+impl TotpSecret {
+  /// Whether the user has confirmed their enrollment.
+  pub const fn is_enabled(&self) -> bool {
+    self.enabled_at.is_some()
+  }
+}
+
+impl TotpSecret {
+  /// Creates or replaces the pending (unconfirmed) TOTP secret of
+  /// a user. It returns `None` if the user has already enabled
+  /// two-factor authentication.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn upsert_pending(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    encrypted_secret: &str,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "totp_secrets" (user_id, encrypted_secret)
+         VALUES ($1, $2)
+         ON CONFLICT (user_id) DO UPDATE
+           SET encrypted_secret = EXCLUDED.encrypted_secret,
+               created_at = EXCLUDED.created_at,
+               last_used_step = NULL
+           WHERE "totp_secrets".enabled_at IS NULL
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .bind(encrypted_secret)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn by_user_id(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(r#"SELECT * FROM "totp_secrets" WHERE user_id = $1"#)
+      .bind(user_id)
+      .fetch_optional(conn)
+      .await
+      .into_db_error()
+  }
+
+  /// Checks whether a user has enabled two-factor authentication.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn is_enabled_for(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<bool> {
+    sqlx::query_scalar::<_, bool>(
+      r#"SELECT EXISTS (
+           SELECT 1 FROM "totp_secrets"
+           WHERE user_id = $1 AND enabled_at IS NOT NULL
+         )"#,
+    )
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Enables two-factor authentication of a user with the time step
+  /// of the code used to confirm it. It returns `false` if the user
+  /// has no pending TOTP secret.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn enable(conn: &mut Connection, user_id: Id<UserMarker>, step: i64) -> Result<bool> {
+    let result = sqlx::query(
+      r#"UPDATE "totp_secrets"
+         SET enabled_at = now(), last_used_step = $2
+         WHERE user_id = $1 AND enabled_at IS NULL"#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Records the time step of an accepted code. It returns `false`
+  /// if a code from the same or later time step has already been
+  /// used in the meantime.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn use_step(conn: &mut Connection, user_id: Id<UserMarker>, step: i64) -> Result<bool> {
+    let result = sqlx::query(
+      r#"UPDATE "totp_secrets" SET last_used_step = $2
+         WHERE user_id = $1 AND enabled_at IS NOT NULL
+           AND (last_used_step IS NULL OR last_used_step < $2)"#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Deletes the TOTP secret of a user (either pending or enabled).
+  /// It returns `false` if the user has no TOTP secret.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn delete(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<bool> {
+    let result = sqlx::query(r#"DELETE FROM "totp_secrets" WHERE user_id = $1"#)
+      .bind(user_id)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
@@ -1,57 +1,321 @@
-use chrono::NaiveDateTime;
 use sqlx::FromRow;
 
 use crate::{
+  config,
   database::{error::ErrorExt, Connection, Result},
-  types::id::{marker::UserMarker, Id},
+  types::{
+    id::{marker::UserMarker, Id},
+    username, Timestamp,
+  },
 };
 
 #[derive(Debug, FromRow, PartialEq, Eq)]
 pub struct User {
   pub id: Id<UserMarker>,
-  pub created_at: NaiveDateTime,
+  pub created_at: Timestamp,
   pub name: String,
   pub display_name: Option<String>,
   pub email: Option<String>,
   pub password_hash: String,
-  pub updated_at: Option<NaiveDateTime>,
+  pub updated_at: Option<Timestamp>,
+  pub email_verified_at: Option<Timestamp>,
+  pub bio: Option<String>,
+  pub location: Option<String>,
+  pub website: Option<String>,
+  /// When the user requested to delete their account. The account is
+  /// purged after the configured grace period unless they cancel it.
+  pub deletion_requested_at: Option<Timestamp>,
+}
+
+/// Changes to the user's public profile. Fields set to `None`
+/// are left unchanged while `Some(None)` clears them.
+#[derive(Debug, Default)]
+pub struct ProfileUpdate<'a> {
+  pub display_name: Option<Option<&'a str>>,
+  pub bio: Option<Option<&'a str>>,
+  pub location: Option<Option<&'a str>>,
+  pub website: Option<Option<&'a str>>,
+}
+
+impl User {
+  /// Unique index of case-insensitive names.
+  pub const NAME_UNIQUE_INDEX: &'static str = "users_name_lower_key";
+  /// Unique index of case-insensitive email addresses.
+  pub const EMAIL_UNIQUE_INDEX: &'static str = "users_email_lower_key";
+
+  /// Whether the user has an email address which is not verified yet.
+  pub const fn needs_email_verification(&self) -> bool {
+    self.email.is_some() && self.email_verified_at.is_none()
+  }
+
+  /// Whether the user is allowed to post by the instance's policies.
+  pub const fn can_post(&self, instance: &config::Instance) -> bool {
+    !self.needs_email_verification() || instance.allow_unverified_posting()
+  }
 }
 
 impl User {
   #[tracing::instrument(skip(id), fields(id = "<hidden>"))]
   pub async fn by_id(conn: &mut Connection, id: Id<UserMarker>) -> Result<Option<Self>> {
-    sqlx::query_as::<_, Self>(r#"SELECT * FROM "users" WHERE id = $1"#)
-      .bind(id)
-      .fetch_optional(conn)
-      .await
-      .into_db_error()
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "users" WHERE id = $1 AND deletion_requested_at IS NULL"#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
   }
 
   // Its function name is ridiculously long tbh
   #[tracing::instrument(skip(condition), fields(condition = "<hidden>"))]
   pub async fn by_name_or_email(conn: &mut Connection, condition: &str) -> Result<Option<Self>> {
-    sqlx::query_as::<_, Self>(r#"SELECT * FROM "users" WHERE name = $1 OR email = $1"#)
-      .bind(condition)
-      .fetch_optional(conn)
-      .await
-      .into_db_error()
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "users"
+         WHERE (lower(name) = lower($1) OR lower(email) = lower($1))
+           AND deletion_requested_at IS NULL"#,
+    )
+    .bind(condition)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Like [`User::by_name_or_email`] but includes users pending deletion,
+  /// so they can be told about it when logging in and cancel it.
+  #[tracing::instrument(skip(condition), fields(condition = "<hidden>"))]
+  pub async fn by_name_or_email_including_pending(
+    conn: &mut Connection,
+    condition: &str,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "users"
+         WHERE lower(name) = lower($1) OR lower(email) = lower($1)"#,
+    )
+    .bind(condition)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
   }
 
   #[tracing::instrument(skip(condition), fields(condition = "<hidden>"))]
   pub async fn by_email(conn: &mut Connection, condition: &str) -> Result<Option<Self>> {
-    sqlx::query_as::<_, Self>(r#"SELECT * FROM "users" WHERE email = $1"#)
-      .bind(condition)
-      .fetch_optional(conn)
-      .await
-      .into_db_error()
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "users"
+         WHERE lower(email) = lower($1) AND deletion_requested_at IS NULL"#,
+    )
+    .bind(condition)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
   }
 
   #[tracing::instrument(skip(condition), fields(condition = "<hidden>"))]
   pub async fn by_name(conn: &mut Connection, condition: &str) -> Result<Option<Self>> {
-    sqlx::query_as::<_, Self>(r#"SELECT * FROM "users" WHERE name = $1"#)
-      .bind(condition)
-      .fetch_optional(conn)
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "users"
+         WHERE lower(name) = lower($1) AND deletion_requested_at IS NULL"#,
+    )
+    .bind(condition)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn update_password_hash(
+    conn: &mut Connection,
+    id: Id<UserMarker>,
+    password_hash: &str,
+  ) -> Result<()> {
+    sqlx::query(r#"UPDATE "users" SET password_hash = $1 WHERE id = $2"#)
+      .bind(password_hash)
+      .bind(id)
+      .execute(conn)
       .await
-      .into_db_error()
+      .into_db_error()?;
+
+    Ok(())
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn update_profile(
+    conn: &mut Connection,
+    id: Id<UserMarker>,
+    update: &ProfileUpdate<'_>,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"UPDATE "users"
+         SET display_name = CASE WHEN $2 THEN $3 ELSE display_name END,
+             bio = CASE WHEN $4 THEN $5 ELSE bio END,
+             location = CASE WHEN $6 THEN $7 ELSE location END,
+             website = CASE WHEN $8 THEN $9 ELSE website END,
+             updated_at = now()
+         WHERE id = $1
+         RETURNING *"#,
+    )
+    .bind(id)
+    .bind(update.display_name.is_some())
+    .bind(update.display_name.flatten())
+    .bind(update.bio.is_some())
+    .bind(update.bio.flatten())
+    .bind(update.location.is_some())
+    .bind(update.location.flatten())
+    .bind(update.website.is_some())
+    .bind(update.website.flatten())
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Checks whether another user has a name which is either equal
+  /// (ignoring case) or confusable with `name`.
+  ///
+  /// `except` is excluded so users can change the case of their name.
+  #[tracing::instrument(skip_all, fields(name = "<hidden>"))]
+  pub async fn name_conflicts(
+    conn: &mut Connection,
+    name: &str,
+    except: Option<Id<UserMarker>>,
+  ) -> Result<bool> {
+    sqlx::query_scalar::<_, bool>(
+      r#"SELECT EXISTS (
+           SELECT 1 FROM "users"
+           WHERE (lower(name) = lower($1) OR name_skeleton = $2)
+             AND ($3::bigint IS NULL OR id <> $3)
+         )"#,
+    )
+    .bind(name)
+    .bind(username::skeleton(name))
+    .bind(except)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn update_name(conn: &mut Connection, id: Id<UserMarker>, name: &str) -> Result<()> {
+    sqlx::query(
+      r#"UPDATE "users"
+         SET name = $1, name_skeleton = $2, updated_at = now()
+         WHERE id = $3"#,
+    )
+    .bind(name)
+    .bind(username::skeleton(name))
+    .bind(id)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(())
+  }
+
+  /// Marks the user as pending deletion and returns when it is requested.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn request_deletion(
+    conn: &mut Connection,
+    id: Id<UserMarker>,
+  ) -> Result<Option<Timestamp>> {
+    sqlx::query_scalar::<_, Timestamp>(
+      r#"UPDATE "users" SET deletion_requested_at = now()
+         WHERE id = $1 AND deletion_requested_at IS NULL
+         RETURNING deletion_requested_at"#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Cancels the deletion of a user if it is requested after `since`,
+  /// otherwise the user is about to be purged. It returns `false` if
+  /// the user is not pending deletion or too late to cancel.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn cancel_deletion(
+    conn: &mut Connection,
+    id: Id<UserMarker>,
+    since: Timestamp,
+  ) -> Result<bool> {
+    let result = sqlx::query(
+      r#"UPDATE "users" SET deletion_requested_at = NULL
+         WHERE id = $1 AND deletion_requested_at > $2"#,
+    )
+    .bind(id)
+    .bind(since)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Deletes users (and their dependent data) who requested
+  /// to delete their account before `before`.
+  #[tracing::instrument(skip_all)]
+  pub async fn purge_deleted(conn: &mut Connection, before: Timestamp) -> Result<u64> {
+    let result = sqlx::query(r#"DELETE FROM "users" WHERE deletion_requested_at <= $1"#)
+      .bind(before)
+      .execute(conn)
+      .await
+      .into_db_error()?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Marks the user's email address as verified as long as
+  /// the user's email address is still the same as `email`.
+  #[tracing::instrument(skip_all, fields(id = "<hidden>"))]
+  pub async fn mark_email_verified(
+    conn: &mut Connection,
+    id: Id<UserMarker>,
+    email: &str,
+  ) -> Result<bool> {
+    let result = sqlx::query(
+      r#"UPDATE "users" SET email_verified_at = now()
+         WHERE id = $1 AND email = $2 AND email_verified_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(email)
+    .execute(conn)
+    .await
+    .into_db_error()?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_can_post() {
+    let mut user = User {
+      id: Id::new(1),
+      created_at: Timestamp::now(),
+      name: "memo".into(),
+      display_name: None,
+      email: Some("memo@example.com".into()),
+      password_hash: String::new(),
+      updated_at: None,
+      email_verified_at: None,
+      bio: None,
+      location: None,
+      website: None,
+      deletion_requested_at: None,
+    };
+
+    let mut instance = config::Instance::default();
+    assert!(!user.can_post(&instance));
+
+    instance.allow_unverified_posting = true;
+    assert!(user.can_post(&instance));
+
+    instance.allow_unverified_posting = false;
+    user.email_verified_at = Some(Timestamp::now());
+    assert!(user.can_post(&instance));
+
+    user.email = None;
+    user.email_verified_at = None;
+    assert!(user.can_post(&instance));
   }
 }
@@ -0,0 +1,94 @@
+use sqlx::FromRow;
+
+use crate::{
+  database::{error::ErrorExt, Connection, Result},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A record of a user changing their username.
+///
+/// The old name stays reserved for its previous owner during the
+/// configured grace period so links to their old `@handle` keep
+/// working and nobody can impersonate them right away.
+#[derive(Debug, FromRow, PartialEq, Eq)]
+pub struct UsernameHistory {
+  pub id: i64,
+  pub user_id: Id<UserMarker>,
+  pub old_name: String,
+  pub new_name: String,
+  pub changed_at: Timestamp,
+}
+
+impl UsernameHistory {
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn create(
+    conn: &mut Connection,
+    user_id: Id<UserMarker>,
+    old_name: &str,
+    new_name: &str,
+  ) -> Result<Self> {
+    sqlx::query_as::<_, Self>(
+      r#"INSERT INTO "username_history" (user_id, old_name, new_name)
+         VALUES ($1, $2, $3)
+         RETURNING *"#,
+    )
+    .bind(user_id)
+    .bind(old_name)
+    .bind(new_name)
+    .fetch_one(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Gets the most recent username change of a user.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn latest(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "username_history"
+         WHERE user_id = $1
+         ORDER BY changed_at DESC, id DESC
+         LIMIT 1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Lists all username changes of a user, recent first.
+  #[tracing::instrument(skip_all, fields(user_id = "<hidden>"))]
+  pub async fn list(conn: &mut Connection, user_id: Id<UserMarker>) -> Result<Vec<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "username_history"
+         WHERE user_id = $1
+         ORDER BY changed_at DESC, id DESC"#,
+    )
+    .bind(user_id)
+    .fetch_all(conn)
+    .await
+    .into_db_error()
+  }
+
+  /// Finds the user who released a name after `since`.
+  #[tracing::instrument(skip_all, fields(name = "<hidden>"))]
+  pub async fn released_since(
+    conn: &mut Connection,
+    name: &str,
+    since: Timestamp,
+  ) -> Result<Option<Self>> {
+    sqlx::query_as::<_, Self>(
+      r#"SELECT * FROM "username_history"
+         WHERE lower(old_name) = lower($1) AND changed_at > $2
+         ORDER BY changed_at DESC, id DESC
+         LIMIT 1"#,
+    )
+    .bind(name)
+    .bind(since)
+    .fetch_optional(conn)
+    .await
+    .into_db_error()
+  }
+}
@@ -0,0 +1,185 @@
+//! Coordinated shutdown of the web server and background jobs.
+use actix_web::dev::{Extensions, Server};
+use std::{any::Any, future::Future, time::Duration};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+/// Waits until the process is told to shut down
+/// either with `SIGTERM` or `SIGINT` (Ctrl+C).
+pub async fn signal() {
+  #[cfg(unix)]
+  {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+      Ok(mut sigterm) => {
+        tokio::select! {
+          _ = sigterm.recv() => tracing::info!("received SIGTERM"),
+          _ = tokio::signal::ctrl_c() => tracing::info!("received SIGINT"),
+        }
+        return;
+      }
+      Err(error) => tracing::warn!(%error, "failed to listen for SIGTERM"),
+    }
+  }
+
+  if let Err(error) = tokio::signal::ctrl_c().await {
+    tracing::warn!(%error, "failed to listen for SIGINT");
+    std::future::pending::<()>().await;
+  }
+  tracing::info!("received SIGINT");
+}
+
+/// Keeps track of the web server's open connections.
+///
+/// actix-server may drop in-flight connections if a worker notices
+/// that the server has stopped accepting connections before it gets
+/// told to shut down gracefully, so [`serve`] waits for them to be
+/// closed by itself before stopping the server.
+#[derive(Debug, Clone, Default)]
+pub struct Connections {
+  tracker: TaskTracker,
+}
+
+impl Connections {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Callback for [`HttpServer::on_connect`] which tracks the
+  /// connection until it is closed.
+  ///
+  /// [`HttpServer::on_connect`]: actix_web::HttpServer::on_connect
+  pub fn on_connect(&self) -> impl Fn(&dyn Any, &mut Extensions) + Send + Sync + 'static {
+    let tracker = self.tracker.clone();
+    move |_, extensions| {
+      extensions.insert(tracker.token());
+    }
+  }
+
+  /// Number of connections which are still open.
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.tracker.len()
+  }
+
+  /// Whether every connection has been closed.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.tracker.is_empty()
+  }
+}
+
+/// Tracks tasks spawned while handling requests, such as sending
+/// emails or building data exports, so they can be awaited before
+/// the database pools are closed.
+///
+/// Tasks which may take a while should stop early once
+/// [`BackgroundTasks::shutdown_token`] is cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct BackgroundTasks {
+  tracker: TaskTracker,
+  shutdown: CancellationToken,
+}
+
+impl BackgroundTasks {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Token which is cancelled once the server is shutting down.
+  #[must_use]
+  pub const fn shutdown_token(&self) -> &CancellationToken {
+    &self.shutdown
+  }
+
+  pub fn spawn<F>(&self, task: F)
+  where
+    F: Future<Output = ()> + Send + 'static,
+  {
+    self.tracker.spawn(task);
+  }
+
+  /// Number of tasks which are still running.
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.tracker.len()
+  }
+
+  /// Whether every task has finished.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.tracker.is_empty()
+  }
+
+  /// Waits until every spawned task has finished.
+  pub async fn wait(&self) {
+    self.tracker.close();
+    self.tracker.wait().await;
+  }
+}
+
+/// Runs the web server until `signal` resolves.
+///
+/// Once it resolves, `token` is cancelled to notify background
+/// jobs and the server stops accepting new connections while
+/// open connections are given until `timeout` to finish their
+/// requests. Connections which are still open afterwards are
+/// closed forcefully.
+///
+/// The server must be built with [`HttpServer::disable_signals`]
+/// so it does not stop on its own before the jobs are notified and
+/// with [`Connections::on_connect`] so its connections are tracked.
+///
+/// [`HttpServer::disable_signals`]: actix_web::HttpServer::disable_signals
+pub async fn serve(
+  server: Server,
+  signal: impl Future<Output = ()>,
+  token: CancellationToken,
+  connections: Connections,
+  timeout: Duration,
+) -> std::io::Result<()> {
+  let handle = server.handle();
+  tokio::pin!(server);
+
+  tokio::select! {
+    result = &mut server => {
+      token.cancel();
+      return result;
+    }
+    () = signal => {}
+  }
+
+  tracing::info!("shutting down, waiting for in-flight requests to finish");
+  token.cancel();
+
+  // Server commands are only processed while the server is polled
+  let drained = async {
+    handle.pause().await;
+    connections.tracker.close();
+    tokio::time::timeout(timeout, connections.tracker.wait())
+      .await
+      .is_ok()
+  };
+
+  tokio::pin!(drained);
+  let graceful = tokio::select! {
+    result = &mut server => {
+      return result;
+    }
+    graceful = &mut drained => graceful,
+  };
+
+  if !graceful {
+    tracing::warn!(
+      connections = connections.len(),
+      "shutdown timeout has elapsed, closing the remaining connections"
+    );
+  }
+
+  let stopped = handle.stop(graceful);
+  let result = server.await;
+  stopped.await;
+  result
+}
@@ -0,0 +1,92 @@
+use error_stack::{Report, Result, ResultExt};
+use futures::future::BoxFuture;
+use std::{
+  io::ErrorKind,
+  path::{Path, PathBuf},
+};
+
+use super::{is_valid_key, Error, Storage};
+
+/// Writes files into a local directory.
+#[derive(Debug)]
+pub struct LocalStorage {
+  root: PathBuf,
+}
+
+impl LocalStorage {
+  #[must_use]
+  pub fn new(root: &Path) -> Self {
+    Self {
+      root: root.to_path_buf(),
+    }
+  }
+
+  fn path(&self, key: &str) -> Result<PathBuf, Error> {
+    if !is_valid_key(key) {
+      return Err(Report::new(Error::InvalidKey));
+    }
+    Ok(self.root.join(key))
+  }
+}
+
+impl Storage for LocalStorage {
+  fn put(&self, key: &str, data: Vec<u8>) -> BoxFuture<'_, Result<(), Error>> {
+    let path = self.path(key);
+    Box::pin(async move {
+      let path = path?;
+      if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+          .await
+          .change_context(Error::Io)?;
+      }
+      tokio::fs::write(&path, data)
+        .await
+        .change_context(Error::Io)
+    })
+  }
+
+  fn get(&self, key: &str) -> BoxFuture<'_, Result<Option<Vec<u8>>, Error>> {
+    let path = self.path(key);
+    Box::pin(async move {
+      match tokio::fs::read(path?).await {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Report::new(e).change_context(Error::Io)),
+      }
+    })
+  }
+
+  fn delete(&self, key: &str) -> BoxFuture<'_, Result<(), Error>> {
+    let path = self.path(key);
+    Box::pin(async move {
+      match tokio::fs::remove_file(path?).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(Report::new(e).change_context(Error::Io)),
+        _ => Ok(()),
+      }
+    })
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_local_storage() {
+    let dir = std::env::temp_dir().join(format!("whim-storage-{}", std::process::id()));
+    let storage = LocalStorage::new(&dir);
+
+    storage.put("a/b.txt", b"hello".to_vec()).await.unwrap();
+    let data = storage.get("a/b.txt").await.unwrap();
+
+    storage.delete("a/b.txt").await.unwrap();
+    storage.delete("a/b.txt").await.unwrap();
+    let deleted = storage.get("a/b.txt").await.unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(data.as_deref(), Some(&b"hello"[..]));
+    assert_eq!(deleted, None);
+    assert!(storage.put("../b.txt", Vec::new()).await.is_err());
+  }
+}
@@ -0,0 +1,75 @@
+use error_stack::{Report, Result};
+use futures::future::BoxFuture;
+use std::{collections::HashMap, sync::Mutex};
+
+use super::{is_valid_key, Error, Storage};
+
+/// Keeps files in memory. It is only useful for testing and development.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+  files: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn files(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<u8>>> {
+    // The map cannot be left in an inconsistent state
+    self
+      .files
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+  }
+}
+
+fn check_key(key: &str) -> Result<(), Error> {
+  if is_valid_key(key) {
+    Ok(())
+  } else {
+    Err(Report::new(Error::InvalidKey))
+  }
+}
+
+impl Storage for MemoryStorage {
+  fn put(&self, key: &str, data: Vec<u8>) -> BoxFuture<'_, Result<(), Error>> {
+    let result = check_key(key).map(|()| {
+      self.files().insert(key.to_string(), data);
+    });
+    Box::pin(async move { result })
+  }
+
+  fn get(&self, key: &str) -> BoxFuture<'_, Result<Option<Vec<u8>>, Error>> {
+    let result = check_key(key).map(|()| self.files().get(key).cloned());
+    Box::pin(async move { result })
+  }
+
+  fn delete(&self, key: &str) -> BoxFuture<'_, Result<(), Error>> {
+    let result = check_key(key).map(|()| {
+      self.files().remove(key);
+    });
+    Box::pin(async move { result })
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_memory_storage() {
+    let storage = MemoryStorage::new();
+    storage.put("a/b.txt", b"hello".to_vec()).await.unwrap();
+    assert_eq!(
+      storage.get("a/b.txt").await.unwrap().as_deref(),
+      Some(&b"hello"[..])
+    );
+
+    storage.delete("a/b.txt").await.unwrap();
+    assert_eq!(storage.get("a/b.txt").await.unwrap(), None);
+    assert!(storage.get("../b.txt").await.is_err());
+  }
+}
@@ -0,0 +1,83 @@
+use error_stack::Result;
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::config;
+
+mod local;
+mod memory;
+
+pub use local::LocalStorage;
+pub use memory::MemoryStorage;
+
+/// Storage related errors
+#[derive(Debug, Error)]
+pub enum Error {
+  /// The key contains characters which are not allowed
+  /// or attempts to escape the storage's directory.
+  #[error("invalid storage key")]
+  InvalidKey,
+  /// The storage failed to read, write or delete a file.
+  #[error("failed to access storage")]
+  Io,
+}
+
+/// Stores files generated by the server by their keys.
+///
+/// Keys are relative paths separated by `/` (like `exports/1/2.zip`)
+/// and must be valid according to [`is_valid_key`].
+///
+/// Implementations must be cheap to share between threads
+/// as the same storage is used for the entire server.
+pub trait Storage: std::fmt::Debug + Send + Sync {
+  /// Stores a file, replacing the existing one if there is any.
+  fn put(&self, key: &str, data: Vec<u8>) -> BoxFuture<'_, Result<(), Error>>;
+
+  /// Gets the contents of a file if it exists.
+  fn get(&self, key: &str) -> BoxFuture<'_, Result<Option<Vec<u8>>, Error>>;
+
+  /// Deletes a file. Deleting a file which does not exist is not an error.
+  fn delete(&self, key: &str) -> BoxFuture<'_, Result<(), Error>>;
+}
+
+/// Creates a storage from the [storage config](config::Storage).
+#[must_use]
+pub fn from_config(cfg: &config::Storage) -> Arc<dyn Storage> {
+  match cfg.backend() {
+    config::StorageBackend::Memory => Arc::new(MemoryStorage::new()),
+    config::StorageBackend::Local { path } => Arc::new(LocalStorage::new(path)),
+  }
+}
+
+/// Whether a key only consists of ASCII alphanumerics, `-`, `_`
+/// and `.` separated by `/` without any empty, `.` or `..` segments.
+#[must_use]
+pub fn is_valid_key(key: &str) -> bool {
+  !key.is_empty()
+    && key.split('/').all(|segment| {
+      !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && segment
+          .bytes()
+          .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::is_valid_key;
+
+  #[test]
+  fn test_is_valid_key() {
+    assert!(is_valid_key("exports/1/2.zip"));
+    assert!(is_valid_key("hello"));
+
+    assert!(!is_valid_key(""));
+    assert!(!is_valid_key("/etc/passwd"));
+    assert!(!is_valid_key("exports/../secret"));
+    assert!(!is_valid_key("exports//2.zip"));
+    assert!(!is_valid_key("exports/\\2.zip"));
+  }
+}
@@ -0,0 +1,99 @@
+//! Logging and exporting traces to an OpenTelemetry collector.
+use error_stack::{Report, Result, ResultExt};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporterBuilder, WithExportConfig};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace, Resource};
+use thiserror::Error;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+use crate::config::{self, LogFormat, OtlpProtocol};
+
+#[derive(Debug, Error)]
+#[error("failed to initialize telemetry")]
+pub struct InitError;
+
+/// Handle of the installed telemetry. [`Telemetry::shutdown`] must be
+/// called before exiting, otherwise the last spans will be lost.
+#[derive(Debug)]
+#[must_use]
+pub struct Telemetry {
+  exports_traces: bool,
+}
+
+/// Installs the global tracing subscriber which writes logs to
+/// the standard output and exports traces if [OTLP](config::Otlp)
+/// is configured.
+///
+/// It must be called within a Tokio runtime if traces are exported.
+pub fn init(cfg: &config::Telemetry) -> Result<Telemetry, InitError> {
+  let filter = EnvFilter::builder()
+    .parse(cfg.log_filter())
+    .change_context(InitError)
+    .attach_printable("invalid log filter")?;
+
+  let (pretty, json) = match cfg.log_format() {
+    LogFormat::Pretty => (Some(tracing_subscriber::fmt::layer().pretty()), None),
+    LogFormat::Json => (None, Some(tracing_subscriber::fmt::layer().json())),
+  };
+
+  // Incoming requests continue the trace of their `traceparent` header
+  opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+  let otel = cfg
+    .otlp()
+    .map(tracer)
+    .transpose()?
+    .map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+
+  tracing_subscriber::registry()
+    .with(filter)
+    .with(pretty)
+    .with(json)
+    .with(otel)
+    .try_init()
+    .change_context(InitError)?;
+
+  Ok(Telemetry {
+    exports_traces: cfg.otlp().is_some(),
+  })
+}
+
+impl Telemetry {
+  /// Exports the remaining spans and stops the exporter.
+  pub async fn shutdown(self) {
+    if self.exports_traces {
+      // It blocks until the remaining spans are exported
+      let result = tokio::task::spawn_blocking(opentelemetry::global::shutdown_tracer_provider);
+      if let Err(error) = result.await {
+        tracing::warn!(%error, "failed to shut down the trace exporter");
+      }
+    }
+  }
+}
+
+fn tracer(cfg: &config::Otlp) -> Result<trace::Tracer, InitError> {
+  let exporter: SpanExporterBuilder = match cfg.protocol() {
+    OtlpProtocol::Grpc => opentelemetry_otlp::new_exporter()
+      .tonic()
+      .with_endpoint(cfg.endpoint())
+      .with_timeout(cfg.timeout())
+      .into(),
+    OtlpProtocol::Http => opentelemetry_otlp::new_exporter()
+      .http()
+      .with_endpoint(cfg.endpoint())
+      .with_timeout(cfg.timeout())
+      .into(),
+  };
+
+  let resource = Resource::new([KeyValue::new(
+    "service.name",
+    cfg.service_name().to_string(),
+  )]);
+  opentelemetry_otlp::new_pipeline()
+    .tracing()
+    .with_exporter(exporter)
+    .with_trace_config(trace::config().with_resource(resource))
+    .install_batch(opentelemetry_sdk::runtime::Tokio)
+    .map_err(|e| Report::new(e).change_context(InitError))
+    .attach_printable_lazy(|| format!("with OTLP endpoint: {}", cfg.endpoint()))
+}
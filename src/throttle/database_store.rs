@@ -0,0 +1,80 @@
+use error_stack::{Result, ResultExt};
+use futures::future::BoxFuture;
+
+use super::{AttemptStore, Attempts, Error};
+use crate::{database, schema::LoginAttempt, types::Timestamp};
+
+/// Keeps failed attempts in the primary database so they are
+/// shared between multiple server instances.
+#[derive(Debug, Clone)]
+pub struct DatabaseStore {
+  pool: database::Pool,
+}
+
+impl DatabaseStore {
+  #[must_use]
+  pub fn new(pool: database::Pool) -> Self {
+    Self { pool }
+  }
+}
+
+impl From<LoginAttempt> for Attempts {
+  fn from(value: LoginAttempt) -> Self {
+    Self {
+      failures: u32::try_from(value.failures).unwrap_or_default(),
+      last_failed_at: value.last_failed_at,
+    }
+  }
+}
+
+impl AttemptStore for DatabaseStore {
+  fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Attempts>, Error>> {
+    Box::pin(async move {
+      let mut conn = self.pool.get().await.change_context(Error)?;
+      let attempt = LoginAttempt::by_key(&mut conn, key)
+        .await
+        .change_context(Error)?;
+
+      Ok(attempt.map(Attempts::from))
+    })
+  }
+
+  fn record_failure<'a>(
+    &'a self,
+    key: &'a str,
+    now: Timestamp,
+    forget_before: Timestamp,
+  ) -> BoxFuture<'a, Result<Attempts, Error>> {
+    Box::pin(async move {
+      let mut conn = self.pool.get().await.change_context(Error)?;
+      let attempt = LoginAttempt::record_failure(&mut conn, key, now, forget_before)
+        .await
+        .change_context(Error)?;
+
+      Ok(attempt.into())
+    })
+  }
+
+  fn reset<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+    Box::pin(async move {
+      let mut conn = self.pool.get().await.change_context(Error)?;
+      LoginAttempt::delete(&mut conn, key)
+        .await
+        .change_context(Error)
+    })
+  }
+
+  fn release<'a>(
+    &'a self,
+    key: &'a str,
+    failed_at: Timestamp,
+    previous: Option<Timestamp>,
+  ) -> BoxFuture<'a, Result<(), Error>> {
+    Box::pin(async move {
+      let mut conn = self.pool.get().await.change_context(Error)?;
+      LoginAttempt::release(&mut conn, key, failed_at, previous)
+        .await
+        .change_context(Error)
+    })
+  }
+}
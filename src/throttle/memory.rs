@@ -0,0 +1,82 @@
+use error_stack::Result;
+use futures::future::BoxFuture;
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use super::{AttemptStore, Attempts, Error};
+use crate::types::Timestamp;
+
+/// Keeps failed attempts in memory.
+///
+/// This is only useful for testing and development.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+  attempts: Arc<Mutex<HashMap<String, Attempts>>>,
+}
+
+impl MemoryStore {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn with_attempts<T>(&self, f: impl FnOnce(&mut HashMap<String, Attempts>) -> T) -> T {
+    match self.attempts.lock() {
+      Ok(mut attempts) => f(&mut attempts),
+      Err(poisoned) => f(&mut poisoned.into_inner()),
+    }
+  }
+}
+
+impl AttemptStore for MemoryStore {
+  fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Attempts>, Error>> {
+    let attempts = self.with_attempts(|v| v.get(key).copied());
+    Box::pin(async move { Ok(attempts) })
+  }
+
+  fn record_failure<'a>(
+    &'a self,
+    key: &'a str,
+    now: Timestamp,
+    forget_before: Timestamp,
+  ) -> BoxFuture<'a, Result<Attempts, Error>> {
+    let attempts = self.with_attempts(|v| {
+      let entry = v.entry(key.to_string()).or_insert(Attempts {
+        failures: 0,
+        last_failed_at: now,
+      });
+
+      if entry.last_failed_at < forget_before {
+        entry.failures = 0;
+      }
+      entry.failures = entry.failures.saturating_add(1);
+      entry.last_failed_at = now;
+      *entry
+    });
+    Box::pin(async move { Ok(attempts) })
+  }
+
+  fn reset<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+    self.with_attempts(|v| v.remove(key));
+    Box::pin(async { Ok(()) })
+  }
+
+  fn release<'a>(
+    &'a self,
+    key: &'a str,
+    failed_at: Timestamp,
+    previous: Option<Timestamp>,
+  ) -> BoxFuture<'a, Result<(), Error>> {
+    self.with_attempts(|v| {
+      if let Some(entry) = v.get_mut(key) {
+        entry.failures = entry.failures.saturating_sub(1);
+        if entry.last_failed_at == failed_at {
+          entry.last_failed_at = previous.unwrap_or(failed_at);
+        }
+      }
+    });
+    Box::pin(async { Ok(()) })
+  }
+}
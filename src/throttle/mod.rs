@@ -0,0 +1,409 @@
+use error_stack::Result;
+use futures::future::BoxFuture;
+use std::{sync::Arc, time::Duration};
+use thiserror::Error;
+
+use crate::{auth::token, config, database, types::Timestamp};
+
+mod database_store;
+mod memory;
+
+pub use database_store::DatabaseStore;
+pub use memory::MemoryStore;
+
+/// Throttling related errors
+#[derive(Debug, Error)]
+#[error("failed to access failed attempts storage")]
+pub struct Error;
+
+/// Failed attempts of a throttled key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attempts {
+  pub failures: u32,
+  pub last_failed_at: Timestamp,
+}
+
+/// Keeps track of failed attempts of throttled keys.
+///
+/// Implementations must be cheap to share between threads
+/// as the same storage is used for the entire server.
+pub trait AttemptStore: std::fmt::Debug + Send + Sync {
+  fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Attempts>, Error>>;
+
+  /// Records a failed attempt at `now`. Previous failed attempts
+  /// must be forgotten if the last one happened before `forget_before`.
+  fn record_failure<'a>(
+    &'a self,
+    key: &'a str,
+    now: Timestamp,
+    forget_before: Timestamp,
+  ) -> BoxFuture<'a, Result<Attempts, Error>>;
+
+  fn reset<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+
+  /// Takes back a failed attempt recorded at `failed_at`. The time of
+  /// the last failed attempt must be restored to `previous` unless
+  /// another failed attempt has been recorded since then.
+  fn release<'a>(
+    &'a self,
+    key: &'a str,
+    failed_at: Timestamp,
+    previous: Option<Timestamp>,
+  ) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+/// Creates a failed attempts storage from the [throttle config](config::Throttle).
+pub fn from_config(cfg: &config::Throttle, primary_db: &database::Pool) -> Arc<dyn AttemptStore> {
+  match cfg.storage() {
+    config::ThrottleStorage::Database => Arc::new(DatabaseStore::new(primary_db.clone())),
+    config::ThrottleStorage::Memory => Arc::new(MemoryStore::new()),
+  }
+}
+
+/// Login attempt counted by [`LoginThrottle::attempt`].
+#[derive(Debug)]
+#[must_use]
+pub struct Attempt {
+  retry_after: Option<Duration>,
+  counted_at: Timestamp,
+  // Keys the attempt is counted against along
+  // with the time of their previous failure
+  counted: Vec<(String, Option<Timestamp>)>,
+}
+
+impl Attempt {
+  /// How long the client has to wait if any of the keys is blocked.
+  #[must_use]
+  pub const fn retry_after(&self) -> Option<Duration> {
+    self.retry_after
+  }
+}
+
+/// Slows down brute-force login attempts by applying exponential
+/// backoff and temporary lockouts to accounts and IP addresses
+/// with too many failed attempts.
+///
+/// Storage errors are logged and never block the user from logging
+/// in, so the server stays usable when the database is read-only.
+#[derive(Debug, Clone)]
+pub struct LoginThrottle {
+  store: Arc<dyn AttemptStore>,
+  policy: config::LoginThrottle,
+}
+
+impl LoginThrottle {
+  #[must_use]
+  pub fn new(store: Arc<dyn AttemptStore>, policy: config::LoginThrottle) -> Self {
+    Self { store, policy }
+  }
+
+  /// Throttling key of an account, shared by logging in
+  /// with either its name or email address.
+  #[must_use]
+  pub fn account_key(user_id: impl std::fmt::Display) -> String {
+    token::hash(&format!("account:{user_id}"))
+  }
+
+  /// Throttling key of a name or email address without an account,
+  /// so unknown accounts are throttled just like existing ones.
+  #[must_use]
+  pub fn unknown_account_key(name_or_email: &str) -> String {
+    token::hash(&format!("unknown:{}", name_or_email.trim().to_lowercase()))
+  }
+
+  /// Throttling key of a client's IP address.
+  #[must_use]
+  pub fn ip_key(ip_address: &str) -> String {
+    token::hash(&format!("ip:{ip_address}"))
+  }
+
+  /// Throttling key of two-factor authentication attempts of a user.
+  #[must_use]
+  pub fn two_factor_key(user_id: impl std::fmt::Display) -> String {
+    token::hash(&format!("2fa:{user_id}"))
+  }
+
+  /// Counts an attempt against each key before it is verified, so
+  /// parallel attempts cannot all get through before any of them
+  /// fails. Keys which are blocked are not counted again.
+  ///
+  /// The attempt stays counted as a failure unless it is taken
+  /// back with [`LoginThrottle::reset`] or [`LoginThrottle::release`].
+  #[tracing::instrument(skip_all)]
+  pub async fn attempt(&self, keys: &[String]) -> Attempt {
+    let now = Timestamp::now();
+    let forget_before = now.saturating_sub(self.policy.window());
+
+    let mut attempt = Attempt {
+      retry_after: None,
+      counted_at: now,
+      counted: Vec::with_capacity(keys.len()),
+    };
+    for key in keys {
+      let previous = match self.store.get(key).await {
+        Ok(attempts) => attempts,
+        Err(error) => {
+          tracing::warn!(?error, "failed to check failed login attempts");
+          continue;
+        }
+      };
+
+      if let Some(remaining) = previous.and_then(|v| remaining_delay(&self.policy, v, now)) {
+        attempt.retry_after = attempt.retry_after.max(Some(remaining));
+        continue;
+      }
+
+      let counted = match self.store.record_failure(key, now, forget_before).await {
+        Ok(attempts) => attempts,
+        Err(error) => {
+          tracing::warn!(?error, "failed to record login attempt");
+          continue;
+        }
+      };
+      attempt
+        .counted
+        .push((key.clone(), previous.map(|v| v.last_failed_at)));
+
+      let previous_failures = previous
+        .filter(|v| v.last_failed_at >= forget_before)
+        .map_or(0, |v| v.failures);
+
+      if let Some(delay) = raced_delay(&self.policy, previous_failures, counted.failures) {
+        attempt.retry_after = attempt.retry_after.max(Some(delay));
+      }
+    }
+    attempt
+  }
+
+  /// Forgets all failed attempts of a key.
+  #[tracing::instrument(skip_all)]
+  pub async fn reset(&self, key: &str) {
+    if let Err(error) = self.store.reset(key).await {
+      tracing::warn!(?error, "failed to reset failed login attempts");
+    }
+  }
+
+  /// Takes back a successful attempt counted against a key while
+  /// keeping its earlier failures, as if it was never counted.
+  #[tracing::instrument(skip_all)]
+  pub async fn release(&self, attempt: &Attempt, key: &str) {
+    let Some((_, previous)) = attempt.counted.iter().find(|(v, _)| v == key) else {
+      return;
+    };
+    if let Err(error) = self.store.release(key, attempt.counted_at, *previous).await {
+      tracing::warn!(?error, "failed to release login attempt");
+    }
+  }
+}
+
+/// How long further attempts are blocked after a number of
+/// consecutive failed attempts.
+pub fn delay(policy: &config::LoginThrottle, failures: u32) -> Duration {
+  if failures >= policy.lockout_threshold() {
+    return policy.lockout();
+  }
+
+  let Some(exponent) = failures.checked_sub(policy.free_attempts() + 1) else {
+    return Duration::ZERO;
+  };
+
+  2u32
+    .checked_pow(exponent)
+    .and_then(|n| policy.backoff_base().checked_mul(n))
+    .map_or(policy.backoff_max(), |v| v.min(policy.backoff_max()))
+}
+
+/// How long an attempt is blocked if other attempts were counted
+/// between checking the key and counting it (`counted` includes
+/// the attempt itself).
+///
+/// Attempts which did not race with others have already waited
+/// out the delay of the previous failures.
+fn raced_delay(policy: &config::LoginThrottle, previous: u32, counted: u32) -> Option<Duration> {
+  if counted <= previous.saturating_add(1) {
+    return None;
+  }
+  Some(delay(policy, counted - 1)).filter(|v| !v.is_zero())
+}
+
+fn remaining_delay(
+  policy: &config::LoginThrottle,
+  attempts: Attempts,
+  now: Timestamp,
+) -> Option<Duration> {
+  let blocked_until = attempts
+    .last_failed_at
+    .saturating_add(delay(policy, attempts.failures));
+  blocked_until.duration_since(now).filter(|v| !v.is_zero())
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_delay() {
+    let policy = config::LoginThrottle::default();
+    let free = policy.free_attempts();
+
+    assert_eq!(delay(&policy, 0), Duration::ZERO);
+    assert_eq!(delay(&policy, free), Duration::ZERO);
+    assert_eq!(delay(&policy, free + 1), policy.backoff_base());
+    assert_eq!(delay(&policy, free + 2), policy.backoff_base() * 2);
+    assert_eq!(delay(&policy, free + 3), policy.backoff_base() * 4);
+    assert_eq!(
+      delay(&policy, policy.lockout_threshold() - 1),
+      policy.backoff_max()
+    );
+    assert_eq!(delay(&policy, policy.lockout_threshold()), policy.lockout());
+    assert_eq!(delay(&policy, u32::MAX), policy.lockout());
+  }
+
+  #[test]
+  fn test_raced_delay() {
+    let policy = config::LoginThrottle::default();
+    let free = policy.free_attempts();
+
+    assert_eq!(raced_delay(&policy, 0, 1), None);
+    assert_eq!(raced_delay(&policy, free + 3, free + 4), None);
+
+    // parallel attempts only get through while they're free
+    assert_eq!(raced_delay(&policy, 0, free + 1), None);
+    assert_eq!(
+      raced_delay(&policy, 0, free + 2),
+      Some(policy.backoff_base())
+    );
+    assert_eq!(
+      raced_delay(&policy, 0, policy.lockout_threshold() + 1),
+      Some(policy.lockout())
+    );
+  }
+
+  #[tokio::test]
+  async fn test_login_throttle() {
+    let policy = config::LoginThrottle::default();
+    let throttle = LoginThrottle::new(Arc::new(MemoryStore::new()), policy.clone());
+    let keys = vec![
+      LoginThrottle::account_key(1),
+      LoginThrottle::ip_key("127.0.0.1"),
+    ];
+
+    for _ in 0..=policy.free_attempts() {
+      assert_eq!(throttle.attempt(&keys).await.retry_after(), None);
+    }
+
+    let retry_after = throttle.attempt(&keys).await.retry_after().unwrap();
+    assert!(retry_after <= policy.backoff_base());
+
+    // names are case-insensitive
+    let unknown = LoginThrottle::unknown_account_key("Memo");
+    assert_eq!(unknown, LoginThrottle::unknown_account_key(" memo "));
+
+    // logging in successfully only resets the account
+    throttle.reset(&keys[0]).await;
+    assert_eq!(throttle.attempt(&keys[..1]).await.retry_after(), None);
+    assert!(throttle.attempt(&keys[1..]).await.retry_after().is_some());
+  }
+
+  /// Counts `racers` parallel attempts of a key right after it is checked.
+  #[derive(Debug)]
+  struct RacingStore {
+    inner: MemoryStore,
+    racers: u32,
+  }
+
+  impl AttemptStore for RacingStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Attempts>, Error>> {
+      Box::pin(async move {
+        let attempts = self.inner.get(key).await?;
+        for _ in 0..self.racers {
+          let now = Timestamp::now();
+          let forget_before = now.saturating_sub(Duration::from_secs(60));
+          self.inner.record_failure(key, now, forget_before).await?;
+        }
+        Ok(attempts)
+      })
+    }
+
+    fn record_failure<'a>(
+      &'a self,
+      key: &'a str,
+      now: Timestamp,
+      forget_before: Timestamp,
+    ) -> BoxFuture<'a, Result<Attempts, Error>> {
+      self.inner.record_failure(key, now, forget_before)
+    }
+
+    fn reset<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+      self.inner.reset(key)
+    }
+
+    fn release<'a>(
+      &'a self,
+      key: &'a str,
+      failed_at: Timestamp,
+      previous: Option<Timestamp>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+      self.inner.release(key, failed_at, previous)
+    }
+  }
+
+  #[tokio::test]
+  async fn test_parallel_attempts() {
+    let policy = config::LoginThrottle::default();
+    let keys = [LoginThrottle::account_key(1)];
+
+    let store = RacingStore {
+      inner: MemoryStore::new(),
+      racers: policy.free_attempts(),
+    };
+    let throttle = LoginThrottle::new(Arc::new(store), policy.clone());
+    assert_eq!(throttle.attempt(&keys).await.retry_after(), None);
+
+    let store = RacingStore {
+      inner: MemoryStore::new(),
+      racers: policy.free_attempts() + 1,
+    };
+    let throttle = LoginThrottle::new(Arc::new(store), policy.clone());
+    assert_eq!(
+      throttle.attempt(&keys).await.retry_after(),
+      Some(policy.backoff_base())
+    );
+  }
+
+  #[tokio::test]
+  async fn test_release() {
+    let policy = config::LoginThrottle::default();
+    let store = MemoryStore::new();
+    let throttle = LoginThrottle::new(Arc::new(store.clone()), policy.clone());
+    let keys = [LoginThrottle::ip_key("127.0.0.1")];
+
+    // successful attempts do not add up
+    for _ in 0..policy.lockout_threshold() {
+      let attempt = throttle.attempt(&keys).await;
+      assert_eq!(attempt.retry_after(), None);
+      throttle.release(&attempt, &keys[0]).await;
+    }
+    let attempts = store.get(&keys[0]).await.unwrap().unwrap();
+    assert_eq!(attempts.failures, 0);
+
+    // nor do they delay the backoff of earlier failures
+    let failed = throttle.attempt(&keys).await;
+    let attempt = throttle.attempt(&keys).await;
+    throttle.release(&attempt, &keys[0]).await;
+
+    let attempts = store.get(&keys[0]).await.unwrap().unwrap();
+    assert_eq!(attempts.failures, 1);
+    assert_eq!(attempts.last_failed_at, failed.counted_at);
+
+    // unless another attempt has failed in the meantime
+    let attempt = throttle.attempt(&keys).await;
+    let failed = throttle.attempt(&keys).await;
+    throttle.release(&attempt, &keys[0]).await;
+
+    let attempts = store.get(&keys[0]).await.unwrap().unwrap();
+    assert_eq!(attempts.failures, 2);
+    assert_eq!(attempts.last_failed_at, failed.counted_at);
+  }
+}
@@ -9,7 +9,29 @@ pub enum Error {
   InvalidFormBody(validator::ValidateError),
   NotFound,
   Unauthorized,
+  /// The user is not allowed to modify the resource
+  /// as it belongs to another user.
+  Forbidden,
   ReadonlyMode,
+  EmailNotVerified,
+  /// The account is pending deletion, it has to be
+  /// restored before logging in again.
+  AccountPendingDeletion,
+  /// The API token used for the request is not granted
+  /// with the scope required by the route.
+  MissingScope {
+    scope: super::Scope,
+  },
+  /// Too many failed login attempts, the client must wait
+  /// for `retry_after` seconds before trying again.
+  LoginThrottled {
+    retry_after: u64,
+  },
+  /// The client has sent too many requests, it must wait
+  /// for `retry_after` seconds before trying again.
+  RateLimited {
+    retry_after: u64,
+  },
 }
 
 impl Display for Error {
@@ -18,8 +40,20 @@ impl Display for Error {
       Error::Internal => f.write_str("Failed to perform request"),
       Error::InvalidFormBody(..) => f.write_str("User performed request with invalid body"),
       Error::NotFound => f.write_str("Attempt to find resource which is not exists"),
+      Error::Forbidden => f.write_str("Attempt to modify resource owned by another user"),
       Error::ReadonlyMode => f.write_str("Attempt to write read-only database"),
       Error::Unauthorized => f.write_str("Attempt to access resource only for logged in users"),
+      Error::EmailNotVerified => {
+        f.write_str("Attempt to access resource only for users with verified email address")
+      }
+      Error::AccountPendingDeletion => {
+        f.write_str("Attempt to log in to an account pending deletion")
+      }
+      Error::MissingScope { .. } => {
+        f.write_str("Attempt to access resource without the required API token scope")
+      }
+      Error::LoginThrottled { .. } => f.write_str("Too many failed login attempts"),
+      Error::RateLimited { .. } => f.write_str("Too many requests"),
     }
   }
 }
@@ -48,6 +82,36 @@ mod tests {
   #[test]
   fn test_serde_impl() {
     assert_unit_variant(Error::Internal, "internal");
+    assert_unit_variant(Error::Forbidden, "forbidden");
     assert_unit_variant(Error::ReadonlyMode, "readonly_mode");
+    assert_unit_variant(Error::EmailNotVerified, "email_not_verified");
+    assert_unit_variant(Error::AccountPendingDeletion, "account_pending_deletion");
+  }
+
+  #[track_caller]
+  fn assert_retry_after_variant(value: &Error, variant: &'static str) {
+    serde_test::assert_tokens(
+      value,
+      &[
+        Token::Struct {
+          name: "Error",
+          len: 2,
+        },
+        Token::Str("type"),
+        Token::Str(variant),
+        Token::Str("retry_after"),
+        Token::U64(30),
+        Token::StructEnd,
+      ],
+    );
+  }
+
+  #[test]
+  fn test_retry_after_variants() {
+    assert_retry_after_variant(
+      &Error::LoginThrottled { retry_after: 30 },
+      "login_throttled",
+    );
+    assert_retry_after_variant(&Error::RateLimited { retry_after: 30 }, "rate_limited");
   }
 }
@@ -0,0 +1,15 @@
+use crate::util::Sensitive;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct Request {
+  #[validate(length(min = 1, max = 128))]
+  pub refresh_token: Sensitive<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Response {
+  pub token: Sensitive<String>,
+  pub refresh_token: Sensitive<String>,
+}
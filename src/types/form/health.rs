@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::{self, migrations::MigrationStatus};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+  /// Everything is working as expected.
+  Ok,
+  /// The server can still serve requests but the replica
+  /// is unhealthy or migrations are not fully applied.
+  Degraded,
+  /// The primary database is unhealthy.
+  Unavailable,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Liveness {
+  pub status: Status,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Readiness {
+  pub status: Status,
+  /// Whether writes are currently rejected, either because the
+  /// primary database is in read-only mode or only the replica
+  /// is able to serve requests.
+  pub read_only: bool,
+  pub primary: PoolStatus,
+  pub replica: Option<PoolStatus>,
+  /// It is `None` if none of the database pools are healthy.
+  pub migrations: Option<Migrations>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PoolStatus {
+  pub healthy: bool,
+  pub connections: u32,
+}
+
+impl PoolStatus {
+  #[must_use]
+  pub fn new(pool: &database::Pool, healthy: bool) -> Self {
+    Self {
+      healthy,
+      connections: pool.connections(),
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Migrations {
+  pub applied: usize,
+  pub pending: usize,
+  pub dirty: bool,
+}
+
+impl From<MigrationStatus> for Migrations {
+  fn from(status: MigrationStatus) -> Self {
+    Self {
+      applied: status.applied,
+      pending: status.pending,
+      dirty: status.dirty,
+    }
+  }
+}
+
+impl Readiness {
+  /// Overall status derived from the primary and
+  /// replica pools and migrations.
+  #[must_use]
+  pub fn status_of(
+    primary: &PoolStatus,
+    replica: Option<&PoolStatus>,
+    migrations: Option<&Migrations>,
+  ) -> Status {
+    if !primary.healthy {
+      return Status::Unavailable;
+    }
+
+    let replica_healthy = replica.map_or(true, |v| v.healthy);
+    let migrated = migrations.is_some_and(|v| v.pending == 0 && !v.dirty);
+    if replica_healthy && migrated {
+      Status::Ok
+    } else {
+      Status::Degraded
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pool(healthy: bool) -> PoolStatus {
+    PoolStatus {
+      healthy,
+      connections: u32::from(healthy),
+    }
+  }
+
+  fn migrations(pending: usize) -> Migrations {
+    Migrations {
+      applied: 1,
+      pending,
+      dirty: false,
+    }
+  }
+
+  #[test]
+  fn test_status_of() {
+    let up_to_date = migrations(0);
+    assert_eq!(
+      Readiness::status_of(&pool(true), None, Some(&up_to_date)),
+      Status::Ok
+    );
+    assert_eq!(
+      Readiness::status_of(&pool(true), Some(&pool(false)), Some(&up_to_date)),
+      Status::Degraded
+    );
+    assert_eq!(
+      Readiness::status_of(&pool(true), None, Some(&migrations(1))),
+      Status::Degraded
+    );
+    assert_eq!(
+      Readiness::status_of(&pool(false), Some(&pool(true)), Some(&up_to_date)),
+      Status::Unavailable
+    );
+  }
+}
@@ -1 +1,19 @@
+use serde::{Deserialize, Deserializer};
+
+pub mod auth;
+pub mod health;
+pub mod posts;
 pub mod users;
+
+/// Deserializes a field which can be either missing (`None`),
+/// explicitly set to `null` (`Some(None)`) or set to a value.
+///
+/// It must be used along with `#[serde(default)]`.
+#[allow(clippy::option_option)]
+pub(crate) fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+  T: Deserialize<'de>,
+  D: Deserializer<'de>,
+{
+  Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+  schema,
+  types::{
+    id::{
+      marker::{PostMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Post {
+  pub id: Id<PostMarker>,
+  pub author_id: Id<UserMarker>,
+  pub created_at: Timestamp,
+  pub content: String,
+}
+
+impl Post {
+  #[must_use]
+  pub fn new(post: schema::Post) -> Self {
+    Self {
+      id: post.id,
+      author_id: post.author_id,
+      created_at: post.created_at,
+      content: post.content,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateRequest {
+  #[validate(
+    length(min = 1, max = 500),
+    with = "CreateRequest::validate_content",
+    error = "Post must not be empty"
+  )]
+  pub content: String,
+}
+
+impl CreateRequest {
+  /// Content of the post without surrounding whitespaces.
+  #[must_use]
+  pub fn content(&self) -> &str {
+    self.content.trim()
+  }
+
+  fn validate_content(content: &str) -> bool {
+    !content.trim().is_empty()
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_create_request() {
+    let form = CreateRequest {
+      content: "Hello, world!".into(),
+    };
+    assert!(form.validate().is_ok());
+
+    let form = CreateRequest {
+      content: String::new(),
+    };
+    assert!(form.validate().is_err());
+
+    let form = CreateRequest {
+      content: "a".repeat(501),
+    };
+    assert!(form.validate().is_err());
+
+    let form = CreateRequest {
+      content: " \n\t ".into(),
+    };
+    assert!(form.validate().is_err());
+
+    let form = CreateRequest {
+      content: "  Hello, world!\n".into(),
+    };
+    assert!(form.validate().is_ok());
+    assert_eq!(form.content(), "Hello, world!");
+  }
+}
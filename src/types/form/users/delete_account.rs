@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{types::Timestamp, util::Sensitive};
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct Request {
+  #[validate(length(min = 1, max = 128))]
+  pub password: Sensitive<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Response {
+  /// The account can be restored with `POST /users/cancel-deletion`
+  /// until this time, then it will be deleted permanently.
+  pub purge_at: Timestamp,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CancelRequest {
+  #[validate(length(min = 1, max = 128))]
+  pub username_or_email: Sensitive<String>,
+  #[validate(length(min = 1, max = 128))]
+  pub password: Sensitive<String>,
+}
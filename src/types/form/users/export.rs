@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  schema::{self, DataExportStatus},
+  types::{
+    form::posts::post::Post,
+    id::{
+      marker::{DataExportMarker, UserMarker},
+      Id,
+    },
+    Timestamp,
+  },
+};
+
+use super::{follows::FollowUser, sessions::Session, tokens::ApiToken};
+
+/// A data export returned by `POST /users/@me/export`
+/// and `GET /users/@me/export/{id}`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Export {
+  pub id: Id<DataExportMarker>,
+  pub status: Status,
+  pub created_at: Timestamp,
+  pub expires_at: Option<Timestamp>,
+  /// Signed URL to download the archive. It is only
+  /// available once the export is ready.
+  pub download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+  Pending,
+  Ready,
+  Failed,
+  Downloaded,
+  Expired,
+}
+
+impl From<DataExportStatus> for Status {
+  fn from(status: DataExportStatus) -> Self {
+    match status {
+      DataExportStatus::Pending => Self::Pending,
+      DataExportStatus::Ready => Self::Ready,
+      DataExportStatus::Failed => Self::Failed,
+      DataExportStatus::Downloaded => Self::Downloaded,
+      DataExportStatus::Expired => Self::Expired,
+    }
+  }
+}
+
+/// Contents of `data.json` inside the archive.
+///
+/// Every object is serialized the same way as in the public API.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Archive {
+  pub account: Account,
+  pub sessions: Vec<Session>,
+  pub api_tokens: Vec<ApiToken>,
+  pub username_history: Vec<UsernameChange>,
+  pub posts: Vec<Post>,
+  /// Users followed by the user.
+  pub following: Vec<FollowUser>,
+  /// Users following the user.
+  pub followers: Vec<FollowUser>,
+}
+
+/// Everything stored in the user's account except their password hash.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Account {
+  pub id: Id<UserMarker>,
+  pub name: String,
+  pub display_name: Option<String>,
+  pub email: Option<String>,
+  pub email_verified_at: Option<Timestamp>,
+  pub bio: Option<String>,
+  pub location: Option<String>,
+  pub website: Option<String>,
+  pub created_at: Timestamp,
+  pub updated_at: Option<Timestamp>,
+}
+
+impl Account {
+  #[must_use]
+  pub fn new(user: schema::User) -> Self {
+    Self {
+      id: user.id,
+      name: user.name,
+      display_name: user.display_name,
+      email: user.email,
+      email_verified_at: user.email_verified_at,
+      bio: user.bio,
+      location: user.location,
+      website: user.website,
+      created_at: user.created_at,
+      updated_at: user.updated_at,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UsernameChange {
+  pub old_name: String,
+  pub new_name: String,
+  pub changed_at: Timestamp,
+}
+
+impl UsernameChange {
+  #[must_use]
+  pub fn new(entry: schema::UsernameHistory) -> Self {
+    Self {
+      old_name: entry.old_name,
+      new_name: entry.new_name,
+      changed_at: entry.changed_at,
+    }
+  }
+}
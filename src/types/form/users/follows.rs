@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  schema,
+  types::id::{marker::UserMarker, Id},
+};
+
+/// A user listed in `GET /users/@{name}/followers`
+/// and `GET /users/@{name}/following`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FollowUser {
+  pub id: Id<UserMarker>,
+  pub name: String,
+  pub display_name: Option<String>,
+}
+
+impl FollowUser {
+  #[must_use]
+  pub fn new(user: schema::User) -> Self {
+    Self {
+      id: user.id,
+      name: user.name,
+      display_name: user.display_name,
+    }
+  }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListQuery {
+  /// Lists users after the user with this ID.
+  #[serde(default)]
+  pub after: Option<Id<UserMarker>>,
+  /// How many users to list, it is clamped between
+  /// 1 and [`ListQuery::MAX_LIMIT`].
+  #[serde(default)]
+  pub limit: Option<u32>,
+}
+
+impl ListQuery {
+  pub const DEFAULT_LIMIT: u32 = 50;
+  pub const MAX_LIMIT: u32 = 100;
+
+  #[must_use]
+  pub fn limit(&self) -> u32 {
+    self
+      .limit
+      .unwrap_or(Self::DEFAULT_LIMIT)
+      .clamp(1, Self::MAX_LIMIT)
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListResponse {
+  pub users: Vec<FollowUser>,
+  /// Pass it as `after` to get the next page. It is `null`
+  /// if there are no more users to list.
+  pub next: Option<Id<UserMarker>>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_list_query_limit() {
+    let query = ListQuery::default();
+    assert_eq!(query.limit(), ListQuery::DEFAULT_LIMIT);
+
+    let query = ListQuery {
+      after: None,
+      limit: Some(0),
+    };
+    assert_eq!(query.limit(), 1);
+
+    let query = ListQuery {
+      after: None,
+      limit: Some(u32::MAX),
+    };
+    assert_eq!(query.limit(), ListQuery::MAX_LIMIT);
+  }
+}
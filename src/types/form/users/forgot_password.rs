@@ -0,0 +1,9 @@
+use crate::util::Sensitive;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct Request {
+  #[validate(length(min = 1, max = 255))]
+  pub username_or_email: Sensitive<String>,
+}
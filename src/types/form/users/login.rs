@@ -17,4 +17,13 @@ pub struct Request {
 pub struct Response {
   pub id: Id<UserMarker>,
   pub token: Sensitive<String>,
+  pub refresh_token: Sensitive<String>,
+}
+
+/// Returned instead of [`Response`] if the user has enabled two-factor
+/// authentication. The challenge token must be exchanged for a
+/// [`Response`] along with a two-factor authentication code.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChallengeResponse {
+  pub challenge_token: Sensitive<String>,
 }
@@ -1,2 +1,13 @@
+pub mod delete_account;
+pub mod export;
+pub mod follows;
+pub mod forgot_password;
 pub mod login;
+pub mod profile;
 pub mod register;
+pub mod reset_password;
+pub mod sessions;
+pub mod tokens;
+pub mod two_factor;
+pub mod username;
+pub mod verify_email;
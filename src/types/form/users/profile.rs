@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use validator::{extras::validate_url, Validate, ValidateError};
+
+use crate::{
+  schema::{self, FollowCounts, ProfileUpdate},
+  types::{
+    id::{marker::UserMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A user's public profile.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Profile {
+  pub id: Id<UserMarker>,
+  pub created_at: Timestamp,
+  pub name: String,
+  pub display_name: Option<String>,
+  pub bio: Option<String>,
+  pub location: Option<String>,
+  pub website: Option<String>,
+  pub followers_count: u64,
+  pub following_count: u64,
+}
+
+impl Profile {
+  #[must_use]
+  pub fn new(user: schema::User, counts: FollowCounts) -> Self {
+    Self {
+      id: user.id,
+      created_at: user.created_at,
+      name: user.name,
+      display_name: user.display_name,
+      bio: user.bio,
+      location: user.location,
+      website: user.website,
+      followers_count: u64::try_from(counts.followers).unwrap_or_default(),
+      following_count: u64::try_from(counts.following).unwrap_or_default(),
+    }
+  }
+}
+
+/// Partial update of the user's profile. Missing fields are left
+/// unchanged while `null` or empty values clear them.
+#[allow(clippy::option_option)]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateRequest {
+  #[serde(default, deserialize_with = "super::super::double_option")]
+  pub display_name: Option<Option<String>>,
+  #[serde(default, deserialize_with = "super::super::double_option")]
+  pub bio: Option<Option<String>>,
+  #[serde(default, deserialize_with = "super::super::double_option")]
+  pub location: Option<Option<String>>,
+  #[serde(default, deserialize_with = "super::super::double_option")]
+  pub website: Option<Option<String>>,
+}
+
+impl UpdateRequest {
+  pub const DISPLAY_NAME_MAX: usize = 25;
+  pub const BIO_MAX: usize = 160;
+  pub const LOCATION_MAX: usize = 64;
+  pub const WEBSITE_MAX: usize = 255;
+
+  /// Whether the request changes nothing.
+  pub const fn is_empty(&self) -> bool {
+    self.display_name.is_none()
+      && self.bio.is_none()
+      && self.location.is_none()
+      && self.website.is_none()
+  }
+
+  /// Converts into changes to be written into the database.
+  pub fn to_update(&self) -> ProfileUpdate<'_> {
+    ProfileUpdate {
+      display_name: normalize(self.display_name.as_ref()),
+      bio: normalize(self.bio.as_ref()),
+      location: normalize(self.location.as_ref()),
+      website: normalize(self.website.as_ref()),
+    }
+  }
+}
+
+impl Validate for UpdateRequest {
+  fn validate(&self) -> Result<(), ValidateError> {
+    let update = self.to_update();
+    let mut fields = ValidateError::field_builder();
+
+    let limits = [
+      ("display_name", update.display_name, Self::DISPLAY_NAME_MAX),
+      ("bio", update.bio, Self::BIO_MAX),
+      ("location", update.location, Self::LOCATION_MAX),
+      ("website", update.website, Self::WEBSITE_MAX),
+    ];
+    for (field, value, max) in limits {
+      if value.flatten().is_some_and(|v| v.chars().count() > max) {
+        let mut error = ValidateError::msg_builder();
+        error.insert(format!("Must not be longer than {max} characters"));
+        fields.insert(field, error.build());
+      }
+    }
+
+    if let Some(website) = update.website.flatten() {
+      let is_http = website.starts_with("https://") || website.starts_with("http://");
+      if !is_http || !validate_url(website) {
+        let mut error = ValidateError::msg_builder();
+        error.insert("Invalid website URL");
+        fields.insert("website", error.build());
+      }
+    }
+
+    fields.build().into_result()
+  }
+}
+
+// Surrounding whitespaces are trimmed and blank values are treated as `null`
+#[allow(clippy::option_option)]
+fn normalize(value: Option<&Option<String>>) -> Option<Option<&str>> {
+  value.map(|v| v.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(json: &str) -> UpdateRequest {
+    serde_json::from_str(json).unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  #[test]
+  fn test_partial_update() {
+    let form = parse(r#"{"display_name":"  Memo  ","bio":null,"location":""}"#);
+    let update = form.to_update();
+    assert_eq!(update.display_name, Some(Some("Memo")));
+    assert_eq!(update.bio, Some(None));
+    assert_eq!(update.location, Some(None));
+    assert_eq!(update.website, None);
+    assert!(form.validate().is_ok());
+
+    assert!(parse("{}").is_empty());
+  }
+
+  #[test]
+  fn test_validate() {
+    let form = parse(&format!(r#"{{"display_name":"{}"}}"#, "a".repeat(26)));
+    assert!(form.validate().is_err());
+
+    let form = parse(r#"{"website":"https://example.com/memo"}"#);
+    assert!(form.validate().is_ok());
+
+    for website in ["example.com", "javascript:alert(1)", "ftp://example.com"] {
+      let form = parse(&format!(r#"{{"website":"{website}"}}"#));
+      assert!(form.validate().is_err(), "website = {website:?}");
+    }
+  }
+}
@@ -34,27 +34,7 @@ impl Validate for Request {
       });
     }
 
-    // TODO: check for weak passwords
-    fields.insert("password", {
-      // All passwords must have no trailing or leading whitespaces
-      let mut error = ValidateError::msg_builder();
-      let password = self.password.as_str().trim();
-      if self.password.len() != password.len() {
-        error.insert("Passwords must not have starting or ending with spaces");
-      } else if self.password.len() > validation::PASSWORD_MAX {
-        error.insert("Passwords must not be too big");
-      } else if self.password.len() < validation::PASSWORD_MIN {
-        error.insert("Passwords must not be too short");
-      }
-      error.build()
-    });
-
-    // Not very secure... :(
-    if self.password.as_str() != self.confirm_password.as_str() {
-      let mut error = ValidateError::msg_builder();
-      error.insert("Unmatched password");
-      fields.insert("confirm_password", error.build());
-    }
+    validation::validate_new_password(&mut fields, &self.password, &self.confirm_password);
 
     fields.build().into_result()
   }
@@ -0,0 +1,54 @@
+use crate::{types::validation, util::Sensitive};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidateError};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Request {
+  pub token: Sensitive<String>,
+  pub password: Sensitive<String>,
+  pub confirm_password: Sensitive<String>,
+}
+
+impl Validate for Request {
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    if self.token.is_empty() || self.token.len() > 128 {
+      let mut error = ValidateError::msg_builder();
+      error.insert("Invalid or expired password reset token");
+      fields.insert("token", error.build());
+    }
+
+    validation::validate_new_password(&mut fields, &self.password, &self.confirm_password);
+
+    fields.build().into_result()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate() {
+    let form = Request {
+      token: "abc".into(),
+      password: "correct horse battery staple".into(),
+      confirm_password: "correct horse battery staple".into(),
+    };
+    assert!(form.validate().is_ok());
+
+    let form = Request {
+      token: "".into(),
+      password: "correct horse battery staple".into(),
+      confirm_password: "correct horse battery staple".into(),
+    };
+    assert!(form.validate().is_err());
+
+    let form = Request {
+      token: "abc".into(),
+      password: "too_short".into(),
+      confirm_password: "too_short".into(),
+    };
+    assert!(form.validate().is_err());
+  }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  schema,
+  types::{
+    id::{marker::SessionMarker, Id},
+    Timestamp,
+  },
+};
+
+/// A session listed in `GET /users/@me/sessions`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Session {
+  pub id: Id<SessionMarker>,
+  pub created_at: Timestamp,
+  pub last_used_at: Timestamp,
+  pub expires_at: Timestamp,
+  pub user_agent: Option<String>,
+  pub ip_address: Option<String>,
+  /// Whether the request is made from this session.
+  pub current: bool,
+}
+
+impl Session {
+  #[must_use]
+  pub fn new(session: schema::Session, current: bool) -> Self {
+    Self {
+      id: session.id,
+      created_at: session.created_at,
+      last_used_at: session.last_used_at,
+      expires_at: session.expires_at,
+      user_agent: session.user_agent,
+      ip_address: session.ip_address,
+      current,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Response {
+  pub sessions: Vec<Session>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RevokeAllResponse {
+  pub revoked: u64,
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use validator::{Validate, ValidateError};
+
+use crate::{
+  schema,
+  types::{
+    id::{marker::ApiTokenMarker, Id},
+    Scope, Timestamp,
+  },
+  util::Sensitive,
+};
+
+/// An API token listed in `GET /users/@me/tokens`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ApiToken {
+  pub id: Id<ApiTokenMarker>,
+  pub name: String,
+  /// The visible part of the token.
+  pub prefix: String,
+  pub scopes: BTreeSet<Scope>,
+  pub created_at: Timestamp,
+  pub expires_at: Option<Timestamp>,
+  pub last_used_at: Option<Timestamp>,
+}
+
+impl ApiToken {
+  #[must_use]
+  pub fn new(token: schema::ApiToken) -> Self {
+    Self {
+      id: token.id,
+      name: token.name,
+      prefix: token.prefix,
+      scopes: token.scopes.iter().filter_map(|v| v.parse().ok()).collect(),
+      created_at: token.created_at,
+      expires_at: token.expires_at,
+      last_used_at: token.last_used_at,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListResponse {
+  pub tokens: Vec<ApiToken>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateRequest {
+  #[validate(length(min = 1, max = 64))]
+  pub name: String,
+  #[validate(length(min = 1))]
+  pub scopes: BTreeSet<Scope>,
+  /// How long (in seconds) the token is valid.
+  /// It never expires if it is not set.
+  #[serde(default)]
+  pub expires_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateResponse {
+  /// The raw token. It is shown only once, the server
+  /// only keeps its hash.
+  pub token: Sensitive<String>,
+  #[serde(flatten)]
+  pub info: ApiToken,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateRequest {
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub scopes: Option<BTreeSet<Scope>>,
+}
+
+impl Validate for UpdateRequest {
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    if let Some(name) = self.name.as_deref() {
+      if name.is_empty() || name.chars().count() > 64 {
+        let mut error = ValidateError::msg_builder();
+        error.insert("Name must be between 1 and 64 characters");
+        fields.insert("name", error.build());
+      }
+    }
+    if self.scopes.as_ref().is_some_and(BTreeSet::is_empty) {
+      let mut error = ValidateError::msg_builder();
+      error.insert("At least one scope is required");
+      fields.insert("scopes", error.build());
+    }
+    fields.build().into_result()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_create_request() {
+    let form: CreateRequest =
+      serde_json::from_str(r#"{"name":"bot","scopes":["read:profile","read:profile"]}"#)
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(form.scopes.len(), 1);
+    assert!(form.validate().is_ok());
+
+    let form: CreateRequest =
+      serde_json::from_str(r#"{"name":"bot","scopes":[]}"#).unwrap_or_else(|e| panic!("{e}"));
+    assert!(form.validate().is_err());
+
+    assert!(serde_json::from_str::<CreateRequest>(r#"{"name":"bot","scopes":["admin"]}"#).is_err());
+  }
+
+  #[test]
+  fn test_update_request() {
+    let form = UpdateRequest {
+      name: None,
+      scopes: None,
+    };
+    assert!(form.validate().is_ok());
+
+    let form = UpdateRequest {
+      name: Some(String::new()),
+      scopes: Some(BTreeSet::new()),
+    };
+    assert!(form.validate().is_err());
+  }
+}
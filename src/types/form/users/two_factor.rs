@@ -0,0 +1,38 @@
+use crate::util::Sensitive;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EnrollResponse {
+  /// Base32 encoded TOTP secret for entering it manually.
+  pub secret: Sensitive<String>,
+  /// `otpauth://` URI to be rendered as a QR code.
+  pub uri: Sensitive<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ConfirmRequest {
+  #[validate(length(min = 1, max = 32))]
+  pub code: Sensitive<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConfirmResponse {
+  /// Shown only once, the server only keeps their hashes.
+  pub recovery_codes: Vec<Sensitive<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct DisableRequest {
+  #[validate(length(min = 1, max = 128))]
+  pub password: Sensitive<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct LoginRequest {
+  #[validate(length(min = 1, max = 1024))]
+  pub challenge_token: Sensitive<String>,
+  /// Either a TOTP code or an unused recovery code.
+  #[validate(length(min = 1, max = 32))]
+  pub code: Sensitive<String>,
+}
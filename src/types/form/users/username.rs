@@ -0,0 +1,58 @@
+use crate::{types::validation::is_valid_username, util::Sensitive};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidateError};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChangeRequest {
+  pub username: Sensitive<String>,
+  /// Required because legacy password hashes are salted with
+  /// the user's name and must be rehashed after renaming.
+  pub password: Sensitive<String>,
+}
+
+impl Validate for ChangeRequest {
+  fn validate(&self) -> Result<(), ValidateError> {
+    let mut fields = ValidateError::field_builder();
+    fields.insert("username", {
+      let mut error = ValidateError::msg_builder();
+      if !is_valid_username(&self.username) {
+        error.insert("Invalid username");
+      }
+      error.build()
+    });
+    fields.insert("password", {
+      let mut error = ValidateError::msg_builder();
+      if self.password.is_empty() || self.password.len() > 128 {
+        error.insert("Invalid password");
+      }
+      error.build()
+    });
+    fields.build().into_result()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate() {
+    let form = ChangeRequest {
+      username: "memothelemo".to_string().into(),
+      password: "correct horse battery staple".to_string().into(),
+    };
+    assert!(form.validate().is_ok());
+
+    let form = ChangeRequest {
+      username: "memo the lemo".to_string().into(),
+      password: "correct horse battery staple".to_string().into(),
+    };
+    assert!(form.validate().is_err());
+
+    let form = ChangeRequest {
+      username: "memothelemo".to_string().into(),
+      password: String::new().into(),
+    };
+    assert!(form.validate().is_err());
+  }
+}
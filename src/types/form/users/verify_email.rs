@@ -0,0 +1,15 @@
+use crate::util::Sensitive;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct Request {
+  #[validate(length(min = 1, max = 128))]
+  pub token: Sensitive<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ResendRequest {
+  #[validate(length(min = 1, max = 255))]
+  pub email: Sensitive<String>,
+}
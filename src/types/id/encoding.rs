@@ -0,0 +1,76 @@
+//! Compact string encodings of IDs for shareable URLs.
+//!
+//! Encoded IDs are zero padded to a fixed width and their alphabets
+//! start with letters, so the first character is always a letter
+//! (IDs never reach the 11th base62 or 13th base36 digit). It makes
+//! them distinguishable from decimal IDs which only have digits.
+
+pub const BASE62_ALPHABET: &[u8; 62] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+pub const BASE62_WIDTH: usize = 11;
+
+pub const BASE36_ALPHABET: &[u8; 36] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+pub const BASE36_WIDTH: usize = 13;
+
+pub fn encode(mut value: u64, alphabet: &[u8], width: usize) -> String {
+  let base = alphabet.len() as u64;
+  let mut output = vec![alphabet[0]; width];
+  for slot in output.iter_mut().rev() {
+    #[allow(clippy::cast_possible_truncation)]
+    let digit = (value % base) as usize;
+    *slot = alphabet[digit];
+    value /= base;
+  }
+  // Alphabets are ASCII only
+  output.into_iter().map(char::from).collect()
+}
+
+pub fn decode(input: &str, alphabet: &[u8], width: usize) -> Option<u64> {
+  if input.len() != width {
+    return None;
+  }
+
+  let base = alphabet.len() as u64;
+  input.bytes().try_fold(0u64, |value, c| {
+    let digit = alphabet.iter().position(|v| *v == c)? as u64;
+    value.checked_mul(base)?.checked_add(digit)
+  })
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_roundtrip() {
+    for value in [1, 62, 1_234_567_890, i64::MAX as u64, u64::MAX] {
+      let encoded = encode(value, BASE62_ALPHABET, BASE62_WIDTH);
+      assert_eq!(encoded.len(), BASE62_WIDTH);
+      assert_eq!(decode(&encoded, BASE62_ALPHABET, BASE62_WIDTH), Some(value));
+
+      let encoded = encode(value, BASE36_ALPHABET, BASE36_WIDTH);
+      assert_eq!(encoded.len(), BASE36_WIDTH);
+      assert_eq!(decode(&encoded, BASE36_ALPHABET, BASE36_WIDTH), Some(value));
+    }
+  }
+
+  #[test]
+  fn test_starts_with_letter() {
+    let value = i64::MAX as u64;
+    let encoded = encode(value, BASE62_ALPHABET, BASE62_WIDTH);
+    assert!(encoded.as_bytes()[0].is_ascii_alphabetic());
+
+    let encoded = encode(value, BASE36_ALPHABET, BASE36_WIDTH);
+    assert!(encoded.as_bytes()[0].is_ascii_alphabetic());
+  }
+
+  #[test]
+  fn test_decode_invalid() {
+    assert_eq!(decode("AAAAAAAAAA", BASE62_ALPHABET, BASE62_WIDTH), None);
+    assert_eq!(decode("AAAAAAAAAA-", BASE62_ALPHABET, BASE62_WIDTH), None);
+    // overflow
+    assert_eq!(decode("zzzzzzzzzzz", BASE62_ALPHABET, BASE62_WIDTH), None);
+    assert_eq!(decode("AAAAAAAAAAAAA", BASE36_ALPHABET, BASE36_WIDTH), None);
+  }
+}
@@ -0,0 +1,199 @@
+use chrono::Utc;
+use std::{
+  num::NonZeroU64,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+use super::{marker::Marker, Id};
+use crate::types::timestamp::{EPOCH, TIMESTAMP_BITS_LEN};
+
+/// How many bits of an ID are reserved for the worker ID.
+pub const WORKER_ID_BITS: usize = 10;
+/// How many bits of an ID are reserved for the sequence number.
+pub const SEQUENCE_BITS: usize = 63 - TIMESTAMP_BITS_LEN - WORKER_ID_BITS;
+
+/// The largest worker ID that can be assigned to a process.
+///
+/// The last worker ID is reserved for IDs generated by the
+/// database with the `whim_next_id()` function.
+pub const MAX_WORKER_ID: u16 = (1 << WORKER_ID_BITS) - 2;
+/// Worker ID used by the `whim_next_id()` database function.
+pub const DATABASE_WORKER_ID: u16 = MAX_WORKER_ID + 1;
+
+const SEQUENCE_MASK: u64 = (1 << SEQUENCE_BITS) - 1;
+const TIMESTAMP_SHIFT: usize = WORKER_ID_BITS + SEQUENCE_BITS;
+
+/// Generates unique snowflake IDs whose timestamps can
+/// be read back with [`Id::timestamp`].
+///
+/// An ID is made of (from the most significant bit):
+/// - 1 unused bit to keep IDs positive as a Postgres `bigint`.
+/// - 43 bits of milliseconds since the Whim epoch.
+/// - 10 bits of the worker ID, unique for every running process.
+/// - 10 bits of a sequence number incremented within a millisecond.
+///
+/// IDs generated by the same generator are strictly increasing. If
+/// the sequence runs out within a millisecond or the system clock
+/// goes backwards, it borrows the next millisecond instead of waiting.
+#[derive(Debug)]
+pub struct IdGenerator {
+  worker_id: u16,
+  last: AtomicU64,
+}
+
+impl IdGenerator {
+  /// # Panics
+  ///
+  /// It will panic if the worker ID is greater than [`MAX_WORKER_ID`].
+  #[must_use]
+  pub fn new(worker_id: u16) -> Self {
+    assert!(
+      worker_id <= MAX_WORKER_ID,
+      "worker ID must not be greater than {MAX_WORKER_ID}"
+    );
+    Self {
+      worker_id,
+      last: AtomicU64::new(0),
+    }
+  }
+
+  #[must_use]
+  pub const fn worker_id(&self) -> u16 {
+    self.worker_id
+  }
+
+  /// Generates a new ID.
+  #[must_use]
+  pub fn generate<T: Marker>(&self) -> Id<T> {
+    let now = u64::try_from(Utc::now().timestamp_millis()).unwrap_or_default();
+    self.generate_at(now)
+  }
+
+  /// Generates a new ID as if the current time is `now`
+  /// (in UNIX milliseconds).
+  #[must_use]
+  pub fn generate_at<T: Marker>(&self, now: u64) -> Id<T> {
+    let worker = u64::from(self.worker_id) << SEQUENCE_BITS;
+    let base = (now.saturating_sub(EPOCH) << TIMESTAMP_SHIFT) | worker;
+
+    let mut last = self.last.load(Ordering::Acquire);
+    loop {
+      let next = if base > last {
+        base
+      } else if last & SEQUENCE_MASK < SEQUENCE_MASK {
+        last + 1
+      } else {
+        (((last >> TIMESTAMP_SHIFT) + 1) << TIMESTAMP_SHIFT) | worker
+      };
+
+      match self
+        .last
+        .compare_exchange_weak(last, next, Ordering::AcqRel, Ordering::Acquire)
+      {
+        // `next` is greater than `last` which is at least 0
+        Ok(..) => return Id::from_nonzero(NonZeroU64::new(next).unwrap_or(NonZeroU64::MIN)),
+        Err(actual) => last = actual,
+      }
+    }
+  }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::id::marker::AnyMarker;
+  use proptest::prelude::*;
+
+  const WHIM_EPOCH: u64 = EPOCH;
+  // Timestamps are 43 bits long
+  const MAX_MILLIS: u64 = (1 << TIMESTAMP_BITS_LEN) - 2;
+
+  fn worker_of(id: Id<AnyMarker>) -> u64 {
+    (id.get() >> SEQUENCE_BITS) & ((1 << WORKER_ID_BITS) - 1)
+  }
+
+  proptest! {
+    #[test]
+    fn test_monotonic(worker_id in 0..=MAX_WORKER_ID, mut times in proptest::collection::vec(0..MAX_MILLIS, 1..200)) {
+      times.sort_unstable();
+      let generator = IdGenerator::new(worker_id);
+
+      let mut last = 0;
+      for offset in times {
+        let id: Id<AnyMarker> = generator.generate_at(WHIM_EPOCH + offset);
+        prop_assert!(id.get() > last);
+        prop_assert_eq!(worker_of(id), u64::from(worker_id));
+        last = id.get();
+      }
+    }
+
+    #[test]
+    fn test_monotonic_with_clock_going_backwards(times in proptest::collection::vec(0..MAX_MILLIS, 1..200)) {
+      let generator = IdGenerator::new(1);
+      let mut last = 0;
+      for offset in times {
+        let id: Id<AnyMarker> = generator.generate_at(WHIM_EPOCH + offset);
+        prop_assert!(id.get() > last);
+        last = id.get();
+      }
+    }
+
+    #[test]
+    fn test_timestamp_roundtrip(worker_id in 0..=MAX_WORKER_ID, offset in 0..MAX_MILLIS) {
+      let generator = IdGenerator::new(worker_id);
+      let id: Id<AnyMarker> = generator.generate_at(WHIM_EPOCH + offset);
+      let expected = i64::try_from(WHIM_EPOCH + offset).unwrap();
+      prop_assert_eq!(id.timestamp().timestamp_millis(), expected);
+    }
+  }
+
+  #[test]
+  fn test_sequence_overflow() {
+    let generator = IdGenerator::new(MAX_WORKER_ID);
+    let now = WHIM_EPOCH + 1000;
+
+    let ids = (0..=SEQUENCE_MASK + 1)
+      .map(|_| generator.generate_at::<AnyMarker>(now).get())
+      .collect::<Vec<_>>();
+
+    assert!(ids.windows(2).all(|v| v[0] < v[1]));
+    // The last one borrows the next millisecond
+    let last = Id::<AnyMarker>::new(*ids.last().unwrap());
+    assert_eq!(
+      last.timestamp().timestamp_millis(),
+      i64::try_from(now).unwrap() + 1
+    );
+    assert_eq!(worker_of(last), u64::from(MAX_WORKER_ID));
+  }
+
+  #[test]
+  fn test_concurrent() {
+    let generator = std::sync::Arc::new(IdGenerator::new(3));
+    let handles = (0..4)
+      .map(|_| {
+        let generator = generator.clone();
+        std::thread::spawn(move || {
+          (0..1000)
+            .map(|_| generator.generate::<AnyMarker>().get())
+            .collect::<Vec<_>>()
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let mut ids = handles
+      .into_iter()
+      .flat_map(|v| v.join().unwrap())
+      .collect::<Vec<_>>();
+    let total = ids.len();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), total);
+  }
+
+  #[test]
+  #[should_panic(expected = "worker ID must not be greater than")]
+  fn test_invalid_worker_id() {
+    drop(IdGenerator::new(DATABASE_WORKER_ID));
+  }
+}
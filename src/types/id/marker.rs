@@ -11,6 +11,10 @@ macro_rules! markers {
 
 markers! {
   AnyMarker,
+  ApiTokenMarker,
+  DataExportMarker,
+  PostMarker,
+  SessionMarker,
   UserMarker,
 }
 
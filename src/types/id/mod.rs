@@ -5,14 +5,19 @@ use std::{
   hash::Hash,
   marker::PhantomData,
   num::NonZeroU64,
+  str::FromStr,
 };
 use thiserror::Error;
 
 use self::marker::Marker;
 use crate::types::Timestamp;
 
+mod encoding;
+mod generator;
 pub mod marker;
 
+pub use generator::{IdGenerator, DATABASE_WORKER_ID, MAX_WORKER_ID};
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Id<T: Marker> {
   value: NonZeroU64,
@@ -77,6 +82,52 @@ impl<T: Marker> Id<T> {
   pub fn timestamp(self) -> Timestamp {
     Timestamp::from_snowflake(self.value)
   }
+
+  /// Encodes this ID into an 11 characters long base62 string.
+  ///
+  /// Unlike decimal IDs, it always starts with a letter
+  /// so [`Id::from_str`] can tell them apart.
+  #[must_use]
+  pub fn to_base62(self) -> String {
+    encoding::encode(
+      self.value.get(),
+      encoding::BASE62_ALPHABET,
+      encoding::BASE62_WIDTH,
+    )
+  }
+
+  /// Encodes this ID into a 13 characters long lowercase base36 string
+  /// for places where letter case is not preserved.
+  #[must_use]
+  pub fn to_base36(self) -> String {
+    encoding::encode(
+      self.value.get(),
+      encoding::BASE36_ALPHABET,
+      encoding::BASE36_WIDTH,
+    )
+  }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid ID")]
+pub struct ParseIdError;
+
+impl<T: Marker> FromStr for Id<T> {
+  type Err = ParseIdError;
+
+  /// Parses an ID from either its decimal, base62
+  /// ([`Id::to_base62`]) or base36 ([`Id::to_base36`]) form.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let value = if s.bytes().all(|c| c.is_ascii_digit()) {
+      s.parse().ok()
+    } else if s.len() == encoding::BASE62_WIDTH {
+      encoding::decode(s, encoding::BASE62_ALPHABET, encoding::BASE62_WIDTH)
+    } else {
+      encoding::decode(s, encoding::BASE36_ALPHABET, encoding::BASE36_WIDTH)
+    };
+
+    value.and_then(Self::new_checked).ok_or(ParseIdError)
+  }
 }
 
 impl<T: Marker> Debug for Id<T> {
@@ -152,12 +203,10 @@ impl<'de, T: Marker> serde::Deserialize<'de> for Id<T> {
       where
         E: DeError,
       {
-        let value = v.parse().map_err(|_| {
+        v.parse().map_err(|_| {
           let unexpected = Unexpected::Str(v);
-          DeError::invalid_value(unexpected, &"nonzero u64 string")
-        })?;
-
-        self.visit_u64(value)
+          DeError::invalid_value(unexpected, &"nonzero u64, base62 or base36 string")
+        })
       }
     }
 
@@ -286,5 +335,33 @@ mod tests {
     serde_test::assert_de_tokens(&id, &[Token::Str("1234567890")]);
     serde_test::assert_de_tokens(&id, &[Token::I64(1234567890)]);
     serde_test::assert_ser_tokens(&id, &[Token::Str("1234567890")]);
+
+    // path segments are deserialized from strings
+    let value = serde_json::Value::String(id.to_base62());
+    assert_eq!(
+      serde_json::from_value::<Id<AnyMarker>>(value).ok(),
+      Some(id)
+    );
+  }
+
+  #[test]
+  fn test_from_str() {
+    let id = Id::<AnyMarker>::new(1_234_567_890);
+    assert_eq!("1234567890".parse(), Ok(id));
+    assert_eq!(id.to_base62().parse(), Ok(id));
+    assert_eq!(id.to_base36().parse(), Ok(id));
+
+    // Base62 IDs are case-sensitive unlike base36 IDs
+    assert_eq!(id.to_base62(), "AAAAABViHfU");
+    assert_ne!(
+      id.to_base62().to_lowercase().parse::<Id<AnyMarker>>(),
+      Ok(id)
+    );
+
+    assert_eq!("0".parse::<Id<AnyMarker>>(), Err(ParseIdError));
+    assert_eq!("".parse::<Id<AnyMarker>>(), Err(ParseIdError));
+    assert_eq!("-1".parse::<Id<AnyMarker>>(), Err(ParseIdError));
+    assert_eq!("hello".parse::<Id<AnyMarker>>(), Err(ParseIdError));
+    assert_eq!("AAAAAAAAAAA".parse::<Id<AnyMarker>>(), Err(ParseIdError));
   }
 }
@@ -1,8 +1,11 @@
 pub mod error;
 pub mod form;
 pub mod id;
+pub mod scope;
 pub mod timestamp;
+pub mod username;
 pub mod validation;
 
 pub use error::Error;
+pub use scope::Scope;
 pub use timestamp::Timestamp;
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+/// A permission which can be granted to an API token.
+///
+/// Requests authenticated with a JWT are allowed to
+/// access everything the user can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Scope {
+  #[serde(rename = "read:profile")]
+  ReadProfile,
+  #[serde(rename = "write:profile")]
+  WriteProfile,
+  #[serde(rename = "read:posts")]
+  ReadPosts,
+  #[serde(rename = "write:posts")]
+  WritePosts,
+}
+
+impl Scope {
+  pub const ALL: &'static [Scope] = &[
+    Self::ReadProfile,
+    Self::WriteProfile,
+    Self::ReadPosts,
+    Self::WritePosts,
+  ];
+
+  #[must_use]
+  pub const fn as_str(self) -> &'static str {
+    match self {
+      Self::ReadProfile => "read:profile",
+      Self::WriteProfile => "write:profile",
+      Self::ReadPosts => "read:posts",
+      Self::WritePosts => "write:posts",
+    }
+  }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unknown scope")]
+pub struct UnknownScope;
+
+impl FromStr for Scope {
+  type Err = UnknownScope;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::ALL
+      .iter()
+      .find(|v| v.as_str() == s)
+      .copied()
+      .ok_or(UnknownScope)
+  }
+}
+
+impl Display for Scope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_test::Token;
+
+  #[test]
+  fn test_roundtrip() {
+    for scope in Scope::ALL {
+      assert_eq!(scope.as_str().parse::<Scope>(), Ok(*scope));
+      serde_test::assert_tokens(
+        scope,
+        &[Token::UnitVariant {
+          name: "Scope",
+          variant: scope.as_str(),
+        }],
+      );
+    }
+    assert_eq!("admin".parse::<Scope>(), Err(UnknownScope));
+  }
+}
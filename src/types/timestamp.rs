@@ -1,20 +1,50 @@
 use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
-use std::{fmt::Display, hash::Hash, num::NonZeroU64, ops::Deref, str::FromStr};
+use std::{fmt::Display, hash::Hash, num::NonZeroU64, ops::Deref, str::FromStr, time::Duration};
 use thiserror::Error;
 
 // Whim epoch starts at November 18, 2023 at 07:52:48 AM in Manila time
-const EPOCH: u64 = 1700265168293;
-const TIMESTAMP_BITS_LEN: usize = 43; // It should last up to 278 years
+pub(crate) const EPOCH: u64 = 1700265168293;
+pub(crate) const TIMESTAMP_BITS_LEN: usize = 43; // It should last up to 278 years
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Timestamp(DateTime<Utc>);
 
 impl Timestamp {
+  pub const MIN: Self = Self(DateTime::<Utc>::MIN_UTC);
+  pub const MAX: Self = Self(DateTime::<Utc>::MAX_UTC);
+
   #[must_use]
   pub fn now() -> Self {
     Self(Utc::now())
   }
 
+  /// Gets the point in time `duration` after this timestamp. It
+  /// returns [`Timestamp::MAX`] if it is out of range.
+  #[must_use]
+  pub fn saturating_add(self, duration: Duration) -> Self {
+    chrono::Duration::from_std(duration)
+      .ok()
+      .and_then(|v| self.0.checked_add_signed(v))
+      .map_or(Self::MAX, Self)
+  }
+
+  /// Gets the point in time `duration` before this timestamp. It
+  /// returns [`Timestamp::MIN`] if it is out of range.
+  #[must_use]
+  pub fn saturating_sub(self, duration: Duration) -> Self {
+    chrono::Duration::from_std(duration)
+      .ok()
+      .and_then(|v| self.0.checked_sub_signed(v))
+      .map_or(Self::MIN, Self)
+  }
+
+  /// How much time has elapsed from `earlier` to this timestamp.
+  /// It returns `None` if `earlier` is later than this timestamp.
+  #[must_use]
+  pub fn duration_since(self, earlier: Self) -> Option<Duration> {
+    (self.0 - earlier.0).to_std().ok()
+  }
+
   pub fn from_timestamp(secs: i64) -> Result<Self, InvalidTimestamp> {
     let dt = NaiveDateTime::from_timestamp_opt(secs, 0).ok_or(InvalidTimestamp)?;
     Ok(Self(DateTime::from_naive_utc_and_offset(dt, Utc)))
@@ -57,6 +87,12 @@ impl Display for Timestamp {
   }
 }
 
+impl From<Timestamp> for DateTime<Utc> {
+  fn from(value: Timestamp) -> Self {
+    value.0
+  }
+}
+
 impl Deref for Timestamp {
   type Target = DateTime<Utc>;
 
@@ -115,6 +151,29 @@ impl serde::Serialize for Timestamp {
   }
 }
 
+impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Timestamp {
+  fn encode_by_ref(
+    &self,
+    buf: &mut <sqlx::Postgres as sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+  ) -> sqlx::encode::IsNull {
+    <DateTime<Utc> as sqlx::Encode<'q, sqlx::Postgres>>::encode_by_ref(&self.0, buf)
+  }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Timestamp {
+  fn decode(
+    value: <sqlx::Postgres as sqlx::database::HasValueRef<'r>>::ValueRef,
+  ) -> Result<Self, sqlx::error::BoxDynError> {
+    <DateTime<Utc> as sqlx::Decode<'r, sqlx::Postgres>>::decode(value).map(Self)
+  }
+}
+
+impl sqlx::Type<sqlx::Postgres> for Timestamp {
+  fn type_info() -> <sqlx::Postgres as sqlx::Database>::TypeInfo {
+    <DateTime<Utc> as sqlx::Type<sqlx::Postgres>>::type_info()
+  }
+}
+
 #[derive(Debug, Error)]
 #[error("invalid UNIX timestamp value")]
 pub struct InvalidTimestamp;
@@ -137,10 +196,17 @@ impl Deref for ParseError {
   }
 }
 
+#[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
   use super::*;
   use serde_test::Token;
+  use static_assertions::assert_impl_all;
+
+  assert_impl_all!(Timestamp:
+    Send, Sync, sqlx::Decode<'static, sqlx::Postgres>,
+    sqlx::Encode<'static, sqlx::Postgres>, sqlx::Type<sqlx::Postgres>
+  );
 
   #[test]
   fn test_fmt_display_impl() {
@@ -166,4 +232,21 @@ mod tests {
     );
     assert_eq!("2024-08-14T19:52:48.348Z", timestamp.to_string());
   }
+
+  #[test]
+  fn test_saturating_arithmetic() {
+    let timestamp = Timestamp::from_snowflake(1.try_into().unwrap());
+    let later = timestamp.saturating_add(Duration::from_millis(1500));
+    assert_eq!("2023-11-17T23:52:49.793Z", later.to_string());
+    assert_eq!(later.saturating_sub(Duration::from_millis(1500)), timestamp);
+
+    assert_eq!(
+      later.duration_since(timestamp),
+      Some(Duration::from_millis(1500))
+    );
+    assert_eq!(timestamp.duration_since(later), None);
+
+    assert_eq!(timestamp.saturating_add(Duration::MAX), Timestamp::MAX);
+    assert_eq!(timestamp.saturating_sub(Duration::MAX), Timestamp::MIN);
+  }
 }
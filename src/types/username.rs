@@ -0,0 +1,136 @@
+//! Policies about which usernames can be taken.
+//!
+//! Besides the format checked by [`is_valid_username`], usernames
+//! must not be reserved by the instance and must not be confusable
+//! with an existing username (`paypa1` and `paypal` for example).
+//!
+//! Two usernames are confusable if they have the same [`skeleton`].
+use super::validation::is_valid_username;
+
+/// Reserved usernames by default. Most of them either collide with
+/// routes (`/users/@me`) or can be used to impersonate the staff.
+pub const DEFAULT_RESERVED: &[&str] = &[
+  "about",
+  "admin",
+  "administrator",
+  "api",
+  "auth",
+  "forgot-password",
+  "help",
+  "login",
+  "me",
+  "moderator",
+  "refresh",
+  "register",
+  "reset-password",
+  "root",
+  "settings",
+  "staff",
+  "support",
+  "system",
+  "users",
+  "verify-email",
+  "whim",
+];
+
+/// Why a username cannot be taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+  Invalid,
+  Reserved,
+}
+
+/// Checks whether a username is well-formed and not reserved.
+///
+/// Reserved words are compared by their [`skeleton`], so
+/// `Adm1n` is rejected if `admin` is reserved.
+pub fn check<S: AsRef<str>>(name: &str, reserved: &[S]) -> Result<(), Rejection> {
+  if !is_valid_username(name) {
+    return Err(Rejection::Invalid);
+  }
+
+  let skeleton = skeleton(name);
+  if reserved
+    .iter()
+    .any(|v| self::skeleton(v.as_ref()) == skeleton)
+  {
+    return Err(Rejection::Reserved);
+  }
+
+  Ok(())
+}
+
+/// Reduces a username into a form where visually similar
+/// usernames end up being equal.
+///
+/// It is case-insensitive, ignores separators (`.`, `-` and `_`)
+/// and replaces commonly confused characters, including some
+/// Cyrillic and Greek lookalikes of Latin letters.
+///
+/// Keep the ASCII part in sync with the `name_skeleton` backfill in
+/// the `add_username_skeletons` migration.
+pub fn skeleton(name: &str) -> String {
+  let mapped = name
+    .chars()
+    .flat_map(char::to_lowercase)
+    .filter_map(|c| match c {
+      '.' | '-' | '_' => None,
+      '0' | 'о' | 'ο' => Some('o'),
+      '1' | 'i' | 'і' | 'ι' | 'l' | '|' => Some('l'),
+      '3' | 'е' | 'ε' => Some('e'),
+      '4' | 'а' | 'α' => Some('a'),
+      '5' | 'ѕ' => Some('s'),
+      '7' | 'τ' => Some('t'),
+      '8' | 'β' => Some('b'),
+      'с' => Some('c'),
+      'ԁ' => Some('d'),
+      'ј' => Some('j'),
+      'κ' | 'к' => Some('k'),
+      'р' | 'ρ' => Some('p'),
+      'ν' => Some('v'),
+      'х' | 'χ' => Some('x'),
+      'у' | 'γ' => Some('y'),
+      c => Some(c),
+    })
+    .collect::<String>();
+
+  mapped
+    .replace("rn", "m")
+    .replace("vv", "w")
+    .replace("cl", "d")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_skeleton() {
+    assert_eq!(skeleton("paypal"), skeleton("paypa1"));
+    assert_eq!(skeleton("paypal"), skeleton("PayPal"));
+    assert_eq!(skeleton("paypal"), skeleton("pay.pal"));
+    assert_eq!(skeleton("modern"), skeleton("modem"));
+    assert_eq!(skeleton("wave"), skeleton("vvave"));
+    // Cyrillic `а` and `р`
+    assert_eq!(skeleton("paypal"), skeleton("\u{440}\u{430}ypal"));
+
+    assert_ne!(skeleton("memothelemo"), skeleton("memothelime"));
+  }
+
+  #[test]
+  fn test_check() {
+    assert_eq!(check("memothelemo", DEFAULT_RESERVED), Ok(()));
+    assert_eq!(check("me", DEFAULT_RESERVED), Err(Rejection::Reserved));
+    assert_eq!(check("ME", DEFAULT_RESERVED), Err(Rejection::Reserved));
+    assert_eq!(check("adm1n", DEFAULT_RESERVED), Err(Rejection::Reserved));
+    assert_eq!(
+      check("a_d_m_i_n", DEFAULT_RESERVED),
+      Err(Rejection::Reserved)
+    );
+    assert_eq!(
+      check("pretty ugly", DEFAULT_RESERVED),
+      Err(Rejection::Invalid)
+    );
+    assert_eq!(check::<&str>("me", &[]), Ok(()));
+  }
+}
@@ -1,5 +1,6 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use validator::{FieldBuilder, ValidateError};
 
 #[allow(clippy::expect_used)]
 static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
@@ -34,13 +35,51 @@ pub fn is_valid_email(email: &str) -> bool {
   EMAIL_REGEX.is_match(email) && email.len() <= 254
 }
 
+/// Normalizes an email address before storing it by trimming
+/// whitespaces and lowercasing its domain part.
+///
+/// The local part is kept as is but lookups and uniqueness
+/// are case-insensitive anyway.
+pub fn normalize_email(email: &str) -> String {
+  let email = email.trim();
+  match email.rsplit_once('@') {
+    Some((local, domain)) => format!("{local}@{}", domain.to_ascii_lowercase()),
+    None => email.to_string(),
+  }
+}
+
 pub fn is_valid_username(name: &str) -> bool {
   USERNAME_REGEX.is_match(name) && name.len() <= USERNAME_MAX
 }
 
+/// Validates a new password (when registering or resetting a password)
+/// and inserts its errors into `password` and `confirm_password` fields.
+pub fn validate_new_password(fields: &mut FieldBuilder, password: &str, confirm_password: &str) {
+  // TODO: check for weak passwords
+  fields.insert("password", {
+    // All passwords must have no trailing or leading whitespaces
+    let mut error = ValidateError::msg_builder();
+    if password.len() != password.trim().len() {
+      error.insert("Passwords must not have starting or ending with spaces");
+    } else if password.len() > PASSWORD_MAX {
+      error.insert("Passwords must not be too big");
+    } else if password.len() < PASSWORD_MIN {
+      error.insert("Passwords must not be too short");
+    }
+    error.build()
+  });
+
+  // Not very secure... :(
+  if password != confirm_password {
+    let mut error = ValidateError::msg_builder();
+    error.insert("Unmatched password");
+    fields.insert("confirm_password", error.build());
+  }
+}
+
 #[cfg(test)]
 mod tests {
-  use super::{is_valid_email, is_valid_username};
+  use super::{is_valid_email, is_valid_username, normalize_email};
 
   #[test]
   fn test_is_valid_email() {
@@ -48,6 +87,13 @@ mod tests {
     assert!(!is_valid_email("nada_neutho"));
   }
 
+  #[test]
+  fn test_normalize_email() {
+    assert_eq!(normalize_email(" Gush@GMail.com "), "Gush@gmail.com");
+    assert_eq!(normalize_email("gush@gmail.com"), "gush@gmail.com");
+    assert_eq!(normalize_email("nada_neutho"), "nada_neutho");
+  }
+
   #[test]
   fn test_is_valid_username() {
     assert!(is_valid_username("memothelemo"));
@@ -4,3 +4,12 @@ mod sensitive;
 pub use maybe_generated::*;
 pub use sensitive::Sensitive;
 pub(crate) mod shims;
+
+use std::time::Duration;
+
+use crate::types::Timestamp;
+
+/// Gets the point in time `duration` ago from now.
+pub(crate) fn ago(duration: Duration) -> Timestamp {
+  Timestamp::now().saturating_sub(duration)
+}
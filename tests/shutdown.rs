@@ -0,0 +1,145 @@
+#![allow(clippy::unwrap_used)]
+use actix_web::{web, App, HttpResponse, HttpServer};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpStream,
+  sync::{mpsc, oneshot},
+  task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+use whim::shutdown::{BackgroundTasks, Connections};
+
+const SLOW_REQUEST: Duration = Duration::from_millis(500);
+
+async fn slow(started: web::Data<mpsc::Sender<()>>) -> HttpResponse {
+  started.send(()).await.unwrap();
+  tokio::time::sleep(SLOW_REQUEST).await;
+  HttpResponse::Ok().body("done")
+}
+
+async fn get(addr: SocketAddr, path: &str) -> std::io::Result<String> {
+  let mut stream = TcpStream::connect(addr).await?;
+  let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+  stream.write_all(request.as_bytes()).await?;
+
+  let mut response = String::new();
+  stream.read_to_string(&mut response).await?;
+  Ok(response)
+}
+
+fn start(shutdown_timeout: Duration) -> (SocketAddr, Serving) {
+  let (started_tx, started_rx) = mpsc::channel::<()>(1);
+  let connections = Connections::new();
+  let server = HttpServer::new(move || {
+    App::new()
+      .app_data(web::Data::new(started_tx.clone()))
+      .route("/slow", web::get().to(slow))
+  })
+  .workers(1)
+  .on_connect(connections.on_connect())
+  .shutdown_timeout(shutdown_timeout.as_secs())
+  .disable_signals()
+  .bind(("127.0.0.1", 0))
+  .unwrap();
+
+  let addr = server.addrs()[0];
+  let token = CancellationToken::new();
+  let (signal_tx, signal_rx) = oneshot::channel::<()>();
+  let task = tokio::spawn(whim::shutdown::serve(
+    server.run(),
+    async move {
+      signal_rx.await.ok();
+    },
+    token.clone(),
+    connections,
+    shutdown_timeout,
+  ));
+
+  let serving = Serving {
+    token,
+    started_rx,
+    signal_tx,
+    task,
+  };
+  (addr, serving)
+}
+
+struct Serving {
+  token: CancellationToken,
+  started_rx: mpsc::Receiver<()>,
+  signal_tx: oneshot::Sender<()>,
+  task: JoinHandle<std::io::Result<()>>,
+}
+
+#[tokio::test]
+async fn test_in_flight_requests_complete() {
+  let (addr, mut serving) = start(Duration::from_secs(10));
+  let request = tokio::spawn(get(addr, "/slow"));
+  serving.started_rx.recv().await.unwrap();
+
+  // The request is still being handled while shutting down
+  serving.signal_tx.send(()).unwrap();
+
+  let response = request.await.unwrap().unwrap();
+  assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+  assert!(response.ends_with("done"), "{response}");
+
+  tokio::time::timeout(Duration::from_secs(5), serving.task)
+    .await
+    .unwrap()
+    .unwrap()
+    .unwrap();
+
+  assert!(serving.token.is_cancelled());
+  assert!(get(addr, "/slow").await.is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_timeout() {
+  let (addr, mut serving) = start(SLOW_REQUEST / 5);
+  let request = tokio::spawn(get(addr, "/slow"));
+  serving.started_rx.recv().await.unwrap();
+  serving.signal_tx.send(()).unwrap();
+
+  // The request takes longer than the shutdown timeout
+  tokio::time::timeout(SLOW_REQUEST / 2, serving.task)
+    .await
+    .unwrap()
+    .unwrap()
+    .unwrap();
+
+  let response = request.await.unwrap().unwrap_or_default();
+  assert!(!response.ends_with("done"), "{response}");
+}
+
+#[tokio::test]
+async fn test_background_tasks_finish() {
+  let tasks = BackgroundTasks::new();
+  let (done_tx, mut done_rx) = mpsc::channel::<&str>(2);
+
+  let done = done_tx.clone();
+  tasks.spawn(async move {
+    tokio::time::sleep(SLOW_REQUEST).await;
+    done.send("slow").await.unwrap();
+  });
+
+  // Long running tasks are told to stop early
+  let shutdown = tasks.shutdown_token().clone();
+  tasks.spawn(async move {
+    tokio::select! {
+      () = shutdown.cancelled() => done_tx.send("interrupted").await.unwrap(),
+      () = std::future::pending() => {}
+    }
+  });
+  assert_eq!(tasks.len(), 2);
+
+  tasks.shutdown_token().cancel();
+  tokio::time::timeout(SLOW_REQUEST * 2, tasks.wait())
+    .await
+    .unwrap();
+
+  assert!(tasks.is_empty());
+  assert_eq!(done_rx.recv().await, Some("interrupted"));
+  assert_eq!(done_rx.recv().await, Some("slow"));
+}
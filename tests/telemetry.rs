@@ -0,0 +1,111 @@
+#![allow(clippy::unwrap_used)]
+use actix_web::{test, web, App, HttpResponse};
+use figment::providers::Serialized;
+use std::time::Duration;
+use tokio::{
+  io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+  net::{TcpListener, TcpStream},
+  sync::mpsc,
+};
+use tracing_actix_web::TracingLogger;
+use whim::{config, http::util::QuieterRootSpanBuilder};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+const SERVICE_NAME: &str = "whim-telemetry-test";
+
+/// Stands in for an OpenTelemetry collector by sending
+/// the body of every `POST /v1/traces` request it receives.
+async fn collector(listener: TcpListener, exported: mpsc::UnboundedSender<Vec<u8>>) {
+  loop {
+    let (stream, _) = listener.accept().await.unwrap();
+    tokio::spawn(handle(stream, exported.clone()));
+  }
+}
+
+async fn handle(stream: TcpStream, exported: mpsc::UnboundedSender<Vec<u8>>) {
+  let mut stream = BufReader::new(stream);
+  loop {
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+      return;
+    }
+
+    let mut content_length = 0;
+    loop {
+      let mut line = String::new();
+      stream.read_line(&mut line).await.unwrap();
+      let line = line.trim_end();
+      if line.is_empty() {
+        break;
+      }
+      if let Some((name, value)) = line.split_once(':') {
+        if name.eq_ignore_ascii_case("content-length") {
+          content_length = value.trim().parse().unwrap();
+        }
+      }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await.unwrap();
+    if request_line.starts_with("POST /v1/traces ") {
+      exported.send(body).ok();
+    }
+
+    stream
+      .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+      .await
+      .unwrap();
+  }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+  haystack
+    .windows(needle.len())
+    .any(|window| window == needle)
+}
+
+#[tokio::test]
+async fn test_export_traces() {
+  let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+  let endpoint = format!("http://{}", listener.local_addr().unwrap());
+  let (exported_tx, mut exported_rx) = mpsc::unbounded_channel();
+  tokio::spawn(collector(listener, exported_tx));
+
+  let cfg = figment::Figment::new()
+    .merge(Serialized::default("log_filter", "info"))
+    .merge(Serialized::default("otlp.endpoint", endpoint))
+    .merge(Serialized::default("otlp.protocol", "http"))
+    .merge(Serialized::default("otlp.service_name", SERVICE_NAME))
+    .extract::<config::Telemetry>()
+    .unwrap();
+
+  let telemetry = whim::telemetry::init(&cfg).unwrap();
+
+  let app = test::init_service(
+    App::new()
+      .wrap(TracingLogger::<QuieterRootSpanBuilder>::new())
+      .route("/", web::get().to(HttpResponse::Ok)),
+  )
+  .await;
+
+  let request = test::TestRequest::get()
+    .uri("/")
+    .insert_header(("traceparent", TRACEPARENT))
+    .to_request();
+  let response = test::call_service(&app, request).await;
+  assert!(response.status().is_success());
+
+  // The request span ends once its response is dropped
+  drop(response);
+  telemetry.shutdown().await;
+
+  let body = tokio::time::timeout(Duration::from_secs(10), exported_rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+
+  let trace_id = u128::from_str_radix(TRACE_ID, 16).unwrap().to_be_bytes();
+  assert!(contains(&body, &trace_id));
+  assert!(contains(&body, SERVICE_NAME.as_bytes()));
+}